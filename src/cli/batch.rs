@@ -0,0 +1,76 @@
+use std::path::Path;
+
+use crate::{
+    cli::{self, Arguments, CliError},
+    config_json::ConfigJson,
+    export::{self, ExportOptions},
+};
+
+const OPTIONS: [&str; 3] = ["--config", "--out", "--file-name"];
+
+const FLAGS: [&str; 1] = ["--no-animations"];
+
+pub fn print_usage() {
+    println!("batch: export every model listed in a config file with its saved mesh settings");
+    println!("  --config <file>           config file (default config.json)");
+    println!("  --out <dir>               output directory (default ./export)");
+    println!("  --file-name <template>    file name, supports {{name}} and {{animation}} (default {{name}})");
+    println!("  --no-animations           do not export animations");
+    cli::print_export_usage();
+    println!(
+        "A report is written to <out>/{}. Exit code 1 when any model failed.",
+        export::batch::REPORT_FILE_NAME
+    );
+}
+
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    if args.iter().any(|x| x == "--help" || x == "-h") {
+        print_usage();
+        return Ok(());
+    }
+
+    let arguments = Arguments::parse(
+        args,
+        &[&OPTIONS[..], &cli::EXPORT_OPTIONS[..]].concat(),
+        &[&FLAGS[..], &cli::EXPORT_FLAGS[..]].concat(),
+    )?;
+
+    let mut export_options = ExportOptions::new();
+    cli::apply_export_options(&arguments, &mut export_options)?;
+    if let Some(out) = arguments.value("--out") {
+        export_options.output_dir = out.to_owned();
+    }
+    if let Some(file_name) = arguments.value("--file-name") {
+        export_options.file_name = file_name.to_owned();
+    }
+
+    let config_path = arguments.value("--config").unwrap_or("config.json");
+    let config_json = ConfigJson::try_read(Path::new(config_path))?;
+    if config_json.paths.is_empty() {
+        return Err(CliError::failure(format!("No models listed in {config_path}")));
+    }
+
+    cli::silence_panics();
+
+    let (_, report) = export::batch::export_batch(
+        &export_options,
+        &config_json,
+        !arguments.flag("--no-animations"),
+    )?;
+
+    for model in report.models.iter() {
+        match &model.error {
+            None => println!("  OK     {} ({:.2}s)", model.name, model.load_seconds + model.export_seconds),
+            Some(error) => println!("  FAILED {}: {error}", model.name),
+        }
+    }
+
+    if report.failed > 0 {
+        return Err(CliError::failure(format!(
+            "{} of {} models failed",
+            report.failed,
+            report.models.len()
+        )));
+    }
+    Ok(())
+}
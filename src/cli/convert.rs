@@ -0,0 +1,207 @@
+use std::path::Path;
+
+use crate::{
+    cli::{self, Arguments, CliError},
+    config_json::OptionsJson,
+    export::{self, ExportOptions},
+    gls::imgui_layout::AddModel,
+};
+
+const OPTIONS: [&str; 12] = [
+    "--skn",
+    "--skl",
+    "--textures",
+    "--animations",
+    "--out",
+    "--name",
+    "--texture",
+    "--hide",
+    "--animation",
+    "--pose",
+    "--time",
+    "--speed",
+];
+
+const FLAGS: [&str; 1] = ["--no-animations"];
+
+pub fn print_usage() {
+    println!("convert: load a model without opening a window and export it");
+    println!("  --skn <file>              SKN mesh (required)");
+    println!("  --skl <file>              SKL skeleton (required)");
+    println!("  --textures <dir>          directory with .dds/.tex textures");
+    println!("  --animations <dir>        directory with .anm animations");
+    println!("  --out <file>              output file, the format follows the extension (required)");
+    println!("                            gltf/dae/obj/usda are written into a folder named after the file");
+    println!("  --name <name>             model name, defaults to the SKN file name");
+    println!("  --texture <submesh=name>  texture for a submesh, repeatable (default: first texture)");
+    println!("  --hide <submesh>          hide a submesh, repeatable");
+    println!("  --animation <name>        only export these animations, repeatable");
+    println!("  --no-animations           do not export animations");
+    println!("  --speed <factor>          speed multiplier for exported animations");
+    println!("  --pose <name>             animation used for the obj pose");
+    println!("  --time <seconds>          time of the obj pose");
+    cli::print_export_usage();
+    println!("Exit codes: 0 success, 1 load or export failed, 2 invalid arguments");
+}
+
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    if args.iter().any(|x| x == "--help" || x == "-h") {
+        print_usage();
+        return Ok(());
+    }
+
+    let arguments = Arguments::parse(
+        args,
+        &[&OPTIONS[..], &cli::EXPORT_OPTIONS[..]].concat(),
+        &[&FLAGS[..], &cli::EXPORT_FLAGS[..]].concat(),
+    )?;
+
+    let skn = arguments.required("--skn")?;
+    let skl = arguments.required("--skl")?;
+    let out = Path::new(arguments.required("--out")?);
+
+    let file_name = out
+        .file_stem()
+        .and_then(|x| x.to_str())
+        .filter(|x| !x.is_empty())
+        .ok_or_else(|| CliError::usage(format!("Invalid output file: {}", out.display())))?;
+    let output_dir = match out.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => std::env::current_dir()
+            .map_err(|error| CliError::failure(format!("Could not get current dir: {error}")))?,
+    };
+
+    let mut export_options = ExportOptions::new();
+    // 未指定 --format 时由输出文件的扩展名决定
+    if arguments.value("--format").is_none() {
+        let extension = out
+            .extension()
+            .and_then(|x| x.to_str())
+            .ok_or_else(|| CliError::usage("--out has no extension, pass --format"))?;
+        export_options.export_as = cli::choice("--out extension", extension, &cli::FORMATS)? as u8;
+    }
+    cli::apply_export_options(&arguments, &mut export_options)?;
+    export_options.output_dir = output_dir.to_string_lossy().into_owned();
+    export_options.file_name = file_name.to_owned();
+
+    let model_name = match arguments.value("--name") {
+        Some(name) => name.to_owned(),
+        None => Path::new(skn)
+            .file_stem()
+            .and_then(|x| x.to_str())
+            .unwrap_or("model")
+            .to_owned(),
+    };
+
+    cli::silence_panics();
+
+    let add_model = AddModel {
+        name: model_name.clone(),
+        skin: skn.to_owned(),
+        skeleton: skl.to_owned(),
+        textures: arguments.value("--textures").unwrap_or("").to_owned(),
+        animations: arguments.value("--animations").unwrap_or("").to_owned(),
+        ..AddModel::new()
+    };
+    for (argument, path) in [
+        ("--textures", &add_model.textures),
+        ("--animations", &add_model.animations),
+    ] {
+        if !path.is_empty() && !Path::new(path).is_dir() {
+            return Err(CliError::failure(format!("{argument} is not a directory: {path}")));
+        }
+    }
+
+    let mut mind_model = crate::read_mind_model(&add_model, None)?;
+    println!(
+        "Loaded {} ({} submeshes, {} joints, {} textures, {} animations)",
+        model_name,
+        mind_model.skin.meshes.len(),
+        mind_model.skeleton.joints.len(),
+        mind_model.textures_paths.len(),
+        mind_model.animations.len()
+    );
+
+    for texture in arguments.values("--texture") {
+        let (submesh, texture_name) = texture
+            .split_once('=')
+            .ok_or_else(|| CliError::usage(format!("--texture expects submesh=texture, got {texture}")))?;
+        let mesh_index = submesh_index(&mind_model.skin, submesh)?;
+        let texture_index = mind_model
+            .textures_file_names
+            .iter()
+            .position(|x| x == texture_name)
+            .ok_or_else(|| CliError::failure(format!("Texture not found: {texture_name}")))?;
+        mind_model.textures_selecteds[mesh_index] = texture_index;
+    }
+
+    for submesh in arguments.values("--hide") {
+        let mesh_index = submesh_index(&mind_model.skin, submesh)?;
+        mind_model.show_meshes[mesh_index] = false;
+    }
+
+    let mut options = OptionsJson::new();
+
+    let selected_animations = arguments.values("--animation");
+    for name in selected_animations.iter() {
+        if !mind_model.animations_file_names.iter().any(|x| x == name) {
+            return Err(CliError::failure(format!("Animation not found: {name}")));
+        }
+    }
+    let speed = arguments.parsed::<f32>("--speed")?.unwrap_or(1.0f32);
+    if speed <= 0.0f32 {
+        return Err(CliError::usage("--speed must be greater than 0"));
+    }
+    for i in 0..mind_model.animations.len() {
+        let name = mind_model.animations_file_names[i].clone();
+        let animation_export = options.export_animation_mut(&name, mind_model.animations[i].duration);
+        animation_export.export = selected_animations.is_empty() || selected_animations.contains(&name.as_str());
+        animation_export.speed = speed;
+    }
+
+    if let Some(pose) = arguments.value("--pose") {
+        mind_model.animation_selected = mind_model
+            .animations_file_names
+            .iter()
+            .position(|x| x == pose)
+            .ok_or_else(|| CliError::failure(format!("Animation not found: {pose}")))?;
+        options.use_animation = true;
+    }
+    if let Some(time) = arguments.parsed::<f32>("--time")? {
+        options.animation_time = time.max(0.0f32);
+        options.use_animation = !mind_model.animations.is_empty();
+    }
+    if let Some(animation) = mind_model.animations_file_names.get(mind_model.animation_selected) {
+        options.selected_animation_path = animation.clone();
+    }
+
+    let export_animations = !arguments.flag("--no-animations");
+    let export_path = export::catch_panic(|| {
+        export::export_model(
+            &export_options,
+            &model_name,
+            &mind_model,
+            export_animations,
+            &options,
+        )
+    })?;
+
+    println!("Exported {} to {}", model_name, export_path.display());
+    Ok(())
+}
+
+fn submesh_index(skin: &crate::lol::Skin, submesh: &str) -> Result<usize, CliError> {
+    skin.meshes
+        .iter()
+        .position(|mesh| mesh.submesh.name == submesh)
+        .ok_or_else(|| {
+            CliError::failure(format!(
+                "Submesh not found: {submesh} (available: {})",
+                skin.meshes
+                    .iter()
+                    .map(|mesh| mesh.submesh.name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ))
+        })
+}
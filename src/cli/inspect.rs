@@ -0,0 +1,361 @@
+use std::{io::Cursor, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    cli::{self, Arguments, CliError},
+    gls::texture::{self, CompressedSource},
+    lol::{skl::Type, Animation, Skeleton, Skin},
+};
+
+const FLAGS: [&str; 1] = ["--compact"];
+
+pub fn print_usage() {
+    println!("inspect: print the metadata of SKN/SKL/ANM/TEX/DDS files as JSON");
+    println!("  inspect <file>... [--compact]");
+    println!("  several files are printed as a JSON array");
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind")]
+enum FileInfo {
+    #[serde(rename = "skn")]
+    Skin(SkinInfo),
+    #[serde(rename = "skl")]
+    Skeleton(SkeletonInfo),
+    #[serde(rename = "anm")]
+    Animation(AnimationInfo),
+    #[serde(rename = "texture")]
+    Texture(TextureInfo),
+}
+
+#[derive(Serialize)]
+struct SkinInfo {
+    path: String,
+    major: u16,
+    minor: u16,
+    vertex_count: usize,
+    index_count: usize,
+    center: [f32; 3],
+    bounding_box: [[f32; 3]; 2],
+    submeshes: Vec<SubmeshInfo>,
+}
+
+#[derive(Serialize)]
+struct SubmeshInfo {
+    name: String,
+    hash: u32,
+    index_offset: u32,
+    index_count: u32,
+    triangle_count: u32,
+    vertex_count: usize,
+    bounding_box: Option<[[f32; 3]; 2]>,
+}
+
+#[derive(Serialize)]
+struct SkeletonInfo {
+    path: String,
+    #[serde(rename = "type")]
+    stype: &'static str,
+    version: u32,
+    joint_count: usize,
+    joints: Vec<JointInfo>,
+    influences: Vec<InfluenceInfo>,
+}
+
+#[derive(Serialize)]
+struct JointInfo {
+    id: i16,
+    name: String,
+    hash: u32,
+    children: Vec<JointInfo>,
+}
+
+#[derive(Serialize)]
+struct InfluenceInfo {
+    joint: u16,
+    name: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AnimationInfo {
+    path: String,
+    format: String,
+    fps: f32,
+    duration: f32,
+    frame_count: usize,
+    track_count: usize,
+    tracks: Vec<TrackInfo>,
+}
+
+#[derive(Serialize)]
+struct TrackInfo {
+    hash: u32,
+    translation_keys: usize,
+    rotation_keys: usize,
+    scale_keys: usize,
+}
+
+#[derive(Serialize)]
+struct TextureInfo {
+    path: String,
+    container: &'static str,
+    format: String,
+    width: usize,
+    height: usize,
+    mipmap_count: usize,
+    mipmaps: Vec<MipmapInfo>,
+}
+
+#[derive(Serialize)]
+struct MipmapInfo {
+    width: usize,
+    height: usize,
+    size: usize,
+}
+
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    if args.iter().any(|x| x == "--help" || x == "-h") {
+        print_usage();
+        return Ok(());
+    }
+
+    let (flags, paths): (Vec<String>, Vec<String>) =
+        args.iter().cloned().partition(|x| x.starts_with("--"));
+    let arguments = Arguments::parse(&flags, &[], &FLAGS)?;
+    if paths.is_empty() {
+        return Err(CliError::usage("inspect expects at least one file"));
+    }
+
+    cli::silence_panics();
+
+    let mut infos = Vec::with_capacity(paths.len());
+    for path in paths.iter() {
+        infos.push(inspect_file(Path::new(path))?);
+    }
+
+    let json = if infos.len() == 1 {
+        serde_json::to_value(&infos[0])
+    } else {
+        serde_json::to_value(&infos)
+    }
+    .map_err(|error| CliError::failure(format!("Could not serialize JSON: {error}")))?;
+
+    let output = if arguments.flag("--compact") {
+        serde_json::to_string(&json)
+    } else {
+        serde_json::to_string_pretty(&json)
+    }
+    .map_err(|error| CliError::failure(format!("Could not serialize JSON: {error}")))?;
+
+    println!("{output}");
+    Ok(())
+}
+
+fn inspect_file(path: &Path) -> Result<FileInfo, CliError> {
+    let contents =
+        std::fs::read(path).map_err(|error| format!("Could not read {}: {error}", path.display()))?;
+    let display = path.display().to_string();
+
+    let extension = path
+        .extension()
+        .and_then(|x| x.to_str())
+        .map(|x| x.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let kind = match extension.as_str() {
+        "skn" | "skl" | "anm" | "tex" | "dds" => extension,
+        _ => detect_kind(&contents)
+            .ok_or_else(|| CliError::failure(format!("Unsupported file: {display}")))?
+            .to_owned(),
+    };
+
+    Ok(match kind.as_str() {
+        "skn" => FileInfo::Skin(skin_info(display, &crate::parse_file(path, Skin::read)?)),
+        "skl" => FileInfo::Skeleton(skeleton_info(display, &crate::parse_file(path, Skeleton::read)?)),
+        "anm" => FileInfo::Animation(animation_info(display, &crate::parse_file(path, Animation::read)?)),
+        _ => {
+            let source = crate::parse_file(path, |contents| {
+                texture::load_compressed_source(&mut Cursor::new(contents.as_slice()))
+            })?;
+            let container = if contents.starts_with(b"DDS ") { "dds" } else { "tex" };
+            FileInfo::Texture(texture_info(display, container, &source))
+        }
+    })
+}
+
+// 没有扩展名时根据文件头判断类型
+fn detect_kind(contents: &[u8]) -> Option<&'static str> {
+    if contents.starts_with(&[0x33, 0x22, 0x11, 0x00]) {
+        Some("skn")
+    } else if contents.starts_with(b"r3d2") {
+        Some("anm")
+    } else if contents.starts_with(b"DDS ") {
+        Some("dds")
+    } else if contents.starts_with(b"TEX\0") {
+        Some("tex")
+    } else if contents.len() >= 8
+        && matches!(
+            u32::from_le_bytes([contents[4], contents[5], contents[6], contents[7]]),
+            0x746C6B73 | 0x22FD4FC3
+        )
+    {
+        Some("skl")
+    } else {
+        None
+    }
+}
+
+fn skin_info(path: String, skin: &Skin) -> SkinInfo {
+    let submeshes = skin
+        .meshes
+        .iter()
+        .map(|mesh| {
+            let indices_offset = mesh.submesh.indices_offset as usize;
+            let indices_count = mesh.submesh.indices_count as usize;
+            let indices = skin
+                .indices
+                .get(indices_offset..indices_offset + indices_count)
+                .unwrap_or(&[]);
+
+            let mut vertices: Vec<u16> = indices.to_vec();
+            vertices.sort_unstable();
+            vertices.dedup();
+
+            let bounding_box = vertices
+                .iter()
+                .filter_map(|index| skin.vertices.get(*index as usize))
+                .fold(None, |bounds: Option<[glam::Vec3; 2]>, vertex| match bounds {
+                    Some([min, max]) => Some([min.min(*vertex), max.max(*vertex)]),
+                    None => Some([*vertex, *vertex]),
+                })
+                .map(|[min, max]| [min.to_array(), max.to_array()]);
+
+            SubmeshInfo {
+                name: mesh.submesh.name.clone(),
+                hash: mesh.hash,
+                index_offset: mesh.submesh.indices_offset,
+                index_count: mesh.submesh.indices_count,
+                triangle_count: mesh.submesh.indices_count / 3,
+                vertex_count: vertices.len(),
+                bounding_box,
+            }
+        })
+        .collect();
+
+    SkinInfo {
+        path,
+        major: skin.major,
+        minor: skin.minor,
+        vertex_count: skin.vertices.len(),
+        index_count: skin.indices.len(),
+        center: skin.center.to_array(),
+        bounding_box: [
+            skin.bounding_box[0].to_array(),
+            skin.bounding_box[1].to_array(),
+        ],
+        submeshes,
+    }
+}
+
+fn skeleton_info(path: String, skeleton: &Skeleton) -> SkeletonInfo {
+    let joints = skeleton
+        .joints
+        .iter()
+        .enumerate()
+        .filter(|(_, joint)| joint.parent_id < 0 || joint.parent_id as usize >= skeleton.joints.len())
+        .map(|(joint_index, _)| joint_tree(skeleton, joint_index, 0))
+        .collect();
+
+    let influences = skeleton
+        .influences
+        .iter()
+        .map(|joint| InfluenceInfo {
+            joint: *joint,
+            name: skeleton
+                .joints
+                .get(*joint as usize)
+                .map(|joint| joint.name.clone()),
+        })
+        .collect();
+
+    SkeletonInfo {
+        path,
+        stype: match skeleton.stype {
+            Type::Classic => "Classic",
+            Type::Version2 => "Version2",
+        },
+        version: skeleton.version,
+        joint_count: skeleton.joints.len(),
+        joints,
+        influences,
+    }
+}
+
+// 递归深度以关节数量为上限，防止损坏的父子关系造成死循环
+fn joint_tree(skeleton: &Skeleton, joint_index: usize, depth: usize) -> JointInfo {
+    let joint = &skeleton.joints[joint_index];
+    let children = if depth < skeleton.joints.len() {
+        joint
+            .children
+            .iter()
+            .filter(|child| **child < skeleton.joints.len())
+            .map(|child| joint_tree(skeleton, *child, depth + 1))
+            .collect()
+    } else {
+        vec![]
+    };
+
+    JointInfo {
+        id: joint.id,
+        name: joint.name.clone(),
+        hash: joint.hash,
+        children,
+    }
+}
+
+fn animation_info(path: String, animation: &Animation) -> AnimationInfo {
+    let tracks: Vec<TrackInfo> = animation
+        .joints
+        .iter()
+        .map(|joint| TrackInfo {
+            hash: joint.hash,
+            translation_keys: joint.translations.len(),
+            rotation_keys: joint.rotations.len(),
+            scale_keys: joint.scales.len(),
+        })
+        .collect();
+
+    AnimationInfo {
+        path,
+        format: animation.format.clone(),
+        fps: animation.fps,
+        duration: animation.duration,
+        frame_count: (animation.duration * animation.fps).round() as usize,
+        track_count: tracks.len(),
+        tracks,
+    }
+}
+
+fn texture_info(path: String, container: &'static str, source: &CompressedSource) -> TextureInfo {
+    let mipmaps = source
+        .mipmaps
+        .iter()
+        .enumerate()
+        .map(|(level, mipmap)| MipmapInfo {
+            width: 1.max(source.width >> level),
+            height: 1.max(source.height >> level),
+            size: mipmap.len(),
+        })
+        .collect();
+
+    TextureInfo {
+        path,
+        container,
+        format: format!("{:?}", source.format),
+        width: source.width,
+        height: source.height,
+        mipmap_count: source.mipmaps.len(),
+        mipmaps,
+    }
+}
@@ -0,0 +1,247 @@
+use std::{collections::BTreeSet, panic, str::FromStr};
+
+use crate::export::{self, ExportOptions};
+
+pub mod batch;
+pub mod convert;
+pub mod inspect;
+pub mod preset;
+pub mod render;
+pub mod viewer;
+
+// 命令行子命令，全部在创建 GLFW 窗口之前执行，不需要 GL 上下文。
+// 返回 None 表示不是子命令，继续启动查看器
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = args.get(1)?;
+    let command_args = &args[2..];
+
+    let result = match command.as_str() {
+        "convert" => convert::run(command_args),
+        "batch" => batch::run(command_args),
+        "inspect" => inspect::run(command_args),
+        "render" => render::run(command_args),
+        "preset" => preset::run(command_args),
+        "help" | "--help" | "-h" => {
+            print_usage();
+            Ok(())
+        }
+        _ => return None,
+    };
+
+    Some(match result {
+        Ok(()) => 0,
+        Err(error) => report(error),
+    })
+}
+
+// 打印错误并返回退出码
+pub fn report(error: CliError) -> i32 {
+    eprintln!("Error: {}", error.message);
+    if error.code == EXIT_USAGE {
+        eprintln!("Run with --help for usage.");
+    }
+    error.code
+}
+
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+pub struct CliError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl CliError {
+    pub fn usage(message: impl Into<String>) -> CliError {
+        CliError {
+            code: EXIT_USAGE,
+            message: message.into(),
+        }
+    }
+
+    pub fn failure(message: impl Into<String>) -> CliError {
+        CliError {
+            code: EXIT_FAILURE,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> CliError {
+        CliError::failure(message)
+    }
+}
+
+pub fn print_usage() {
+    println!("Usage:");
+    println!("  mindcorpviewer-rust [--config <config.json>] [--skn <file> ...] [viewer options]");
+    println!("  mindcorpviewer-rust convert --skn <file> --skl <file> --out <file> [options]");
+    println!("  mindcorpviewer-rust batch [--config <config.json>] [--out <dir>] [options]");
+    println!("  mindcorpviewer-rust inspect <file>... [--compact]");
+    println!("  mindcorpviewer-rust render --out <image> [--config <config.json> | --skn <file>] [options]");
+    println!("  mindcorpviewer-rust preset export|import [--config <config.json>] [options]");
+    println!();
+    viewer::print_usage();
+    println!();
+    convert::print_usage();
+    println!();
+    batch::print_usage();
+    println!();
+    inspect::print_usage();
+    println!();
+    render::print_usage();
+    println!();
+    preset::print_usage();
+}
+
+// convert 和 batch 共用的导出参数
+pub const EXPORT_OPTIONS: [&str; 5] = [
+    "--format",
+    "--texture-format",
+    "--unit-scale",
+    "--up-axis",
+    "--conflict",
+];
+
+pub const EXPORT_FLAGS: [&str; 3] = ["--all-meshes", "--flip-handedness", "--open-folder"];
+
+// 与 ExportOptions 中的索引一一对应
+pub const FORMATS: [&str; 5] = ["gltf", "glb", "dae", "obj", "usda"];
+pub const TEXTURE_FORMATS: [&str; 2] = ["png", "dds"];
+pub const CONFLICT_MODES: [&str; 3] = ["overwrite", "suffix", "abort"];
+
+pub fn print_export_usage() {
+    println!("  --format <format>         {}", FORMATS.join(" | "));
+    println!("  --texture-format <format> {} (gltf/glb only)", TEXTURE_FORMATS.join(" | "));
+    println!("  --unit-scale <scale>      e.g. 0.01 to convert game units to meters");
+    println!("  --up-axis <axis>          y | z");
+    println!("  --flip-handedness         mirror the X axis");
+    println!("  --conflict <mode>         {} (default overwrite)", CONFLICT_MODES.join(" | "));
+    println!("  --all-meshes              also export hidden submeshes");
+    println!("  --open-folder             open the output folder when done");
+}
+
+// 命令行不打开输出目录，除非指定 --open-folder
+pub fn apply_export_options(arguments: &Arguments, export_options: &mut ExportOptions) -> Result<(), CliError> {
+    if let Some(format) = arguments.value("--format") {
+        export_options.export_as = choice("--format", format, &FORMATS)? as u8;
+    }
+    if let Some(texture_format) = arguments.value("--texture-format") {
+        export_options.texture_format = choice("--texture-format", texture_format, &TEXTURE_FORMATS)?;
+    }
+    if let Some(up_axis) = arguments.value("--up-axis") {
+        export_options.up_axis = choice("--up-axis", up_axis, &export::UP_AXES)?;
+    }
+    if let Some(conflict) = arguments.value("--conflict") {
+        export_options.conflict = choice("--conflict", conflict, &CONFLICT_MODES)?;
+    }
+    if let Some(unit_scale) = arguments.parsed::<f32>("--unit-scale")? {
+        if unit_scale <= 0.0f32 {
+            return Err(CliError::usage("--unit-scale must be greater than 0"));
+        }
+        export_options.unit_scale = unit_scale;
+    }
+    export_options.open_folder = arguments.flag("--open-folder");
+    export_options.only_visible_meshes = !arguments.flag("--all-meshes");
+    export_options.flip_handedness = arguments.flag("--flip-handedness");
+    Ok(())
+}
+
+// 解析器在读取损坏的文件时会 panic，命令行模式下不打印 panic 信息，只返回错误
+pub fn silence_panics() {
+    panic::set_hook(Box::new(|_| {}));
+}
+
+// 简单的 --name value / --name=value / --flag 参数解析
+pub struct Arguments {
+    values: Vec<(String, String)>,
+    flags: BTreeSet<String>,
+}
+
+impl Arguments {
+    pub fn parse(args: &[String], options: &[&str], flags: &[&str]) -> Result<Arguments, CliError> {
+        let mut arguments = Arguments {
+            values: vec![],
+            flags: BTreeSet::new(),
+        };
+
+        let mut i = 0;
+        while i < args.len() {
+            let arg = &args[i];
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name, Some(value.to_owned())),
+                _ => (arg.as_str(), None),
+            };
+
+            if options.contains(&name) {
+                let value = match inline_value {
+                    Some(value) => value,
+                    None => {
+                        i += 1;
+                        args.get(i)
+                            .cloned()
+                            .ok_or_else(|| CliError::usage(format!("Missing value for {name}")))?
+                    }
+                };
+                arguments.values.push((name.to_owned(), value));
+            } else if flags.contains(&name) && inline_value.is_none() {
+                arguments.flags.insert(name.to_owned());
+            } else {
+                return Err(CliError::usage(format!("Unknown argument: {arg}")));
+            }
+            i += 1;
+        }
+
+        Ok(arguments)
+    }
+
+    // 重复出现时取最后一个
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(x, _)| x == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn values(&self, name: &str) -> Vec<&str> {
+        self.values
+            .iter()
+            .filter(|(x, _)| x == name)
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    pub fn required(&self, name: &str) -> Result<&str, CliError> {
+        self.value(name)
+            .ok_or_else(|| CliError::usage(format!("Missing required argument {name}")))
+    }
+
+    pub fn parsed<T: FromStr>(&self, name: &str) -> Result<Option<T>, CliError> {
+        match self.value(name) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| CliError::usage(format!("Invalid value for {name}: {value}"))),
+            None => Ok(None),
+        }
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+}
+
+// 在可选项列表中按名称(不区分大小写)查找索引
+pub fn choice(name: &str, value: &str, choices: &[&str]) -> Result<usize, CliError> {
+    choices
+        .iter()
+        .position(|x| x.eq_ignore_ascii_case(value))
+        .ok_or_else(|| {
+            CliError::usage(format!(
+                "Invalid value for {name}: {value} (expected one of {})",
+                choices.join(", ")
+            ))
+        })
+}
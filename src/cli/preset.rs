@@ -0,0 +1,114 @@
+use std::path::Path;
+
+use crate::{
+    cli::{self, Arguments, CliError},
+    config_json::{ConfigJson, OptionsJson},
+    preset::PresetJson,
+    MindModel,
+};
+
+const OPTIONS: [&str; 4] = ["--config", "--model", "--preset", "--out"];
+
+const FLAGS: [&str; 0] = [];
+
+pub fn print_usage() {
+    println!("preset export: save one model of a config file as a preset");
+    println!("  --model <name|index>      model to export (required)");
+    println!("  --out <file>              preset file to write (required)");
+    println!("  --config <file>           config file (default config.json)");
+    println!("preset import: apply a preset to a config file and report how it matched");
+    println!("  --preset <file>           preset or config file to import (required)");
+    println!("  --model <name|index>      apply to this model (default: add the preset as a new model)");
+    println!("  --config <file>           config file to update (default config.json)");
+    println!("  --out <file>              write the result here instead of the config file");
+    println!("Submeshes are matched by name; unmatched submeshes, missing textures and");
+    println!("unused entries are listed.");
+}
+
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    if args.iter().any(|x| x == "--help" || x == "-h") {
+        print_usage();
+        return Ok(());
+    }
+
+    let (command, args) = args
+        .split_first()
+        .ok_or_else(|| CliError::usage("Missing preset command (export or import)"))?;
+    let arguments = Arguments::parse(args, &OPTIONS, &FLAGS)?;
+
+    let config_path = arguments.value("--config").unwrap_or("config.json");
+    let mut config_json = if Path::new(config_path).is_file() || arguments.value("--config").is_some() {
+        ConfigJson::try_read(Path::new(config_path))?
+    } else {
+        ConfigJson::new()
+    };
+
+    cli::silence_panics();
+
+    match command.as_str() {
+        "export" => {
+            let out = Path::new(arguments.required("--out")?);
+            let index = find_model(&config_json, arguments.required("--model")?)?;
+            let mind_model = crate::read_config_model(&config_json, index)?;
+
+            let preset = PresetJson::from_model(&config_json.paths[index], &config_json.options[index], &mind_model);
+            preset.write(out)?;
+            Ok(())
+        }
+        "import" => {
+            let preset = PresetJson::read(Path::new(arguments.required("--preset")?))?;
+
+            let mut mind_models: Vec<MindModel> = Vec::with_capacity(config_json.paths.len());
+            for i in 0..config_json.paths.len() {
+                mind_models.push(crate::read_config_model(&config_json, i)?);
+            }
+
+            let index = match arguments.value("--model") {
+                Some(model) => {
+                    let index = find_model(&config_json, model)?;
+                    if preset.changes_files(&config_json.paths[index]) {
+                        config_json.paths[index] = preset.path.clone();
+                        mind_models[index] = crate::read_config_model(&config_json, index)?;
+                    }
+                    index
+                }
+                None => {
+                    let add_model = preset.add_model();
+                    if add_model.skin.is_empty() || add_model.skeleton.is_empty() {
+                        return Err(CliError::failure(
+                            "The preset has no skin or skeleton, use --model to apply it to an existing model",
+                        ));
+                    }
+                    mind_models.push(crate::read_mind_model(&add_model, None)?);
+                    config_json.paths.push(preset.path.clone());
+                    config_json.options.push(OptionsJson::new());
+                    config_json.meshes.push(vec![]);
+                    config_json.paths.len() - 1
+                }
+            };
+
+            let report = preset.apply(&mut config_json.options[index], &mut mind_models[index]);
+            println!("Applied preset to {}", config_json.paths[index].name);
+            for line in report.lines() {
+                println!("  {line}");
+            }
+
+            let out = Path::new(arguments.value("--out").unwrap_or(config_path));
+            config_json.write(out, &mind_models)?;
+            Ok(())
+        }
+        _ => Err(CliError::usage(format!("Unknown preset command: {command} (expected export or import)"))),
+    }
+}
+
+// 按名称或索引查找模型
+fn find_model(config_json: &ConfigJson, model: &str) -> Result<usize, CliError> {
+    if let Some(index) = config_json.paths.iter().position(|x| x.name == model) {
+        return Ok(index);
+    }
+    model
+        .parse::<usize>()
+        .ok()
+        .filter(|x| *x < config_json.paths.len())
+        .ok_or_else(|| CliError::failure(format!("Model not found: {model}")))
+}
@@ -0,0 +1,178 @@
+use std::path::Path;
+
+use crate::{
+    cli::{self, Arguments, CliError},
+    config_json::{ConfigJson, OptionsJson},
+    g3d::{camera, rasterizer},
+    gls::imgui_layout::AddModel,
+    MindModel,
+};
+
+const OPTIONS: [&str; 14] = [
+    "--config",
+    "--skn",
+    "--skl",
+    "--textures",
+    "--animations",
+    "--out",
+    "--animation",
+    "--time",
+    "--size",
+    "--yaw",
+    "--pitch",
+    "--zoom",
+    "--rotation",
+    "--offset",
+];
+
+const FLAGS: [&str; 1] = ["--bind-pose"];
+
+pub fn print_usage() {
+    println!("render: draw a thumbnail on the CPU, no window or GPU needed");
+    println!("  --out <file>              output image (png/jpg/bmp/tiff, required)");
+    println!("  --config <file>           render the models of this config file (default config.json)");
+    println!("  --skn <file>              render this model instead of the config file");
+    println!("  --skl <file>              skeleton (default: <skn name>.skl)");
+    println!("  --textures <dir>          textures (default: ./textures next to the skn)");
+    println!("  --animations <dir>        animations (default: ./animations next to the skn)");
+    println!("  --animation <name>        pose every model with this animation");
+    println!("  --time <seconds>          animation time");
+    println!("  --bind-pose               ignore the animation settings of the config file");
+    println!("  --size <WxH>              image size (default: screenshot resolution of the config file)");
+    println!("  --yaw <degrees>           camera yaw (default {})", camera::YAW_PITCH[0]);
+    println!("  --pitch <degrees>         camera pitch (default {})", camera::YAW_PITCH[1]);
+    println!("  --zoom <distance>         camera distance (default {})", camera::ZOOM);
+    println!("  --rotation <x,y,z>        model rotation in degrees (--skn only)");
+    println!("  --offset <x,y,z>          model position offset (--skn only)");
+    println!("The framing matches the viewer's initial camera and screenshots.");
+}
+
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    if args.iter().any(|x| x == "--help" || x == "-h") {
+        print_usage();
+        return Ok(());
+    }
+
+    let arguments = Arguments::parse(args, &OPTIONS, &FLAGS)?;
+
+    let out = Path::new(arguments.required("--out")?);
+    let extension = out
+        .extension()
+        .and_then(|x| x.to_str())
+        .map(|x| x.to_ascii_lowercase())
+        .unwrap_or_default();
+    if !["png", "jpg", "jpeg", "bmp", "tif", "tiff"].contains(&extension.as_str()) {
+        return Err(CliError::usage(format!("Unsupported image format: {}", out.display())));
+    }
+
+    let config_path = arguments.value("--config").unwrap_or("config.json");
+    let mut config_json = if Path::new(config_path).is_file() || arguments.value("--config").is_some() {
+        ConfigJson::try_read(Path::new(config_path))?
+    } else {
+        ConfigJson::new()
+    };
+
+    cli::silence_panics();
+
+    let mut mind_models: Vec<MindModel> = vec![];
+
+    if let Some(skn) = arguments.value("--skn") {
+        let mut add_model = AddModel::from_skin(Path::new(skn));
+        if let Some(skl) = arguments.value("--skl") {
+            add_model.skeleton = skl.to_owned();
+        }
+        if let Some(textures) = arguments.value("--textures") {
+            add_model.textures = textures.to_owned();
+        }
+        if let Some(animations) = arguments.value("--animations") {
+            add_model.animations = animations.to_owned();
+        }
+        if add_model.skeleton.is_empty() {
+            return Err(CliError::usage(format!("Missing --skl for model {skn}")));
+        }
+
+        let mut options = OptionsJson::new();
+        if let Some(rotation) = arguments.value("--rotation") {
+            options.rotation_angles = parse_vec3("--rotation", rotation)?;
+        }
+        if let Some(offset) = arguments.value("--offset") {
+            options.position_offset = parse_vec3("--offset", offset)?;
+        }
+
+        mind_models.push(crate::read_mind_model(&add_model, None)?);
+        config_json.options = vec![options];
+    } else {
+        if config_json.paths.is_empty() {
+            return Err(CliError::failure(format!("No models listed in {config_path}")));
+        }
+        for i in 0..config_json.paths.len() {
+            mind_models.push(crate::read_config_model(&config_json, i)?);
+        }
+    }
+
+    let animation = arguments.value("--animation");
+    let time = arguments.parsed::<f32>("--time")?;
+    for (mind_model, options) in mind_models.iter_mut().zip(config_json.options.iter_mut()) {
+        if arguments.flag("--bind-pose") {
+            options.use_animation = false;
+        }
+        if let Some(animation) = animation {
+            mind_model.animation_selected = mind_model
+                .animations_file_names
+                .iter()
+                .position(|x| x == animation)
+                .ok_or_else(|| CliError::failure(format!("Animation not found: {animation}")))?;
+            options.use_animation = true;
+        }
+        if let Some(time) = time {
+            options.animation_time = time.max(0.0f32);
+            options.use_animation = true;
+        }
+    }
+
+    let [width, height] = match arguments.value("--size") {
+        Some(size) => parse_size(size)?,
+        None => config_json.screen_shot_resolution,
+    };
+    if width <= 0 || height <= 0 {
+        return Err(CliError::usage(format!("Invalid image size: {width}x{height}")));
+    }
+
+    let yaw_pitch = glam::vec2(
+        arguments.parsed::<f32>("--yaw")?.unwrap_or(camera::YAW_PITCH[0]),
+        arguments.parsed::<f32>("--pitch")?.unwrap_or(camera::YAW_PITCH[1]),
+    );
+    let zoom = arguments.parsed::<f32>("--zoom")?.unwrap_or(camera::ZOOM);
+    let (view_matrix, _) = camera::view_matrix(camera::initial_translation(&mind_models), yaw_pitch, zoom);
+    let projection_view_matrix = camera::projection_matrix(width as f32, height as f32) * view_matrix;
+
+    let mut framebuffer = rasterizer::Framebuffer::new(width as usize, height as usize);
+    rasterizer::render_models(
+        &mut framebuffer,
+        &mind_models,
+        &config_json.options,
+        &projection_view_matrix,
+    )?;
+    framebuffer.save(out)?;
+
+    println!("Rendered {}x{} image to {}", width, height, out.display());
+    Ok(())
+}
+
+fn parse_size(value: &str) -> Result<[i32; 2], CliError> {
+    let invalid = || CliError::usage(format!("Invalid value for --size: {value} (expected WxH)"));
+    let (width, height) = value.split_once(['x', 'X']).ok_or_else(invalid)?;
+    Ok([
+        width.trim().parse().map_err(|_| invalid())?,
+        height.trim().parse().map_err(|_| invalid())?,
+    ])
+}
+
+fn parse_vec3(argument: &str, value: &str) -> Result<[f32; 3], CliError> {
+    let invalid = || CliError::usage(format!("Invalid value for {argument}: {value} (expected x,y,z)"));
+    let values = value
+        .split(',')
+        .map(|x| x.trim().parse::<f32>().map_err(|_| invalid()))
+        .collect::<Result<Vec<f32>, CliError>>()?;
+    values.try_into().map_err(|_| invalid())
+}
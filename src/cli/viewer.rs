@@ -0,0 +1,254 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    cli::CliError,
+    config_json::{ConfigJson, OptionsJson},
+    gls::imgui_layout::AddModel,
+};
+
+// 启动查看器时的命令行参数
+pub struct ViewerArgs {
+    pub config_path: String,
+    pub models: Vec<ViewerModel>,
+    // 在添加任何模型之前给出的 --animation/--time 作用于配置文件中的所有模型
+    pub animation: Option<String>,
+    pub time: Option<f32>,
+    pub window_size: Option<[i32; 2]>,
+    // Some(0) 表示关闭 MSAA
+    pub msaa: Option<u32>,
+    pub vsync: Option<bool>,
+    pub screenshot: Option<PathBuf>,
+    pub screenshot_size: Option<[i32; 2]>,
+    pub exit: bool,
+}
+
+pub struct ViewerModel {
+    pub add_model: AddModel,
+    pub animation: Option<String>,
+    pub time: Option<f32>,
+}
+
+pub fn print_usage() {
+    println!("viewer: options for launching the window");
+    println!("  --config <file>           config file (default config.json)");
+    println!("  --skn <file>              add a model, repeatable; replaces the models of the config file");
+    println!("                            and starts an untitled scene, so Save asks for a file");
+    println!("                            a bare .skn path works too");
+    println!("  --skl <file>              skeleton of the last model (default: <skn name>.skl)");
+    println!("  --textures <dir>          textures of the last model (default: ./textures next to the skn)");
+    println!("  --animations <dir>        animations of the last model (default: ./animations next to the skn)");
+    println!("  --name <name>             name of the last model");
+    println!("  --animation <name>        initial animation of the last model, or of every model before any --skn");
+    println!("  --time <seconds>          initial animation time, same scope as --animation");
+    println!("  --window-size <WxH>       window size, e.g. 1280x720");
+    println!("  --msaa <samples>          MSAA samples, 0 disables it");
+    println!("  --vsync <on|off>          vertical sync");
+    println!("  --screenshot <file>       render one frame to this image (png/jpg/bmp/tiff/exr)");
+    println!("  --screenshot-size <WxH>   screenshot resolution (default from the config file)");
+    println!("  --exit                    quit after the first frame");
+}
+
+pub fn parse(args: &[String]) -> Result<ViewerArgs, CliError> {
+    if args.iter().any(|x| x == "--help" || x == "-h") {
+        super::print_usage();
+        std::process::exit(0);
+    }
+
+    let mut viewer_args = ViewerArgs {
+        config_path: String::from("config.json"),
+        models: vec![],
+        animation: None,
+        time: None,
+        window_size: None,
+        msaa: None,
+        vsync: None,
+        screenshot: None,
+        screenshot_size: None,
+        exit: false,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+
+        if arg == "--exit" {
+            viewer_args.exit = true;
+            i += 1;
+            continue;
+        }
+
+        // 文件管理器直接传入的 skn 路径
+        if !arg.starts_with("--") {
+            if arg.to_ascii_lowercase().ends_with(".skn") {
+                viewer_args.models.push(viewer_model(arg));
+                i += 1;
+                continue;
+            }
+            return Err(CliError::usage(format!("Unknown argument: {arg}")));
+        }
+
+        let value = args
+            .get(i + 1)
+            .map(String::as_str)
+            .ok_or_else(|| CliError::usage(format!("Missing value for {arg}")))?;
+
+        match arg {
+            "--config" => viewer_args.config_path = value.to_owned(),
+            "--skn" => viewer_args.models.push(viewer_model(value)),
+            "--skl" => last_model(&mut viewer_args, arg)?.add_model.skeleton = value.to_owned(),
+            "--textures" => last_model(&mut viewer_args, arg)?.add_model.textures = value.to_owned(),
+            "--animations" => last_model(&mut viewer_args, arg)?.add_model.animations = value.to_owned(),
+            "--name" => last_model(&mut viewer_args, arg)?.add_model.name = value.to_owned(),
+            "--animation" => match viewer_args.models.last_mut() {
+                Some(model) => model.animation = Some(value.to_owned()),
+                None => viewer_args.animation = Some(value.to_owned()),
+            },
+            "--time" => {
+                let time = parse_value::<f32>(arg, value)?.max(0.0f32);
+                match viewer_args.models.last_mut() {
+                    Some(model) => model.time = Some(time),
+                    None => viewer_args.time = Some(time),
+                }
+            }
+            "--window-size" => viewer_args.window_size = Some(parse_size(arg, value)?),
+            "--msaa" => viewer_args.msaa = Some(parse_value(arg, value)?),
+            "--vsync" => {
+                viewer_args.vsync = Some(match value.to_ascii_lowercase().as_str() {
+                    "on" | "true" | "1" => true,
+                    "off" | "false" | "0" => false,
+                    _ => return Err(CliError::usage(format!("Invalid value for {arg}: {value}"))),
+                })
+            }
+            "--screenshot" => viewer_args.screenshot = Some(PathBuf::from(value)),
+            "--screenshot-size" => viewer_args.screenshot_size = Some(parse_size(arg, value)?),
+            _ => return Err(CliError::usage(format!("Unknown argument: {arg}"))),
+        }
+        i += 2;
+    }
+
+    for model in viewer_args.models.iter() {
+        for (argument, path) in [
+            ("--skn", &model.add_model.skin),
+            ("--skl", &model.add_model.skeleton),
+        ] {
+            if path.is_empty() {
+                return Err(CliError::usage(format!(
+                    "Missing {argument} for model {}",
+                    model.add_model.skin
+                )));
+            }
+            if !Path::new(path).is_file() {
+                return Err(CliError::failure(format!("File not found: {path}")));
+            }
+        }
+    }
+
+    if let Some(screenshot) = &viewer_args.screenshot {
+        let extension = screenshot
+            .extension()
+            .and_then(|x| x.to_str())
+            .map(|x| x.to_ascii_lowercase())
+            .unwrap_or_default();
+        if !["png", "jpg", "jpeg", "bmp", "tif", "tiff", "exr"].contains(&extension.as_str()) {
+            return Err(CliError::usage(format!(
+                "Unsupported screenshot format: {}",
+                screenshot.display()
+            )));
+        }
+    }
+
+    Ok(viewer_args)
+}
+
+impl ViewerArgs {
+    // --skn 替换了配置文件中的模型，此时场景未命名，保存时不会覆盖配置文件
+    pub fn scene_path(&self) -> Option<PathBuf> {
+        if self.models.is_empty() {
+            Some(PathBuf::from(&self.config_path))
+        } else {
+            None
+        }
+    }
+
+    // 把命令行参数写入读取到的配置
+    pub fn apply(&self, config_json: &mut ConfigJson) {
+        if let Some(msaa) = self.msaa {
+            config_json.msaa = if msaa == 0 { None } else { Some(msaa) };
+        }
+        if let Some(vsync) = self.vsync {
+            config_json.vsync = vsync;
+        }
+        if let Some(screenshot_size) = self.screenshot_size {
+            config_json.screen_shot_resolution = screenshot_size;
+        }
+
+        if !self.models.is_empty() {
+            config_json.paths.clear();
+            config_json.options.clear();
+            config_json.meshes.clear();
+
+            for model in self.models.iter() {
+                config_json.paths.push(model.add_model.path_json());
+
+                let mut options = OptionsJson::new();
+                apply_animation(&mut options, model.animation.as_ref(), model.time);
+                config_json.options.push(options);
+                config_json.meshes.push(vec![]);
+            }
+        } else {
+            for options in config_json.options.iter_mut() {
+                apply_animation(options, self.animation.as_ref(), self.time);
+            }
+        }
+
+        // 截图模式下暂停动画，保证截到的是指定的时间
+        if self.screenshot.is_some() {
+            for options in config_json.options.iter_mut() {
+                options.play_animation = false;
+            }
+        }
+    }
+}
+
+fn apply_animation(options: &mut OptionsJson, animation: Option<&String>, time: Option<f32>) {
+    if let Some(animation) = animation {
+        options.selected_animation_path = animation.clone();
+        options.use_animation = true;
+    }
+    if let Some(time) = time {
+        options.animation_time = time;
+        options.use_animation = true;
+    }
+}
+
+fn viewer_model(skin: &str) -> ViewerModel {
+    ViewerModel {
+        add_model: AddModel::from_skin(Path::new(skin)),
+        animation: None,
+        time: None,
+    }
+}
+
+fn last_model<'a>(viewer_args: &'a mut ViewerArgs, argument: &str) -> Result<&'a mut ViewerModel, CliError> {
+    viewer_args
+        .models
+        .last_mut()
+        .ok_or_else(|| CliError::usage(format!("{argument} must follow --skn")))
+}
+
+fn parse_value<T: std::str::FromStr>(argument: &str, value: &str) -> Result<T, CliError> {
+    value
+        .parse()
+        .map_err(|_| CliError::usage(format!("Invalid value for {argument}: {value}")))
+}
+
+fn parse_size(argument: &str, value: &str) -> Result<[i32; 2], CliError> {
+    let invalid = || CliError::usage(format!("Invalid value for {argument}: {value} (expected WxH)"));
+    let (width, height) = value.split_once(['x', 'X']).ok_or_else(invalid)?;
+    let width: i32 = width.trim().parse().map_err(|_| invalid())?;
+    let height: i32 = height.trim().parse().map_err(|_| invalid())?;
+    if width <= 0 || height <= 0 {
+        return Err(invalid());
+    }
+    Ok([width, height])
+}
@@ -1,6 +1,13 @@
 use gl::types::GLsizei;
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fs::File, io::Read, io::Write, path::Path};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Read,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
 use std::sync::Mutex;
 use once_cell::sync::Lazy;
 use crate::MindModel;
@@ -10,9 +17,13 @@ pub fn get_base_path() -> String {
     base_dir
 }
 
+// 配置文件格式版本，格式改变时增加并在 migrate 中添加迁移
+pub const CONFIG_VERSION: u32 = 1;
+
 impl Default for ConfigJson {
     fn default() -> Self {
         ConfigJson {
+            version: CONFIG_VERSION,
             msaa: Some(8),
             vsync: true,
             show_floor: true,
@@ -24,6 +35,7 @@ impl Default for ConfigJson {
             options: vec![],
             meshes: vec![],
             skybox_file: "./skybox/Default.dds".parse().unwrap(),
+            unloaded: vec![],
         }
     }
 }
@@ -31,7 +43,8 @@ impl Default for ConfigJson {
 pub static CONFIG_JSON: Lazy<Mutex<ConfigJson>> = Lazy::new(|| {
     Mutex::new(ConfigJson::default())
 });
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct PathJson {
     #[serde(rename = "Name")]
     pub name: String,
@@ -47,9 +60,17 @@ pub struct PathJson {
 
     #[serde(rename = "Animations", alias = "ANM")]
     pub animations: String,
+
+    // 拖放到模型上的、不在纹理和动画目录中的文件，同名时替换目录中的文件
+    #[serde(rename = "ExtraTextures", skip_serializing_if = "Vec::is_empty")]
+    pub extra_textures: Vec<String>,
+
+    #[serde(rename = "ExtraAnimations", skip_serializing_if = "Vec::is_empty")]
+    pub extra_animations: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct OptionsJson {
     #[serde(rename = "Show")]
     pub show: bool,
@@ -93,6 +114,18 @@ pub struct OptionsJson {
     #[serde(rename = "Rotation_angles")]
     pub rotation_angles: [f32; 3],
 
+    #[serde(rename = "ExportAnimations")]
+    pub export_animations: Vec<AnimationExportJson>,
+
+    // 子网格名称 -> export::ALPHA_MODES 的索引，未记录的子网格使用自动检测
+    #[serde(rename = "AlphaModes")]
+    pub alpha_modes: BTreeMap<String, usize>,
+}
+
+impl Default for OptionsJson {
+    fn default() -> Self {
+        OptionsJson::new()
+    }
 }
 
 impl OptionsJson {
@@ -112,13 +145,63 @@ impl OptionsJson {
             selected_animation_path: String::new(),
             position_offset: [0.0, 0.0, 0.0],
             rotation_angles: [0.0, 0.0, 0.0],
+            export_animations: vec![],
+            alpha_modes: BTreeMap::new(),
+        }
+    }
+
+    pub fn export_animation(&self, name: &str) -> Option<&AnimationExportJson> {
+        self.export_animations.iter().find(|x| x.name == name)
+    }
+
+    pub fn export_animation_mut(&mut self, name: &str, duration: f32) -> &mut AnimationExportJson {
+        let position = match self.export_animations.iter().position(|x| x.name == name) {
+            Some(position) => position,
+            None => {
+                self.export_animations
+                    .push(AnimationExportJson::new(name, duration));
+                self.export_animations.len() - 1
+            }
+        };
+        &mut self.export_animations[position]
+    }
+}
+
+// 单个动画的导出设置：是否导出、裁剪范围(秒)以及速度倍率
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AnimationExportJson {
+    #[serde(rename = "Name")]
+    pub name: String,
+
+    #[serde(rename = "Export", default = "default_true")]
+    pub export: bool,
+
+    #[serde(rename = "Start", default)]
+    pub start: f32,
+
+    // 导出时会限制在动画时长内，缺少时导出到动画结束
+    #[serde(rename = "End", default = "default_end")]
+    pub end: f32,
+
+    #[serde(rename = "Speed", default = "default_speed")]
+    pub speed: f32,
+}
+
+impl AnimationExportJson {
+    pub fn new(name: &str, duration: f32) -> AnimationExportJson {
+        AnimationExportJson {
+            name: name.to_owned(),
+            export: true,
+            start: 0.0f32,
+            end: duration,
+            speed: 1.0f32,
         }
     }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MeshJson {
-    #[serde(rename = "Show")]
+    #[serde(rename = "Show", default = "default_true")]
     pub show: bool,
 
     #[serde(flatten)]
@@ -136,23 +219,33 @@ pub struct ControlSensitivity {
     #[serde(rename = "Rotate", default = "default_rotate")]
     pub rotate: f32,
 }
+// 与缺少单个字段时的默认值一致，并且在设置面板的滑块范围内
 impl Default for ControlSensitivity {
     fn default() -> Self {
         ControlSensitivity {
-            rotate: 0.5,
-            pan: 0.7,
-            zoom: 1.0,
+            rotate: default_rotate(),
+            pan: default_pan(),
+            zoom: default_zoom(),
         }
     }
 }
 fn default_zoom() -> f32 { 75.0 }
 fn default_pan() -> f32 { 0.20 }
 fn default_rotate() -> f32 { 0.03 }
+fn default_true() -> bool { true }
+fn default_end() -> f32 { f32::MAX }
+fn default_speed() -> f32 { 1.0 }
 
 
 
+// 缺少的字段使用 ConfigJson::default() 中的值
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct ConfigJson {
+    // 旧的配置文件没有版本号，读取为 0
+    #[serde(rename = "Version")]
+    pub version: u32,
+
     #[serde(rename = "MSAA")]
     pub msaa: Option<u32>,
 
@@ -186,114 +279,175 @@ pub struct ConfigJson {
 
     #[serde(rename = "SkyboxFile")]
     pub skybox_file: String,
+
+    // 加载失败的模型，不在 paths 中，保存时按原来的位置原样写回
+    #[serde(skip)]
+    pub unloaded: Vec<UnloadedModel>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnloadedModel {
+    pub index: usize,
+    pub path: PathJson,
+    pub options: OptionsJson,
+    pub meshes: Vec<MeshJson>,
 }
 
 impl ConfigJson {
-    pub fn read(path: &Path) -> ConfigJson {
+    // 返回配置和需要在界面中显示的问题，文件无法读取时备份原文件并使用默认配置，
+    // 避免下一次保存时覆盖用户的场景
+    pub fn read(path: &Path) -> (ConfigJson, Vec<String>) {
         println!("Reading config file");
 
-        let mut file = match File::open(path) {
-            Ok(file) => file,
-            Err(error) => {
-                println!("Could not open config file: {error}");
-                return ConfigJson::new();
+        if !path.exists() {
+            println!("Config file not found: {}", path.display());
+            return (ConfigJson::new(), vec![]);
+        }
+
+        let result = std::fs::read_to_string(path)
+            .map_err(|error| format!("Could not read config file: {error}"))
+            .and_then(|contents| ConfigJson::parse(&contents));
+
+        let (config_json, mut messages, backup) = match result {
+            Ok((config_json, warnings)) => {
+                let backup = !warnings.is_empty();
+                (config_json, warnings, backup)
             }
+            Err(error) => (ConfigJson::new(), vec![error], true),
         };
 
-        let mut contents = String::new();
-        match file.read_to_string(&mut contents) {
-            Ok(_) => {}
-            Err(error) => {
-                println!("Could not read config file: {error}");
-                return ConfigJson::new();
+        // 丢弃了字段或来自更新的版本时保留原文件
+        if backup {
+            match backup_file(path) {
+                Ok(backup_path) => messages.push(format!(
+                    "原配置文件已备份到(The original config was backed up to): {}",
+                    backup_path.display()
+                )),
+                Err(error) => messages.push(error),
             }
         }
 
-        let mut config_json = match serde_json::from_str::<ConfigJson>(&contents) {
+        for message in messages.iter() {
+            println!("{message}");
+        }
+        println!("Finished reading config file");
+
+        (config_json, messages)
+    }
+
+    // 与 read 相同，但文件无法读取时返回错误，命令行模式下需要以非零状态退出
+    pub fn try_read(path: &Path) -> Result<ConfigJson, String> {
+        let mut file =
+            File::open(path).map_err(|error| format!("Could not open config file: {error}"))?;
+
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)
+            .map_err(|error| format!("Could not read config file: {error}"))?;
+
+        let (config_json, warnings) = ConfigJson::parse(&contents)?;
+        for warning in warnings {
+            println!("Warning: {warning}");
+        }
+        Ok(config_json)
+    }
+
+    // 先迁移旧格式，再逐个字段反序列化，无效的字段使用默认值并返回警告，
+    // 只有不是 JSON 对象时才返回错误
+    pub fn parse(contents: &str) -> Result<(ConfigJson, Vec<String>), String> {
+        let mut value = serde_json::from_str::<serde_json::Value>(contents)
+            .map_err(|error| format!("Could not deserialize config: {error}"))?;
+        if !value.is_object() {
+            return Err(String::from("Could not deserialize config: expected a JSON object"));
+        }
+
+        let mut warnings = migrate(&mut value);
+
+        let mut config_json = match serde_json::from_value::<ConfigJson>(value.clone()) {
             Ok(config_json) => config_json,
-            Err(error) => {
-                println!("Could not deserialize config: {error}");
-                ConfigJson::new()
+            Err(_) => {
+                let (config_json, field_warnings) = parse_fields(&value);
+                warnings.extend(field_warnings);
+                config_json
             }
         };
+        config_json.version = CONFIG_VERSION;
 
         if config_json.options.len() < config_json.paths.len() {
             let diff = config_json.paths.len() - config_json.options.len();
             let options = vec![OptionsJson::new(); diff];
             config_json.options.extend_from_slice(&options);
         }
+        config_json.options.truncate(config_json.paths.len());
 
         if config_json.meshes.len() < config_json.paths.len() {
             let diff = config_json.paths.len() - config_json.meshes.len();
             let meshes = vec![vec![]; diff];
             config_json.meshes.extend_from_slice(&meshes);
         }
+        config_json.meshes.truncate(config_json.paths.len());
 
-        println!("Finished reading config file");
-
-        config_json
+        Ok((config_json, warnings))
     }
 
-    pub fn write(&self, mind_models: &[MindModel]) {
-        println!("Writing to config file");
+    // 保存到当前场景文件，path 为 --config 指定或另存为/打开场景时选择的文件
+    pub fn write(&self, path: &Path, mind_models: &[MindModel]) -> Result<(), String> {
+        println!("Writing to config file {}", path.display());
 
         let mut config_json = self.clone();
+        config_json.version = CONFIG_VERSION;
 
         config_json
             .options
             .iter_mut()
             .enumerate()
             .for_each(|(i, config)| {
-                config.selected_animation_path = mind_models[i].animations_file_names
-                    [mind_models[i].animation_selected]
-                    .to_owned()
+                config.selected_animation_path = selected_animation_name(&mind_models[i])
             });
 
-        config_json.meshes = Vec::with_capacity(config_json.paths.len());
-        for i in 0..config_json.paths.len() {
-            let mind_model = &mind_models[i];
-
-            let mut meshes = Vec::with_capacity(mind_model.skin.meshes.len());
-            for i in 0..mind_model.skin.meshes.len() {
-                let mut name_texture = BTreeMap::new();
-                name_texture.insert(
-                    mind_model.skin.meshes[i].submesh.name.to_owned(),
-                    mind_model.textures_file_names[mind_model.textures_selecteds[i]].to_owned(),
-                );
-                meshes.push(MeshJson {
-                    show: mind_model.show_meshes[i],
-                    name_texture,
-                });
-            }
-            config_json.meshes.push(meshes);
+        config_json.meshes = mind_models[..config_json.paths.len()].iter().map(model_meshes).collect();
+
+        for unloaded in self.unloaded.iter() {
+            let index = unloaded.index.min(config_json.paths.len());
+            config_json.paths.insert(index, unloaded.path.clone());
+            config_json.options.insert(index, unloaded.options.clone());
+            config_json.meshes.insert(index, unloaded.meshes.clone());
         }
 
-        let contents = match pretty_json(&config_json) {
-            Ok(contents) => contents,
-            Err(error) => {
-                return println!("Could not serialize config file: {error}");
-            }
-        };
+        let contents =
+            pretty_json(&config_json).map_err(|error| format!("Could not serialize config file: {error}"))?;
 
-        let mut file = match File::create(Path::new("config.json")) {
-            Ok(file) => file,
-            Err(error) => {
-                return println!("Could not create config file: {error}");
-            }
-        };
+        let mut file = File::create(path)
+            .map_err(|error| format!("Could not create config file {}: {error}", path.display()))?;
+        file.write_all(contents.as_bytes())
+            .map_err(|error| format!("Could not write to config file {}: {error}", path.display()))?;
+
+        println!("Finished writing to config file");
+        Ok(())
+    }
 
-        match file.write_all(contents.as_bytes()) {
-            Ok(_) => {}
-            Err(error) => {
-                return println!("Could not write to config file: {error}");
+    // 删除已加载的第 index 个模型，加载失败的模型保持原来的相邻顺序
+    pub fn remove_model(&mut self, index: usize) {
+        self.paths.remove(index);
+        self.options.remove(index);
+        self.meshes.remove(index);
+
+        // unloaded 按位置从小到大保存，先算出被删除的模型在完整列表中的位置
+        let mut position = index;
+        for unloaded in self.unloaded.iter() {
+            if unloaded.index <= position {
+                position += 1;
+            }
+        }
+        for unloaded in self.unloaded.iter_mut() {
+            if unloaded.index > position {
+                unloaded.index -= 1;
             }
         }
-
-        println!("Finished writing to config file");
     }
 
     pub fn new() -> ConfigJson {
         ConfigJson {
+            version: CONFIG_VERSION,
             msaa: Some(8),
             vsync: true,
             show_floor: true,
@@ -305,19 +459,328 @@ impl ConfigJson {
             options: vec![],
             meshes: vec![],
             skybox_file: String::new(),
+            unloaded: vec![],
+        }
+    }
+}
+
+// 按版本依次迁移，迁移不会丢失数据，只有版本号比当前更新时返回警告
+fn migrate(value: &mut serde_json::Value) -> Vec<String> {
+    let mut warnings = vec![];
+    let version = value
+        .get("Version")
+        .and_then(|x| x.as_u64())
+        .unwrap_or(0) as u32;
+
+    if version > CONFIG_VERSION {
+        warnings.push(format!(
+            "配置文件来自更新的版本(The config file is from a newer version): {version} > {CONFIG_VERSION}"
+        ));
+        return warnings;
+    }
+
+    // 0 -> 1: PATHS 中旧的 SKN/SKL/DDS/ANM 键改为 Skin/Skeleton/Textures/Animations
+    if version < 1 {
+        if let Some(paths) = value.get_mut("PATHS").and_then(|x| x.as_array_mut()) {
+            for path in paths.iter_mut().filter_map(|x| x.as_object_mut()) {
+                for (old_key, new_key) in [
+                    ("SKN", "Skin"),
+                    ("SKL", "Skeleton"),
+                    ("DDS", "Textures"),
+                    ("ANM", "Animations"),
+                ] {
+                    if let Some(old_value) = path.remove(old_key) {
+                        path.entry(new_key).or_insert(old_value);
+                    }
+                }
+            }
+        }
+        println!("Config migrated from version {version} to {CONFIG_VERSION}");
+    }
+
+    if let Some(object) = value.as_object_mut() {
+        object.insert(String::from("Version"), serde_json::Value::from(CONFIG_VERSION));
+    }
+    warnings
+}
+
+// 整体反序列化失败时逐个字段尝试，跳过无法读取的字段
+fn parse_fields(value: &serde_json::Value) -> (ConfigJson, Vec<String>) {
+    let mut warnings = vec![];
+    let mut fields = serde_json::Map::new();
+
+    if let Some(object) = value.as_object() {
+        let mut object = object.clone();
+        warnings.extend(parse_models(&mut object));
+
+        for (key, field) in object.iter() {
+            let mut candidate = fields.clone();
+            candidate.insert(key.clone(), field.clone());
+            match serde_json::from_value::<ConfigJson>(serde_json::Value::Object(candidate)) {
+                Ok(_) => {
+                    fields.insert(key.clone(), field.clone());
+                }
+                Err(error) => warnings.push(format!(
+                    "已忽略无效的字段(Ignored invalid field) {key}: {error}"
+                )),
+            }
+        }
+    }
+
+    let config_json = serde_json::from_value::<ConfigJson>(serde_json::Value::Object(fields))
+        .unwrap_or_else(|_| ConfigJson::new());
+    (config_json, warnings)
+}
+
+// 按模型恢复 PATHS、OPTIONS 和 MESHES，三个数组按索引对应：
+// 路径无效时移除整个模型，选项无效时使用默认选项，无效的子网格条目被忽略
+fn parse_models(object: &mut serde_json::Map<String, serde_json::Value>) -> Vec<String> {
+    let mut warnings = vec![];
+
+    let mut invalid_models = vec![];
+    if let Some(paths) = object.get("PATHS").and_then(|x| x.as_array()) {
+        for (i, path) in paths.iter().enumerate() {
+            if let Err(error) = serde_json::from_value::<PathJson>(path.clone()) {
+                warnings.push(format!("已忽略无效的模型(Ignored invalid model) PATHS[{i}]: {error}"));
+                invalid_models.push(i);
+            }
+        }
+    }
+
+    if let Some(options) = object.get_mut("OPTIONS").and_then(|x| x.as_array_mut()) {
+        for (i, option) in options.iter_mut().enumerate() {
+            if let Err(error) = serde_json::from_value::<OptionsJson>(option.clone()) {
+                if !invalid_models.contains(&i) {
+                    warnings.push(format!("已重置无效的选项(Reset invalid options) OPTIONS[{i}]: {error}"));
+                }
+                *option = serde_json::to_value(OptionsJson::new()).unwrap_or_default();
+            }
+        }
+    }
+
+    if let Some(meshes) = object.get_mut("MESHES").and_then(|x| x.as_array_mut()) {
+        for (i, model_meshes) in meshes.iter_mut().enumerate() {
+            let Some(model_meshes) = model_meshes.as_array_mut() else {
+                if !invalid_models.contains(&i) {
+                    warnings.push(format!("已忽略无效的子网格(Ignored invalid submeshes) MESHES[{i}]"));
+                }
+                *model_meshes = serde_json::json!([]);
+                continue;
+            };
+            let mut j = 0;
+            model_meshes.retain(|mesh| {
+                let result = serde_json::from_value::<MeshJson>(mesh.clone());
+                if let Err(error) = &result {
+                    if !invalid_models.contains(&i) {
+                        warnings.push(format!(
+                            "已忽略无效的子网格(Ignored invalid submesh) MESHES[{i}][{j}]: {error}"
+                        ));
+                    }
+                }
+                j += 1;
+                result.is_ok()
+            });
+        }
+    }
 
+    for key in ["PATHS", "OPTIONS", "MESHES"] {
+        if let Some(array) = object.get_mut(key).and_then(|x| x.as_array_mut()) {
+            let mut i = 0;
+            array.retain(|_| {
+                i += 1;
+                !invalid_models.contains(&(i - 1))
+            });
         }
     }
+
+    warnings
+}
+
+// 复制为 <文件名>.<时间戳>.bak，不覆盖已有的备份
+fn backup_file(path: &Path) -> Result<PathBuf, String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or(0);
+    let file_name = path
+        .file_name()
+        .and_then(|x| x.to_str())
+        .unwrap_or("config.json");
+    let backup_path = path.with_file_name(format!("{file_name}.{timestamp}.bak"));
+
+    std::fs::copy(path, &backup_path)
+        .map_err(|error| format!("Could not back up config file: {error}"))?;
+    Ok(backup_path)
+}
+
+// 每个子网格的显示状态和选中的纹理名称，保存配置和导出预设时使用
+pub fn model_meshes(mind_model: &MindModel) -> Vec<MeshJson> {
+    let mut meshes = Vec::with_capacity(mind_model.skin.meshes.len());
+    for i in 0..mind_model.skin.meshes.len() {
+        let mut name_texture = BTreeMap::new();
+        name_texture.insert(
+            mind_model.skin.meshes[i].submesh.name.to_owned(),
+            mind_model
+                .textures_file_names
+                .get(mind_model.textures_selecteds[i])
+                .cloned()
+                .unwrap_or_default(),
+        );
+        meshes.push(MeshJson {
+            show: mind_model.show_meshes[i],
+            name_texture,
+        });
+    }
+    meshes
+}
+
+pub fn selected_animation_name(mind_model: &MindModel) -> String {
+    mind_model
+        .animations_file_names
+        .get(mind_model.animation_selected)
+        .cloned()
+        .unwrap_or_default()
 }
 
-fn pretty_json(config_json: &ConfigJson) -> Result<String, serde_json::Error> {
+pub fn pretty_json<T: Serialize>(value: &T) -> Result<String, serde_json::Error> {
     let mut buffer = Vec::new();
     let formatter = serde_json::ser::PrettyFormatter::with_indent(b"\t");
     let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
-    config_json.serialize(&mut serializer)?;
+    value.serialize(&mut serializer)?;
     Ok(unsafe { String::from_utf8_unchecked(buffer) })
 }
 
 use std::env;
 
 // 在结构体定义之后或 impl 块内添加以下函数
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(name: &str) -> PathJson {
+        PathJson {
+            name: String::from(name),
+            ..PathJson::default()
+        }
+    }
+
+    fn names(config_json: &ConfigJson) -> Vec<String> {
+        config_json.paths.iter().map(|x| x.name.clone()).collect()
+    }
+
+    #[test]
+    fn removing_a_model_keeps_unloaded_models_in_place() {
+        // 完整列表: u0 u1 a u3 b，a 和 b 已加载
+        let mut config_json = ConfigJson::new();
+        for name in ["a", "b"] {
+            config_json.paths.push(path(name));
+            config_json.options.push(OptionsJson::new());
+            config_json.meshes.push(vec![]);
+        }
+        for (index, name) in [(0, "u0"), (1, "u1"), (3, "u3")] {
+            config_json.unloaded.push(UnloadedModel {
+                index,
+                path: path(name),
+                options: OptionsJson::new(),
+                meshes: vec![],
+            });
+        }
+        let mind_models = [MindModel::empty(), MindModel::empty()];
+
+        config_json.remove_model(0);
+
+        let file = std::env::temp_dir().join(format!("config_remove_model_{}.json", std::process::id()));
+        config_json.write(&file, &mind_models[..1]).unwrap();
+        let (written, warnings) = ConfigJson::parse(&std::fs::read_to_string(&file).unwrap()).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(names(&written), ["u0", "u1", "u3", "b"]);
+        assert_eq!(written.options.len(), 4);
+        assert_eq!(written.meshes.len(), 4);
+    }
+
+    #[test]
+    fn version_0_path_keys_are_migrated() {
+        let (config_json, warnings) = ConfigJson::parse(
+            r#"{ "PATHS": [{ "Name": "a", "SKN": "a.skn", "SKL": "a.skl", "DDS": "textures", "ANM": "animations" }] }"#,
+        )
+        .unwrap();
+
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(config_json.version, CONFIG_VERSION);
+        let path = &config_json.paths[0];
+        assert_eq!(path.skin, "a.skn");
+        assert_eq!(path.skeleton, "a.skl");
+        assert_eq!(path.textures, "textures");
+        assert_eq!(path.animations, "animations");
+        assert_eq!(config_json.options.len(), 1);
+        assert_eq!(config_json.meshes.len(), 1);
+    }
+
+    #[test]
+    fn invalid_path_drops_its_options_and_meshes() {
+        let (config_json, warnings) = ConfigJson::parse(
+            r#"{
+                "Version": 1,
+                "PATHS": [{ "Name": "a" }, { "Name": 5 }, { "Name": "c" }],
+                "OPTIONS": [{ "AnimationTime": 1.0 }, { "AnimationTime": 2.0 }, { "AnimationTime": 3.0 }],
+                "MESHES": [[{ "Show": true, "a": "ta" }], [{ "Show": true, "b": "tb" }], [{ "Show": false, "c": "tc" }]]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("PATHS[1]"));
+        assert_eq!(names(&config_json), ["a", "c"]);
+        assert_eq!(config_json.options[1].animation_time, 3.0f32);
+        assert_eq!(config_json.meshes[1][0].name_texture["c"], "tc");
+        assert!(!config_json.meshes[1][0].show);
+    }
+
+    #[test]
+    fn invalid_field_falls_back_to_default() {
+        let (config_json, warnings) = ConfigJson::parse(
+            r#"{
+                "Version": 1,
+                "Vsync": "yes",
+                "ShowFloor": false,
+                "PATHS": [{ "Name": "a" }],
+                "OPTIONS": [{ "AnimationTime": "late" }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+        assert!(warnings.iter().any(|x| x.contains("Vsync")));
+        assert!(warnings.iter().any(|x| x.contains("OPTIONS[0]")));
+        assert_eq!(config_json.vsync, ConfigJson::default().vsync);
+        assert!(!config_json.show_floor);
+        assert_eq!(names(&config_json), ["a"]);
+        assert_eq!(config_json.options[0].animation_time, OptionsJson::new().animation_time);
+    }
+
+    #[test]
+    fn newer_version_warns_and_backs_up() {
+        let dir = std::env::temp_dir().join(format!("config_newer_version_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        let contents = format!(r#"{{ "Version": {}, "PATHS": [{{ "Name": "a" }}] }}"#, CONFIG_VERSION + 1);
+        std::fs::write(&path, &contents).unwrap();
+
+        let (config_json, messages) = ConfigJson::read(&path);
+        let backups: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|x| x == "bak"))
+            .collect();
+        let backup_contents = backups.first().map(|backup| std::fs::read_to_string(backup).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(messages.iter().any(|x| x.contains("newer version")), "{messages:?}");
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backup_contents.as_deref(), Some(contents.as_str()));
+        assert_eq!(names(&config_json), ["a"]);
+    }
+}
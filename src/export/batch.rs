@@ -0,0 +1,135 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{
+    config_json::ConfigJson,
+    export::{self, ExportOptions},
+};
+
+pub const REPORT_FILE_NAME: &str = "batch_report.json";
+
+#[derive(Serialize)]
+pub struct BatchReport {
+    // Unix 时间戳(秒)
+    pub started: u64,
+    pub total_seconds: f32,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub models: Vec<BatchModelReport>,
+}
+
+#[derive(Serialize)]
+pub struct BatchModelReport {
+    pub name: String,
+    pub success: bool,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub load_seconds: f32,
+    pub export_seconds: f32,
+}
+
+// 按 config.json 中保存的网格显示和纹理设置重新读取并导出每个模型，
+// 单个模型失败不会中断后续模型，结果写入输出目录下的 batch_report.json
+pub fn export_batch(
+    export_options: &ExportOptions,
+    config_json: &ConfigJson,
+    export_animations: bool,
+) -> Result<(PathBuf, BatchReport), String> {
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or(0);
+    let batch_start = Instant::now();
+
+    // 每个模型都不单独打开目录，全部完成后再打开一次
+    let mut model_export_options = export_options.clone();
+    model_export_options.open_folder = false;
+
+    let mut models = Vec::with_capacity(config_json.paths.len());
+    for i in 0..config_json.paths.len() {
+        let name = config_json.paths[i].name.clone();
+        println!("Batch export {}/{}: {name}", i + 1, config_json.paths.len());
+
+        let (result, load_seconds, export_seconds) =
+            export_config_model(&model_export_options, config_json, i, export_animations);
+
+        if let Err(error) = &result {
+            println!("Batch export failed: {name}: {error}");
+        }
+
+        models.push(BatchModelReport {
+            name,
+            success: result.is_ok(),
+            output: result.as_ref().ok().map(|path| path.display().to_string()),
+            error: result.err(),
+            load_seconds,
+            export_seconds,
+        });
+    }
+
+    let succeeded = models.iter().filter(|x| x.success).count();
+    let report = BatchReport {
+        started,
+        total_seconds: batch_start.elapsed().as_secs_f32(),
+        succeeded,
+        failed: models.len() - succeeded,
+        models,
+    };
+
+    let output_dir = Path::new(&export_options.output_dir);
+    std::fs::create_dir_all(output_dir)
+        .map_err(|error| format!("Could not create export dirs: {error}"))?;
+    let report_path = output_dir.join(REPORT_FILE_NAME);
+    let contents = serde_json::to_string_pretty(&report)
+        .map_err(|error| format!("Could not serialize batch report: {error}"))?;
+    export::write_file(&report_path, contents.as_bytes())?;
+
+    println!(
+        "Batch export finished: {} succeeded, {} failed in {:.2}s. Report: {}",
+        report.succeeded,
+        report.failed,
+        report.total_seconds,
+        report_path.display()
+    );
+
+    if export_options.open_folder {
+        export::open_folder(output_dir);
+    }
+
+    Ok((report_path, report))
+}
+
+// 返回(结果, 读取耗时, 导出耗时)，读取或导出时的 panic 也记为失败
+fn export_config_model(
+    export_options: &ExportOptions,
+    config_json: &ConfigJson,
+    index: usize,
+    export_animations: bool,
+) -> (Result<PathBuf, String>, f32, f32) {
+    let path = &config_json.paths[index];
+    let options = &config_json.options[index];
+
+    let load_start = Instant::now();
+    let mind_model = match crate::read_config_model(config_json, index) {
+        Ok(mind_model) => mind_model,
+        Err(error) => return (Err(error), load_start.elapsed().as_secs_f32(), 0.0f32),
+    };
+    let load_seconds = load_start.elapsed().as_secs_f32();
+
+    let export_start = Instant::now();
+    let result = export::catch_panic(|| {
+        export::export_model(
+            export_options,
+            &path.name,
+            &mind_model,
+            export_animations,
+            options,
+        )
+    });
+
+    (result, load_seconds, export_start.elapsed().as_secs_f32())
+}
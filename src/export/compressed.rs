@@ -0,0 +1,132 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+
+use crate::gls::texture::{CompressedSource, Format};
+
+// 写出 DDS，ETC 格式没有对应的 DDS 格式，返回 None
+pub fn write_dds(source: &CompressedSource) -> Option<Vec<u8>> {
+    let fourcc = match source.format {
+        Format::BC1DXT1 => Some(*b"DXT1"),
+        Format::BC2DXT3 => Some(*b"DXT3"),
+        Format::BC3DXT5 => Some(*b"DXT5"),
+        Format::RGBA8 => None,
+        Format::ETC1 | Format::ETC2EAC => return None,
+    };
+
+    let mipmap_count = source.mipmaps.len() as u32;
+    let has_mipmaps = mipmap_count > 1;
+
+    // CAPS | HEIGHT | WIDTH | PIXELFORMAT，压缩格式使用 LINEARSIZE，未压缩使用 PITCH
+    let mut flags = 0x1 | 0x2 | 0x4 | 0x1000;
+    flags |= if fourcc.is_some() { 0x80000 } else { 0x8 };
+    if has_mipmaps {
+        flags |= 0x20000;
+    }
+
+    let pitch_or_linear_size = match fourcc {
+        Some(_) => source.mipmaps.first().map(Vec::len).unwrap_or(0) as u32,
+        None => source.width as u32 * 4,
+    };
+
+    let mut writer = vec![];
+    writer.extend_from_slice(b"DDS ");
+    writer.write_u32::<LittleEndian>(124).unwrap();
+    writer.write_u32::<LittleEndian>(flags).unwrap();
+    writer.write_u32::<LittleEndian>(source.height as u32).unwrap();
+    writer.write_u32::<LittleEndian>(source.width as u32).unwrap();
+    writer.write_u32::<LittleEndian>(pitch_or_linear_size).unwrap();
+    writer.write_u32::<LittleEndian>(0).unwrap(); // depth
+    writer.write_u32::<LittleEndian>(mipmap_count).unwrap();
+    writer.extend_from_slice(&[0u8; 44]);
+
+    // DDS_PIXELFORMAT
+    writer.write_u32::<LittleEndian>(32).unwrap();
+    match fourcc {
+        Some(fourcc) => {
+            writer.write_u32::<LittleEndian>(0x4).unwrap();
+            writer.extend_from_slice(&fourcc);
+            writer.extend_from_slice(&[0u8; 20]);
+        }
+        None => {
+            writer.write_u32::<LittleEndian>(0x40 | 0x1).unwrap();
+            writer.write_u32::<LittleEndian>(0).unwrap();
+            writer.write_u32::<LittleEndian>(32).unwrap();
+            writer.write_u32::<LittleEndian>(0x000000FF).unwrap();
+            writer.write_u32::<LittleEndian>(0x0000FF00).unwrap();
+            writer.write_u32::<LittleEndian>(0x00FF0000).unwrap();
+            writer.write_u32::<LittleEndian>(0xFF000000).unwrap();
+        }
+    }
+
+    let mut caps = 0x1000;
+    if has_mipmaps {
+        caps |= 0x8 | 0x400000;
+    }
+    writer.write_u32::<LittleEndian>(caps).unwrap();
+    writer.extend_from_slice(&[0u8; 16]);
+
+    for mipmap in source.mipmaps.iter() {
+        writer.extend_from_slice(mipmap);
+    }
+
+    Some(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::gls::texture;
+
+    fn source(format: Format) -> CompressedSource {
+        let block = format.bytes_per_block();
+        CompressedSource {
+            format,
+            width: 8,
+            height: 4,
+            mipmaps: vec![
+                (0..format.level_size(8, 4)).map(|x| x as u8).collect(),
+                (0..format.level_size(4, 2)).map(|x| (x + block) as u8).collect(),
+            ],
+        }
+    }
+
+    #[test]
+    fn dds_round_trip() {
+        for (format, fourcc) in [
+            (Format::BC1DXT1, b"DXT1"),
+            (Format::BC2DXT3, b"DXT3"),
+            (Format::BC3DXT5, b"DXT5"),
+        ] {
+            let source = source(format);
+            let dds = write_dds(&source).unwrap();
+
+            assert_eq!(&dds[0..4], b"DDS ");
+            assert_eq!(dds.len(), 128 + source.mipmaps.iter().map(Vec::len).sum::<usize>());
+            assert_eq!(&dds[84..88], fourcc);
+
+            let read = texture::load_compressed_source(&mut Cursor::new(dds.as_slice()));
+            assert_eq!(read.format, format);
+            assert_eq!((read.width, read.height), (8, 4));
+            assert_eq!(read.mipmaps, source.mipmaps);
+        }
+    }
+
+    #[test]
+    fn dds_rgba8_uses_bit_masks() {
+        let dds = write_dds(&source(Format::RGBA8)).unwrap();
+        let read_u32 = |offset: usize| u32::from_le_bytes(dds[offset..offset + 4].try_into().unwrap());
+
+        assert_eq!(read_u32(80), 0x41);
+        assert_eq!(read_u32(88), 32);
+        assert_eq!(read_u32(92), 0x000000FF);
+        assert_eq!(read_u32(104), 0xFF000000);
+        assert_eq!(read_u32(20), 8 * 4);
+    }
+
+    #[test]
+    fn dds_has_no_etc_format() {
+        assert!(write_dds(&source(Format::ETC1)).is_none());
+        assert!(write_dds(&source(Format::ETC2EAC)).is_none());
+    }
+}
@@ -0,0 +1,240 @@
+use crate::lol::{
+    anm::JointAnm,
+    skl::Joint,
+    skn::{Mesh, SubMeshHeader},
+    Animation, Skeleton, Skin,
+};
+
+// 游戏坐标到导出坐标的变换：先按需镜像 X 轴，再转换向上轴，最后统一缩放。
+// 镜像和轴转换都是带符号的置换矩阵，所以关节的缩放仍然是对角的，可以继续用 TRS 表示
+pub struct Conversion {
+    matrix: glam::Mat4,
+    inverse: glam::Mat4,
+    axes: glam::Mat3,
+    rotation: glam::Quat,
+}
+
+impl Conversion {
+    pub fn new(unit_scale: f32, z_up: bool, flip_handedness: bool) -> Conversion {
+        let mirror = if flip_handedness {
+            glam::Mat3::from_diagonal(glam::vec3(-1.0f32, 1.0f32, 1.0f32))
+        } else {
+            glam::Mat3::IDENTITY
+        };
+        // Y 向上 -> Z 向上：(x, y, z) -> (x, -z, y)
+        let up = if z_up {
+            glam::Mat3::from_cols(glam::Vec3::X, glam::Vec3::Z, glam::Vec3::NEG_Y)
+        } else {
+            glam::Mat3::IDENTITY
+        };
+
+        let axes = up * mirror;
+        let unit_scale = if unit_scale > 0.0f32 { unit_scale } else { 1.0f32 };
+        let matrix = glam::Mat4::from_mat3(axes * unit_scale);
+
+        // 镜像时 -axes 是一个真正的旋转，且 axes * R * axes^T == (-axes) * R * (-axes)^T
+        let proper = if axes.determinant() < 0.0f32 { -axes } else { axes };
+
+        Conversion {
+            matrix,
+            inverse: matrix.inverse(),
+            axes,
+            rotation: glam::Quat::from_mat3(&proper).normalize(),
+        }
+    }
+
+    // 镜像后三角形的环绕方向需要反转
+    pub fn flips_winding(&self) -> bool {
+        self.axes.determinant() < 0.0f32
+    }
+
+    pub fn point(&self, point: glam::Vec3) -> glam::Vec3 {
+        self.matrix.transform_point3(point)
+    }
+
+    pub fn normal(&self, normal: glam::Vec3) -> glam::Vec3 {
+        (self.axes * normal).normalize_or_zero()
+    }
+
+    // 关节局部矩阵、全局矩阵和逆绑定矩阵都用 C * M * C^-1 变换
+    pub fn transform(&self, transform: glam::Mat4) -> glam::Mat4 {
+        self.matrix * transform * self.inverse
+    }
+
+    pub fn translation(&self, translation: glam::Vec3) -> glam::Vec3 {
+        self.matrix.transform_vector3(translation)
+    }
+
+    pub fn rotation(&self, rotation: glam::Quat) -> glam::Quat {
+        self.rotation * rotation * self.rotation.inverse()
+    }
+
+    pub fn scale(&self, scale: glam::Vec3) -> glam::Vec3 {
+        glam::Mat3::from_cols(
+            self.axes.x_axis.abs(),
+            self.axes.y_axis.abs(),
+            self.axes.z_axis.abs(),
+        ) * scale
+    }
+
+    // 带符号置换矩阵下包围盒的两个角变换后重新取最小/最大值即可
+    pub fn bounding_box(&self, bounding_box: [glam::Vec3; 2]) -> [glam::Vec3; 2] {
+        let a = self.point(bounding_box[0]);
+        let b = self.point(bounding_box[1]);
+        [a.min(b), a.max(b)]
+    }
+
+    pub fn triangle(&self, triangle: [u16; 3]) -> [u16; 3] {
+        if self.flips_winding() {
+            [triangle[0], triangle[2], triangle[1]]
+        } else {
+            triangle
+        }
+    }
+
+    pub fn skin(&self, skin: &Skin) -> Skin {
+        let mut indices = skin.indices.clone();
+        if self.flips_winding() {
+            for triangle in indices.chunks_exact_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+
+        Skin {
+            major: skin.major,
+            minor: skin.minor,
+            center: self.point(skin.center),
+            bounding_box: self.bounding_box(skin.bounding_box),
+            vertices: skin.vertices.iter().map(|x| self.point(*x)).collect(),
+            normals: skin.normals.iter().map(|x| self.normal(*x)).collect(),
+            uvs: skin.uvs.clone(),
+            influences: skin.influences.clone(),
+            weights: skin.weights.clone(),
+            indices,
+            meshes: skin
+                .meshes
+                .iter()
+                .map(|mesh| Mesh {
+                    hash: mesh.hash,
+                    submesh: SubMeshHeader {
+                        name: mesh.submesh.name.clone(),
+                        indices_offset: mesh.submesh.indices_offset,
+                        indices_count: mesh.submesh.indices_count,
+                        material_index: mesh.submesh.material_index,
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    pub fn skeleton(&self, skeleton: &Skeleton) -> Skeleton {
+        Skeleton {
+            stype: skeleton.stype,
+            version: skeleton.version,
+            joints: skeleton
+                .joints
+                .iter()
+                .map(|joint| Joint {
+                    name: joint.name.clone(),
+                    hash: joint.hash,
+                    id: joint.id,
+                    parent_id: joint.parent_id,
+                    local_matrix: self.transform(joint.local_matrix),
+                    global_matrix: self.transform(joint.global_matrix),
+                    inverse_global_matrix: self.transform(joint.inverse_global_matrix),
+                    children: joint.children.clone(),
+                })
+                .collect(),
+            influences: skeleton.influences.clone(),
+        }
+    }
+
+    pub fn animation(&self, animation: &Animation) -> Animation {
+        Animation {
+            format: animation.format.clone(),
+            fps: animation.fps,
+            duration: animation.duration,
+            frame_delay: animation.frame_delay,
+            joints: animation
+                .joints
+                .iter()
+                .map(|joint| JointAnm {
+                    hash: joint.hash,
+                    translations: joint
+                        .translations
+                        .iter()
+                        .map(|(time, x)| (*time, self.translation(*x)))
+                        .collect(),
+                    rotations: joint
+                        .rotations
+                        .iter()
+                        .map(|(time, x)| (*time, self.rotation(*x)))
+                        .collect(),
+                    scales: joint
+                        .scales
+                        .iter()
+                        .map(|(time, x)| (*time, self.scale(*x)))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversions() -> Vec<Conversion> {
+        let mut conversions = vec![];
+        for z_up in [false, true] {
+            for flip_handedness in [false, true] {
+                conversions.push(Conversion::new(0.01f32, z_up, flip_handedness));
+            }
+        }
+        conversions
+    }
+
+    #[test]
+    fn transform_is_conjugation() {
+        let scale = glam::vec3(1.0f32, 2.0f32, 3.0f32);
+        let rotation = glam::Quat::from_euler(glam::EulerRot::XYZ, 0.3f32, -0.7f32, 1.1f32);
+        let translation = glam::vec3(4.0f32, -5.0f32, 6.0f32);
+        let transform = glam::Mat4::from_scale_rotation_translation(scale, rotation, translation);
+        let point = glam::vec3(0.5f32, 1.5f32, -2.5f32);
+
+        for conversion in conversions() {
+            // C * M * C^-1 作用于转换后的点，等于转换 M 作用后的点
+            let converted = conversion.transform(transform).transform_point3(conversion.point(point));
+            let expected = conversion.point(transform.transform_point3(point));
+            assert!(converted.abs_diff_eq(expected, 1e-4f32), "{converted} {expected}");
+
+            // 分别转换 TRS 与转换整个矩阵一致，动画关键帧依赖这一点
+            let trs = glam::Mat4::from_scale_rotation_translation(
+                conversion.scale(scale),
+                conversion.rotation(rotation),
+                conversion.translation(translation),
+            );
+            assert!(trs.abs_diff_eq(conversion.transform(transform), 1e-4f32));
+        }
+    }
+
+    #[test]
+    fn triangle_keeps_facing_direction() {
+        let points = [
+            glam::vec3(0.0f32, 0.0f32, 0.0f32),
+            glam::vec3(1.0f32, 0.0f32, 0.5f32),
+            glam::vec3(0.0f32, 1.0f32, -0.5f32),
+        ];
+        let face_normal = |p: [glam::Vec3; 3]| (p[1] - p[0]).cross(p[2] - p[0]).normalize();
+        let normal = face_normal(points);
+
+        for conversion in conversions() {
+            let triangle = conversion.triangle([0, 1, 2]);
+            let converted = triangle.map(|i| conversion.point(points[i as usize]));
+            let converted_normal = face_normal(converted);
+            assert!(converted_normal.abs_diff_eq(conversion.normal(normal), 1e-4f32));
+            assert_eq!(conversion.flips_winding(), triangle != [0, 1, 2]);
+        }
+    }
+}
@@ -0,0 +1,520 @@
+use std::{collections::BTreeSet, fs, path::Path};
+
+use crate::{
+    config_json::OptionsJson,
+    export::{self, conversion::Conversion},
+    lol::{anm, Animation, Skeleton, Skin},
+    MindModel,
+};
+
+pub fn export_dae(
+    export_path: &Path,
+    file_name: &str,
+    model_name: &str,
+    mind_model: &MindModel,
+    skin: &Skin,
+    textures_selecteds: &[usize],
+    export_animations: bool,
+    options: &OptionsJson,
+    conversion: &Conversion,
+) -> Result<(), String> {
+    let texture_export_path = export_path.join("textures");
+    fs::create_dir_all(&texture_export_path)
+        .map_err(|error| format!("Could not create texture export dirs: {error}"))?;
+
+    let model_id = collada_id(model_name);
+    let skin = &conversion.skin(skin);
+    let skeleton = &conversion.skeleton(&mind_model.skeleton);
+    let up_axis = if conversion.point(glam::Vec3::Y).y.abs() > 0.5f32 { "Y_UP" } else { "Z_UP" };
+    let joint_ids = joint_ids(&model_id, skeleton);
+
+    let mut dae = String::new();
+    dae.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    dae.push_str(
+        "<COLLADA xmlns=\"http://www.collada.org/2005/11/COLLADASchema\" version=\"1.4.1\">\n",
+    );
+    dae.push_str("  <asset>\n");
+    dae.push_str("    <unit name=\"meter\" meter=\"1\"/>\n");
+    dae.push_str(&format!("    <up_axis>{up_axis}</up_axis>\n"));
+    dae.push_str("  </asset>\n");
+
+    dae.push_str(&make_images(
+        &mind_model.textures_paths,
+        textures_selecteds,
+        &texture_export_path,
+    )?);
+    dae.push_str(&make_effects_materials(
+        skin,
+        textures_selecteds,
+        mind_model.textures_paths.len(),
+    ));
+    dae.push_str(&make_geometry(&model_id, skin));
+    dae.push_str(&make_controller(&model_id, skin, skeleton, &joint_ids));
+
+    if export_animations && !mind_model.animations.is_empty() {
+        dae.push_str(&make_animations(mind_model, skeleton, &joint_ids, options, conversion));
+    }
+
+    dae.push_str(&make_visual_scene(&model_id, model_name, skin, skeleton, &joint_ids));
+
+    dae.push_str("  <scene>\n");
+    dae.push_str("    <instance_visual_scene url=\"#Scene\"/>\n");
+    dae.push_str("  </scene>\n");
+    dae.push_str("</COLLADA>\n");
+
+    export::write_file(&export_path.join(format!("{file_name}.dae")), dae.as_bytes())
+}
+
+fn make_images(
+    textures_paths: &[String],
+    textures_selecteds: &[usize],
+    texture_export_path: &Path,
+) -> Result<String, String> {
+    // 只导出子网格实际使用的纹理
+    let used_textures: BTreeSet<usize> = textures_selecteds.iter().copied().collect();
+
+    let mut images = String::from("  <library_images>\n");
+    for texture_index in used_textures {
+        if texture_index >= textures_paths.len() {
+            continue;
+        }
+        let texture_file_name =
+            export::save_texture_png(&textures_paths[texture_index], texture_export_path)?;
+        images.push_str(&format!(
+            "    <image id=\"image{texture_index}\" name=\"{}\">\n",
+            xml_escape(&texture_file_name)
+        ));
+        images.push_str(&format!(
+            "      <init_from>textures/{}</init_from>\n",
+            xml_escape(&texture_file_name)
+        ));
+        images.push_str("    </image>\n");
+    }
+    images.push_str("  </library_images>\n");
+    Ok(images)
+}
+
+// 模型没有纹理时效果只使用白色漫反射
+fn make_effects_materials(skin: &Skin, textures_selecteds: &[usize], textures_count: usize) -> String {
+    let mut effects = String::from("  <library_effects>\n");
+    let mut materials = String::from("  <library_materials>\n");
+
+    for (mesh_idx, mesh) in skin.meshes.iter().enumerate() {
+        let texture_index = textures_selecteds.get(mesh_idx).copied().unwrap_or(0);
+        let mesh_name = xml_escape(&mesh.submesh.name);
+
+        let has_texture = texture_index < textures_count;
+
+        effects.push_str(&format!("    <effect id=\"effect{mesh_idx}\">\n"));
+        effects.push_str("      <profile_COMMON>\n");
+        if has_texture {
+            effects.push_str(&format!(
+                "        <newparam sid=\"surface{mesh_idx}\">\n          <surface type=\"2D\">\n            <init_from>image{texture_index}</init_from>\n          </surface>\n        </newparam>\n"
+            ));
+            effects.push_str(&format!(
+                "        <newparam sid=\"sampler{mesh_idx}\">\n          <sampler2D>\n            <source>surface{mesh_idx}</source>\n          </sampler2D>\n        </newparam>\n"
+            ));
+        }
+        effects.push_str("        <technique sid=\"common\">\n          <lambert>\n");
+        if has_texture {
+            effects.push_str(&format!(
+                "            <diffuse>\n              <texture texture=\"sampler{mesh_idx}\" texcoord=\"UVMap\"/>\n            </diffuse>\n"
+            ));
+        } else {
+            effects.push_str("            <diffuse>\n              <color>1 1 1 1</color>\n            </diffuse>\n");
+        }
+        effects.push_str("          </lambert>\n        </technique>\n");
+        effects.push_str("        <extra>\n          <technique profile=\"GOOGLEEARTH\">\n            <double_sided>1</double_sided>\n          </technique>\n        </extra>\n");
+        effects.push_str("      </profile_COMMON>\n");
+        effects.push_str("    </effect>\n");
+
+        materials.push_str(&format!(
+            "    <material id=\"material{mesh_idx}\" name=\"{mesh_name}\">\n      <instance_effect url=\"#effect{mesh_idx}\"/>\n    </material>\n"
+        ));
+    }
+
+    effects.push_str("  </library_effects>\n");
+    materials.push_str("  </library_materials>\n");
+    effects + &materials
+}
+
+fn make_geometry(model_id: &str, skin: &Skin) -> String {
+    let positions: Vec<f32> = skin.vertices.iter().flat_map(|x| x.to_array()).collect();
+    let normals: Vec<f32> = skin.normals.iter().flat_map(|x| x.to_array()).collect();
+    // COLLADA 的纹理坐标原点在左下角
+    let uvs: Vec<f32> = skin.uvs.iter().flat_map(|x| [x.x, 1.0f32 - x.y]).collect();
+
+    let mut geometry = String::from("  <library_geometries>\n");
+    geometry.push_str(&format!(
+        "    <geometry id=\"{model_id}-mesh\" name=\"{model_id}\">\n      <mesh>\n"
+    ));
+    geometry.push_str(&float_source(
+        &format!("{model_id}-positions"),
+        &positions,
+        &["X", "Y", "Z"],
+    ));
+    geometry.push_str(&float_source(
+        &format!("{model_id}-normals"),
+        &normals,
+        &["X", "Y", "Z"],
+    ));
+    geometry.push_str(&float_source(&format!("{model_id}-uvs"), &uvs, &["S", "T"]));
+    geometry.push_str(&format!(
+        "        <vertices id=\"{model_id}-vertices\">\n          <input semantic=\"POSITION\" source=\"#{model_id}-positions\"/>\n        </vertices>\n"
+    ));
+
+    for (mesh_idx, mesh) in skin.meshes.iter().enumerate() {
+        let indices_offset = mesh.submesh.indices_offset as usize;
+        let indices_count = mesh.submesh.indices_count as usize;
+        let indices = &skin.indices[indices_offset..indices_offset + indices_count];
+
+        geometry.push_str(&format!(
+            "        <triangles material=\"material{mesh_idx}\" count=\"{}\">\n",
+            indices_count / 3
+        ));
+        geometry.push_str(&format!(
+            "          <input semantic=\"VERTEX\" source=\"#{model_id}-vertices\" offset=\"0\"/>\n          <input semantic=\"NORMAL\" source=\"#{model_id}-normals\" offset=\"0\"/>\n          <input semantic=\"TEXCOORD\" source=\"#{model_id}-uvs\" offset=\"0\" set=\"0\"/>\n"
+        ));
+        geometry.push_str(&format!("          <p>{}</p>\n", join(indices)));
+        geometry.push_str("        </triangles>\n");
+    }
+
+    geometry.push_str("      </mesh>\n    </geometry>\n");
+    geometry.push_str("  </library_geometries>\n");
+    geometry
+}
+
+fn make_controller(model_id: &str, skin: &Skin, skeleton: &Skeleton, joint_ids: &[String]) -> String {
+    let bind_poses: Vec<f32> = skeleton
+        .joints
+        .iter()
+        .flat_map(|joint| {
+            let mut igm = joint.inverse_global_matrix;
+            igm.x_axis.w = 0.0f32;
+            igm.y_axis.w = 0.0f32;
+            igm.z_axis.w = 0.0f32;
+            igm.w_axis.w = 1.0f32;
+            row_major(&igm)
+        })
+        .collect();
+
+    let mut weights = vec![];
+    let mut vcount = vec![];
+    let mut v = vec![];
+    for i in 0..skin.vertices.len() {
+        let mut count = 0;
+        for j in 0..4 {
+            let weight = skin.weights[i][j];
+            if weight > 0.0f32 {
+                v.push(skin.influences[i][j] as usize);
+                v.push(weights.len());
+                weights.push(weight);
+                count += 1;
+            }
+        }
+        vcount.push(count);
+    }
+
+    let mut controller = String::from("  <library_controllers>\n");
+    controller.push_str(&format!(
+        "    <controller id=\"{model_id}-skin\" name=\"{model_id}\">\n      <skin source=\"#{model_id}-mesh\">\n"
+    ));
+    controller.push_str(&format!(
+        "        <bind_shape_matrix>{}</bind_shape_matrix>\n",
+        join(&row_major(&glam::Mat4::IDENTITY))
+    ));
+
+    controller.push_str(&format!(
+        "        <source id=\"{model_id}-joints\">\n          <Name_array id=\"{model_id}-joints-array\" count=\"{}\">{}</Name_array>\n",
+        joint_ids.len(),
+        joint_ids.join(" ")
+    ));
+    controller.push_str(&format!(
+        "          <technique_common>\n            <accessor source=\"#{model_id}-joints-array\" count=\"{}\" stride=\"1\">\n              <param name=\"JOINT\" type=\"name\"/>\n            </accessor>\n          </technique_common>\n        </source>\n",
+        joint_ids.len()
+    ));
+
+    controller.push_str(&format!(
+        "        <source id=\"{model_id}-bind-poses\">\n          <float_array id=\"{model_id}-bind-poses-array\" count=\"{}\">{}</float_array>\n",
+        bind_poses.len(),
+        join(&bind_poses)
+    ));
+    controller.push_str(&format!(
+        "          <technique_common>\n            <accessor source=\"#{model_id}-bind-poses-array\" count=\"{}\" stride=\"16\">\n              <param name=\"TRANSFORM\" type=\"float4x4\"/>\n            </accessor>\n          </technique_common>\n        </source>\n",
+        skeleton.joints.len()
+    ));
+
+    controller.push_str(&float_source(
+        &format!("{model_id}-weights"),
+        &weights,
+        &["WEIGHT"],
+    ));
+
+    controller.push_str(&format!(
+        "        <joints>\n          <input semantic=\"JOINT\" source=\"#{model_id}-joints\"/>\n          <input semantic=\"INV_BIND_MATRIX\" source=\"#{model_id}-bind-poses\"/>\n        </joints>\n"
+    ));
+    controller.push_str(&format!(
+        "        <vertex_weights count=\"{}\">\n          <input semantic=\"JOINT\" source=\"#{model_id}-joints\" offset=\"0\"/>\n          <input semantic=\"WEIGHT\" source=\"#{model_id}-weights\" offset=\"1\"/>\n",
+        skin.vertices.len()
+    ));
+    controller.push_str(&format!("          <vcount>{}</vcount>\n", join(&vcount)));
+    controller.push_str(&format!("          <v>{}</v>\n", join(&v)));
+    controller.push_str("        </vertex_weights>\n");
+    controller.push_str("      </skin>\n    </controller>\n");
+    controller.push_str("  </library_controllers>\n");
+    controller
+}
+
+fn make_animations(
+    mind_model: &MindModel,
+    skeleton: &Skeleton,
+    joint_ids: &[String],
+    options: &OptionsJson,
+    conversion: &Conversion,
+) -> String {
+    let mut animations = String::from("  <library_animations>\n");
+    let mut clips = String::from("  <library_animation_clips>\n");
+
+    for i in 0..mind_model.animations.len() {
+        let animation_export = export::animation_export_of(options, mind_model, i);
+        if !animation_export.export {
+            continue;
+        }
+
+        let animation = &conversion.animation(&mind_model.animations[i]);
+        let clip_id = collada_id(&mind_model.animations_file_names[i]);
+        let (sample_times, times) = export::animation_times(animation, &animation_export);
+
+        clips.push_str(&format!(
+            "    <animation_clip id=\"{clip_id}\" name=\"{}\" start=\"0\" end=\"{}\">\n",
+            xml_escape(&mind_model.animations_file_names[i]),
+            times.last().copied().unwrap_or(0.0f32)
+        ));
+
+        for (joint_index, joint_id) in joint_ids.iter().enumerate() {
+            let animation_id = format!("{clip_id}-{joint_index}");
+            let matrices: Vec<f32> = sample_times
+                .iter()
+                .flat_map(|time| {
+                    row_major(&joint_local_matrix(animation, skeleton, joint_index, *time))
+                })
+                .collect();
+            let interpolations = vec!["LINEAR"; times.len()];
+
+            animations.push_str(&format!("    <animation id=\"{animation_id}\">\n"));
+            animations.push_str(&float_source(
+                &format!("{animation_id}-input"),
+                &times,
+                &["TIME"],
+            ));
+            animations.push_str(&format!(
+                "        <source id=\"{animation_id}-output\">\n          <float_array id=\"{animation_id}-output-array\" count=\"{}\">{}</float_array>\n",
+                matrices.len(),
+                join(&matrices)
+            ));
+            animations.push_str(&format!(
+                "          <technique_common>\n            <accessor source=\"#{animation_id}-output-array\" count=\"{}\" stride=\"16\">\n              <param name=\"TRANSFORM\" type=\"float4x4\"/>\n            </accessor>\n          </technique_common>\n        </source>\n",
+                times.len()
+            ));
+            animations.push_str(&format!(
+                "        <source id=\"{animation_id}-interpolation\">\n          <Name_array id=\"{animation_id}-interpolation-array\" count=\"{}\">{}</Name_array>\n",
+                interpolations.len(),
+                interpolations.join(" ")
+            ));
+            animations.push_str(&format!(
+                "          <technique_common>\n            <accessor source=\"#{animation_id}-interpolation-array\" count=\"{}\" stride=\"1\">\n              <param name=\"INTERPOLATION\" type=\"name\"/>\n            </accessor>\n          </technique_common>\n        </source>\n",
+                interpolations.len()
+            ));
+            animations.push_str(&format!(
+                "        <sampler id=\"{animation_id}-sampler\">\n          <input semantic=\"INPUT\" source=\"#{animation_id}-input\"/>\n          <input semantic=\"OUTPUT\" source=\"#{animation_id}-output\"/>\n          <input semantic=\"INTERPOLATION\" source=\"#{animation_id}-interpolation\"/>\n        </sampler>\n"
+            ));
+            animations.push_str(&format!(
+                "        <channel source=\"#{animation_id}-sampler\" target=\"{joint_id}/transform\"/>\n"
+            ));
+            animations.push_str("    </animation>\n");
+
+            clips.push_str(&format!(
+                "      <instance_animation url=\"#{animation_id}\"/>\n"
+            ));
+        }
+
+        clips.push_str("    </animation_clip>\n");
+    }
+
+    animations.push_str("  </library_animations>\n");
+    clips.push_str("  </library_animation_clips>\n");
+    animations + &clips
+}
+
+fn make_visual_scene(
+    model_id: &str,
+    model_name: &str,
+    skin: &Skin,
+    skeleton: &Skeleton,
+    joint_ids: &[String],
+) -> String {
+    let mut scene = String::from("  <library_visual_scenes>\n");
+    scene.push_str("    <visual_scene id=\"Scene\" name=\"Scene\">\n");
+
+    let roots: Vec<usize> = skeleton
+        .joints
+        .iter()
+        .enumerate()
+        .filter(|(_, joint)| joint.parent_id < 0)
+        .map(|(i, _)| i)
+        .collect();
+
+    scene.push_str(&format!(
+        "      <node id=\"RootMaster{model_id}\" name=\"RootMaster{}\" type=\"NODE\">\n",
+        xml_escape(model_name)
+    ));
+    for root in roots.iter() {
+        make_joint_node(&mut scene, skeleton, joint_ids, *root, 4);
+    }
+    scene.push_str("      </node>\n");
+
+    scene.push_str(&format!(
+        "      <node id=\"{model_id}\" name=\"{}\" type=\"NODE\">\n",
+        xml_escape(model_name)
+    ));
+    scene.push_str(&format!(
+        "        <instance_controller url=\"#{model_id}-skin\">\n"
+    ));
+    for root in roots.iter() {
+        scene.push_str(&format!(
+            "          <skeleton>#{}</skeleton>\n",
+            joint_ids[*root]
+        ));
+    }
+    scene.push_str("          <bind_material>\n            <technique_common>\n");
+    for mesh_idx in 0..skin.meshes.len() {
+        scene.push_str(&format!(
+            "              <instance_material symbol=\"material{mesh_idx}\" target=\"#material{mesh_idx}\">\n                <bind_vertex_input semantic=\"UVMap\" input_semantic=\"TEXCOORD\" input_set=\"0\"/>\n              </instance_material>\n"
+        ));
+    }
+    scene.push_str("            </technique_common>\n          </bind_material>\n");
+    scene.push_str("        </instance_controller>\n");
+    scene.push_str("      </node>\n");
+
+    scene.push_str("    </visual_scene>\n");
+    scene.push_str("  </library_visual_scenes>\n");
+    scene
+}
+
+fn make_joint_node(scene: &mut String, skeleton: &Skeleton, joint_ids: &[String], joint_index: usize, depth: usize) {
+    let joint = &skeleton.joints[joint_index];
+    let indent = "  ".repeat(depth);
+    let joint_id = &joint_ids[joint_index];
+
+    scene.push_str(&format!(
+        "{indent}<node id=\"{joint_id}\" sid=\"{joint_id}\" name=\"{}\" type=\"JOINT\">\n",
+        xml_escape(&joint.name)
+    ));
+    scene.push_str(&format!(
+        "{indent}  <matrix sid=\"transform\">{}</matrix>\n",
+        join(&row_major(&joint.local_matrix))
+    ));
+    for child in joint.children.iter() {
+        make_joint_node(scene, skeleton, joint_ids, *child, depth + 1);
+    }
+    scene.push_str(&format!("{indent}</node>\n"));
+}
+
+fn joint_local_matrix(
+    animation: &Animation,
+    skeleton: &Skeleton,
+    joint_index: usize,
+    time: f32,
+) -> glam::Mat4 {
+    let skeleton_joint = &skeleton.joints[joint_index];
+    match animation
+        .joints
+        .iter()
+        .find(|joint| joint.hash == skeleton_joint.hash)
+    {
+        Some(joint) => {
+            let (translation_min, translation_max, translation_lerp_value) =
+                anm::find_in_nearest_time(&joint.translations, time);
+            let (rotation_min, rotation_max, rotation_lerp_value) =
+                anm::find_in_nearest_time(&joint.rotations, time);
+            let (scale_min, scale_max, scale_lerp_value) =
+                anm::find_in_nearest_time(&joint.scales, time);
+
+            glam::Mat4::from_scale_rotation_translation(
+                scale_min.lerp(scale_max, scale_lerp_value),
+                rotation_min.lerp(rotation_max, rotation_lerp_value),
+                translation_min.lerp(translation_max, translation_lerp_value),
+            )
+        }
+        None => skeleton_joint.local_matrix,
+    }
+}
+
+fn float_source(id: &str, values: &[f32], params: &[&str]) -> String {
+    let stride = params.len();
+    let mut source = format!(
+        "        <source id=\"{id}\">\n          <float_array id=\"{id}-array\" count=\"{}\">{}</float_array>\n",
+        values.len(),
+        join(values)
+    );
+    source.push_str(&format!(
+        "          <technique_common>\n            <accessor source=\"#{id}-array\" count=\"{}\" stride=\"{stride}\">\n",
+        values.len() / stride
+    ));
+    for param in params {
+        source.push_str(&format!(
+            "              <param name=\"{param}\" type=\"float\"/>\n"
+        ));
+    }
+    source.push_str("            </accessor>\n          </technique_common>\n        </source>\n");
+    source
+}
+
+// COLLADA 矩阵按行主序书写，glam 为列主序
+fn row_major(matrix: &glam::Mat4) -> [f32; 16] {
+    matrix.transpose().to_cols_array()
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// 不同的关节名称转换后可能相同，例如 "R Arm" 和 "R_Arm"，重复的 id 加上 '_' 后缀
+fn joint_ids(model_id: &str, skeleton: &Skeleton) -> Vec<String> {
+    let mut used: BTreeSet<String> = BTreeSet::from([String::from(model_id), String::from("Scene")]);
+    skeleton
+        .joints
+        .iter()
+        .map(|joint| {
+            let mut id = collada_id(&joint.name);
+            while !used.insert(id.clone()) {
+                id.push('_');
+            }
+            id
+        })
+        .collect()
+}
+
+fn collada_id(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn xml_escape(string: &str) -> String {
+    string
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,71 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::lol::skl::Type;
+
+// 写入 glTF extras 的 League 元数据，导入时读回以保证哈希和名称在往返后保持不变
+
+// 关节节点
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JointExtras {
+    pub hash: u32,
+}
+
+// RootMaster 节点
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SkeletonExtras {
+    #[serde(rename = "sklType")]
+    pub skl_type: String,
+
+    #[serde(rename = "sklVersion")]
+    pub skl_version: u32,
+}
+
+// 网格，submeshes 与 primitives 一一对应
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MeshExtras {
+    #[serde(rename = "sknMajor")]
+    pub skn_major: u16,
+
+    #[serde(rename = "sknMinor")]
+    pub skn_minor: u16,
+
+    pub submeshes: Vec<String>,
+}
+
+// 材质
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MaterialExtras {
+    pub submesh: String,
+
+    pub texture: String,
+}
+
+// 动画
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AnimationExtras {
+    pub fps: f32,
+
+    pub format: String,
+}
+
+impl SkeletonExtras {
+    pub fn new(skl_type: &Type, skl_version: u32) -> SkeletonExtras {
+        SkeletonExtras {
+            skl_type: String::from(match skl_type {
+                Type::Classic => "Classic",
+                Type::Version2 => "Version2",
+            }),
+            skl_version,
+        }
+    }
+}
+
+pub fn to_extras<T: Serialize>(value: &T) -> gltf::json::Extras {
+    serde_json::value::to_raw_value(value).ok()
+}
+
+pub fn from_extras<T: DeserializeOwned>(extras: &gltf::json::Extras) -> Option<T> {
+    extras
+        .as_ref()
+        .and_then(|raw| serde_json::from_str(raw.get()).ok())
+}
@@ -0,0 +1,1617 @@
+use std::{
+    borrow,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    fs::File,
+    io::{Cursor, Write},
+    mem,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use gltf::{
+    json::{
+        accessor, animation, buffer, extensions, material,
+        mesh::Primitive,
+        scene, texture,
+        validation::{Checked::Valid, USize64},
+        Accessor, Animation as GltfAnimation, Asset, Buffer, Image, Index, Material, Mesh, Node,
+        Root, Scene, Skin as GltfSkin, Texture, Value,
+    },
+    material::AlphaMode,
+    mesh::Mode,
+    Semantic,
+};
+
+pub mod batch;
+pub mod compressed;
+pub mod conversion;
+pub mod dae;
+pub mod extras;
+pub mod obj;
+pub mod scene;
+pub mod textures;
+pub mod usd;
+
+use crate::{
+    config_json::{AnimationExportJson, OptionsJson},
+    gls::glam_read,
+    lol::{anm, Animation, Skeleton, Skin},
+    MindModel,
+};
+
+pub const CONFLICT_MODES: [&str; 3] = [
+    "覆盖(Overwrite)",
+    "添加后缀(Suffix)",
+    "中止(Abort)",
+];
+
+pub const ALPHA_MODES: [&str; 4] = [
+    "自动(Auto)",
+    "不透明(Opaque)",
+    "遮罩(Mask)",
+    "混合(Blend)",
+];
+
+pub const TEXTURE_FORMATS: [&str; 2] = [
+    "PNG",
+    "DDS (MSFT_texture_dds) + PNG",
+];
+
+pub const UP_AXES: [&str; 2] = ["Y", "Z"];
+
+#[derive(Clone)]
+pub struct ExportOptions {
+    // 0 gltf, 1 glb, 2 dae, 3 obj, 4 usda
+    pub export_as: u8,
+    // 场景导出只支持 0 gltf, 1 glb，与单个模型的导出格式分开保存
+    pub scene_export_as: u8,
+    pub output_dir: String,
+    // 支持 {name} 和 {animation} 占位符
+    pub file_name: String,
+    // CONFLICT_MODES 的索引
+    pub conflict: usize,
+    pub open_folder: bool,
+    pub only_visible_meshes: bool,
+    // TEXTURE_FORMATS 的索引，只用于 gltf/glb
+    pub texture_format: usize,
+    // 单位缩放，例如 0.01 把游戏单位转换为米
+    pub unit_scale: f32,
+    // UP_AXES 的索引
+    pub up_axis: usize,
+    // 镜像 X 轴，转换为右手坐标系
+    pub flip_handedness: bool,
+    // 最近一次导出的结果，显示在导出面板中
+    pub message: String,
+}
+
+impl ExportOptions {
+    pub fn new() -> ExportOptions {
+        let output_dir = std::env::current_dir()
+            .map(|dir| dir.join("export"))
+            .unwrap_or_else(|_| PathBuf::from("export"));
+
+        ExportOptions {
+            export_as: 0,
+            scene_export_as: 0,
+            output_dir: output_dir.to_string_lossy().into_owned(),
+            file_name: String::from("{name}"),
+            conflict: 0,
+            open_folder: true,
+            only_visible_meshes: true,
+            texture_format: 0,
+            unit_scale: 1.0f32,
+            up_axis: 0,
+            flip_handedness: false,
+            message: String::new(),
+        }
+    }
+
+    pub(crate) fn conversion(&self) -> conversion::Conversion {
+        conversion::Conversion::new(self.unit_scale, self.up_axis == 1, self.flip_handedness)
+    }
+
+    // 解析输出目录和文件名，glb 直接写入输出目录，其他格式各自使用一个子目录
+    pub(crate) fn resolve_export_path(&self, name: &str, animation_name: &str) -> Result<(PathBuf, String), String> {
+        let output_dir = PathBuf::from(&self.output_dir);
+        fs::create_dir_all(&output_dir).map_err(|error| format!("Could not create export dirs: {error}"))?;
+
+        let file_name = self.format_file_name(name, animation_name);
+
+        if self.export_as == 1 {
+            let (_, file_name) = self.resolve_conflict(&output_dir, &file_name, Some("glb"))?;
+            Ok((output_dir, file_name))
+        } else {
+            let (export_path, file_name) = self.resolve_conflict(&output_dir, &file_name, None)?;
+            fs::create_dir_all(&export_path).map_err(|error| format!("Could not create export dirs: {error}"))?;
+            Ok((export_path, file_name))
+        }
+    }
+
+    fn format_file_name(&self, model_name: &str, animation_name: &str) -> String {
+        let file_name = self
+            .file_name
+            .replace("{name}", model_name)
+            .replace("{animation}", animation_name);
+        let file_name: String = file_name
+            .chars()
+            .map(|c| if "\\/:*?\"<>|".contains(c) { '_' } else { c })
+            .collect();
+
+        if file_name.trim().is_empty() {
+            String::from(model_name)
+        } else {
+            file_name
+        }
+    }
+
+    // 根据冲突处理方式决定最终的输出路径
+    fn resolve_conflict(&self, output_dir: &Path, file_name: &str, extension: Option<&str>) -> Result<(PathBuf, String), String> {
+        let make_path = |file_name: &str| match extension {
+            Some(extension) => output_dir.join(format!("{file_name}.{extension}")),
+            None => output_dir.join(file_name),
+        };
+
+        let path = make_path(file_name);
+        if !path.exists() || self.conflict == 0 {
+            return Ok((path, String::from(file_name)));
+        }
+
+        if self.conflict == 2 {
+            return Err(format!("Export target already exists: {}", path.display()));
+        }
+
+        let mut i = 1;
+        loop {
+            let suffixed_name = format!("{file_name}_{i}");
+            let path = make_path(&suffixed_name);
+            if !path.exists() {
+                return Ok((path, suffixed_name));
+            }
+            i += 1;
+        }
+    }
+}
+
+// 打开导出目录，不支持的平台上什么也不做
+pub fn open_folder(path: &Path) {
+    let program = if cfg!(target_os = "windows") {
+        Some("explorer")
+    } else if cfg!(target_os = "macos") {
+        Some("open")
+    } else if std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        Some("xdg-open")
+    } else {
+        None
+    };
+
+    if let Some(program) = program {
+        if let Err(error) = Command::new(program).arg(path).spawn() {
+            println!("Could not open export folder: {error}");
+        }
+    }
+}
+
+// 导出过程中的 panic 转换为错误信息，查看器和命令行不会因此退出
+pub fn catch_panic<T>(export: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(export)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|x| x.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("unknown error"));
+        Err(format!("Export panicked: {message}"))
+    })
+}
+
+pub fn export_model(
+    export_options: &ExportOptions,
+    model_name: &String,
+    mind_model: &MindModel,
+    export_animations: bool,
+    options: &OptionsJson,
+) -> Result<PathBuf, String> {
+    let export_as = export_options.export_as;
+    let animation_name = mind_model
+        .animations_file_names
+        .get(mind_model.animation_selected)
+        .map(String::as_str)
+        .unwrap_or("");
+    let (export_path, file_name) = export_options.resolve_export_path(model_name, animation_name)?;
+
+    let (visible_skin, textures_selecteds) = export_skin(export_options, mind_model);
+    let skin = visible_skin.as_ref().unwrap_or(&mind_model.skin);
+
+    if export_as >= 2 {
+        match export_as {
+            2 => dae::export_dae(
+                &export_path,
+                &file_name,
+                model_name,
+                mind_model,
+                skin,
+                &textures_selecteds,
+                export_animations,
+                options,
+                &export_options.conversion(),
+            )?,
+            3 => obj::export_obj(
+                &export_path,
+                &file_name,
+                mind_model,
+                options,
+                export_options.only_visible_meshes,
+                &export_options.conversion(),
+            )?,
+            _ => usd::export_usd(
+                &export_path,
+                &file_name,
+                model_name,
+                mind_model,
+                skin,
+                &textures_selecteds,
+                export_animations,
+                options,
+                &export_options.conversion(),
+            )?,
+        }
+        println!("Export completed. {}", export_path.display());
+        if export_options.open_folder {
+            open_folder(&export_path);
+        }
+        return Ok(export_path);
+    }
+
+    let animation_indices: Vec<usize> = if export_animations {
+        (0..mind_model.animations.len())
+            .filter(|i| animation_export_of(options, mind_model, *i).export)
+            .collect()
+    } else {
+        vec![]
+    };
+
+    let (root, data, texture_extension_sources) = make_model_root(
+        model_name,
+        mind_model,
+        skin,
+        &textures_selecteds,
+        &animation_indices,
+        options,
+        &export_path,
+        "textures",
+        export_options,
+    )?;
+
+    write_root(root, data, &texture_extension_sources, &export_path, &file_name, export_options)
+}
+
+// 跳过隐藏的子网格，纹理选择随子网格一起过滤；返回 None 时使用原始网格
+pub(crate) fn export_skin(
+    export_options: &ExportOptions,
+    mind_model: &MindModel,
+) -> (Option<Skin>, Vec<usize>) {
+    if !export_options.only_visible_meshes {
+        return (None, mind_model.textures_selecteds.clone());
+    }
+
+    let textures_selecteds: Vec<usize> = mind_model
+        .textures_selecteds
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| mind_model.show_meshes.get(*i).copied().unwrap_or(true))
+        .map(|(_, texture_index)| *texture_index)
+        .collect();
+
+    (Some(mind_model.skin.filtered(&mind_model.show_meshes)), textures_selecteds)
+}
+
+// 构建单个模型的 glTF，返回 (root, 缓冲区数据, 每个纹理的扩展图像索引)
+pub(crate) fn make_model_root(
+    model_name: &String,
+    mind_model: &MindModel,
+    skin: &Skin,
+    textures_selecteds: &[usize],
+    animation_indices: &[usize],
+    options: &OptionsJson,
+    export_path: &Path,
+    // gltf 图像相对于 export_path 的目录
+    texture_dir: &str,
+    export_options: &ExportOptions,
+) -> Result<(Root, Vec<u8>, Vec<Option<u32>>), String> {
+    let export_as = export_options.export_as;
+
+    let conversion = export_options.conversion();
+    let skin = &conversion.skin(skin);
+    let skeleton = &conversion.skeleton(&mind_model.skeleton);
+
+    let mut accessor_index = 0;
+    let mut buffer_view_index = 0;
+    let mut buffer_view_offset = 0;
+
+    let (mut buffer_views, mut accessors, mesh, mesh_data) = make_mesh(
+        skin,
+        &[],  // 不再需要textures_selecteds参数
+        &mut accessor_index,
+        &mut buffer_view_index,
+        &mut buffer_view_offset,
+    );
+
+    let mut all_datas = vec![mesh_data];
+
+    let (materials, textures, images, texture_data_buffer_views, texture_extension_sources) = make_material(
+        &mind_model.textures_paths,
+        &mind_model.textures_file_names,  // 新增参数
+        skin,                             // 新增参数
+        export_path,
+        texture_dir,
+        export_as,
+        &mut buffer_view_index,
+        &mut buffer_view_offset,
+        textures_selecteds,              // 新增参数：传递当前选择的纹理索引
+        &options.alpha_modes,
+        export_options.texture_format,
+    )?;
+
+    if let Some((texture_data, texture_buffer_views)) = texture_data_buffer_views {
+        all_datas.push(texture_data);
+        buffer_views.extend_from_slice(&texture_buffer_views);
+    }
+
+    let texture_sampler = texture::Sampler {
+        mag_filter: Some(Valid(texture::MagFilter::Linear)),
+        min_filter: Some(Valid(texture::MinFilter::Linear)),
+        name: None,
+        wrap_s: Valid(texture::WrappingMode::Repeat),
+        wrap_t: Valid(texture::WrappingMode::Repeat),
+        extensions: None,
+        extras: None,
+    };
+
+    let (nodes, gltf_skin, ibm_data, ibm_buffer_view, ibm_accessor) = make_skeleton(
+        model_name,
+        skeleton,
+        &mut accessor_index,
+        &mut buffer_view_index,
+        &mut buffer_view_offset,
+    );
+
+    all_datas.push(ibm_data);
+
+    accessors.push(ibm_accessor);
+    buffer_views.push(ibm_buffer_view);
+
+    let mut animations_gltf = vec![];
+
+    for i in animation_indices.iter().copied() {
+        let animation_export = animation_export_of(options, mind_model, i);
+
+        let (animation_gltf, animation_data, animation_buffer_view, animation_accessor) =
+            make_animation(
+                skeleton,
+                &conversion.animation(&mind_model.animations[i]),
+                &mind_model.animations_file_names[i],
+                &animation_export,
+                &mut accessor_index,
+                &mut buffer_view_index,
+                &mut buffer_view_offset,
+            );
+        animations_gltf.push(animation_gltf);
+        all_datas.push(animation_data);
+        buffer_views.push(animation_buffer_view);
+        accessors.extend_from_slice(&animation_accessor);
+    }
+
+    let all_data_1d = vec_2d_to_vec_1d(&all_datas);
+
+    let buffer = Buffer {
+        byte_length: USize64::from(all_data_1d.len()),
+        extensions: None,
+        extras: None,
+        name: None,
+        uri: None,
+    };
+
+    let scene = Scene {
+        extensions: None,
+        extras: None,
+        name: Some(String::from("Model")),
+        nodes: vec![Index::new(skeleton.joints.len() as u32)],
+    };
+
+    let asset = Asset {
+        copyright: None,
+        extensions: None,
+        extras: None,
+        generator: None,
+        min_version: None,
+        version: String::from("2.0"),
+    };
+
+    let root = Root {
+        accessors,
+        buffers: vec![buffer],
+        buffer_views,
+        meshes: vec![mesh],
+        nodes,
+        skins: vec![gltf_skin],
+        animations: animations_gltf,
+        materials,
+        textures,
+        samplers: vec![texture_sampler],
+        images,
+        scenes: vec![scene],
+        extensions_required: vec![String::from("KHR_materials_unlit")],
+        extensions_used: vec![String::from("KHR_materials_unlit")],
+        asset,
+        scene: Some(Index::new(0)),
+        extensions: None,
+        extras: None,
+        cameras: vec![],
+    };
+
+    Ok((root, all_data_1d, texture_extension_sources))
+}
+
+// 写出 gltf(+bin) 或 glb，返回输出路径
+pub(crate) fn write_root(
+    mut root: Root,
+    data: Vec<u8>,
+    texture_extension_sources: &[Option<u32>],
+    export_path: &Path,
+    file_name: &str,
+    export_options: &ExportOptions,
+) -> Result<PathBuf, String> {
+    let output_path = if export_options.export_as == 0 {
+        root.buffers[0].uri = Some(format!("{file_name}_data.bin"));
+
+        let json_value = add_texture_extensions(&root, texture_extension_sources, export_options.texture_format)?;
+        let json_string = serde_json::to_string_pretty(&json_value)
+            .map_err(|error| format!("Could not serialize gltf: {error}"))?;
+
+        let output_gltf = export_path.join(format!("{file_name}.gltf"));
+        write_file(&output_gltf, json_string.as_bytes())?;
+
+        let output_data_bin = export_path.join(format!("{file_name}_data.bin"));
+        write_file(&output_data_bin, &data)?;
+
+        export_path.to_path_buf()
+    } else {
+        let json_value = add_texture_extensions(&root, texture_extension_sources, export_options.texture_format)?;
+        let json_string = serde_json::to_string(&json_value)
+            .map_err(|error| format!("Could not serialize glb: {error}"))?;
+
+        let glb = gltf::binary::Glb {
+            header: gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                length: 0,
+            },
+            bin: Some(borrow::Cow::Owned(data)),
+            json: borrow::Cow::Owned(json_string.into_bytes()),
+        };
+
+        let output_glb = export_path.join(format!("{file_name}.glb"));
+        let writer_glb = File::create(&output_glb)
+            .map_err(|error| format!("Could not create {}: {error}", output_glb.display()))?;
+        glb.to_writer(writer_glb)
+            .map_err(|error| format!("Could not write glb: {error}"))?;
+
+        output_glb
+    };
+
+    println!("Export completed. {}", output_path.display());
+    if export_options.open_folder {
+        open_folder(export_path);
+    }
+
+    Ok(output_path)
+}
+
+pub(crate) fn write_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let mut writer = File::create(path)
+        .map_err(|error| format!("Could not create {}: {error}", path.display()))?;
+    writer
+        .write_all(contents)
+        .map_err(|error| format!("Could not write {}: {error}", path.display()))
+}
+
+fn make_animation(
+    skeleton: &Skeleton,
+    animation: &Animation,
+    animations_file_name: &String,
+    animation_export: &AnimationExportJson,
+    accessor_index: &mut u32,
+    buffer_view_index: &mut u32,
+    buffer_view_offset: &mut usize,
+) -> (GltfAnimation, Vec<u8>, buffer::View, Vec<Accessor>) {
+    let (sample_times, times) = animation_times(animation, animation_export);
+    let frame_count = times.len();
+
+    let times_length = times.len() * mem::size_of::<f32>();
+
+    let filtered_animation_joints: Vec<(usize, &anm::JointAnm)> = animation
+        .joints
+        .iter()
+        .filter_map(|animation_joint| {
+            let joint_index = skeleton
+                .joints
+                .iter()
+                .position(|skeleton_joint| skeleton_joint.hash == animation_joint.hash);
+            joint_index.map(|joint_index| (joint_index, animation_joint))
+        })
+        .collect();
+
+    let translations_data: Vec<Vec<u8>> = filtered_animation_joints
+        .iter()
+        .map(|(_, animation_joint)| {
+            any_vec_as_vec_u8(
+                &sample_times
+                    .iter()
+                    .map(|time| {
+                        let (min, max, lerp_value) =
+                            anm::find_in_nearest_time(&animation_joint.translations, *time);
+                        min.lerp(max, lerp_value)
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    let rotations_data: Vec<Vec<u8>> = filtered_animation_joints
+        .iter()
+        .map(|(_, animation_joint)| {
+            any_vec_as_vec_u8(
+                &sample_times
+                    .iter()
+                    .map(|time| {
+                        let (min, max, lerp_value) =
+                            anm::find_in_nearest_time(&animation_joint.rotations, *time);
+                        min.lerp(max, lerp_value)
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    let scales_data: Vec<Vec<u8>> = filtered_animation_joints
+        .iter()
+        .map(|(_, animation_joint)| {
+            any_vec_as_vec_u8(
+                &sample_times
+                    .iter()
+                    .map(|time| {
+                        let (min, max, lerp_value) =
+                            anm::find_in_nearest_time(&animation_joint.scales, *time);
+                        min.lerp(max, lerp_value)
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+
+    let translations_1d = vec_2d_to_vec_1d(&translations_data);
+    let rotations_1d = vec_2d_to_vec_1d(&rotations_data);
+    let scales_1d = vec_2d_to_vec_1d(&scales_data);
+
+    let animation_total_data = vec_2d_to_vec_1d(&[
+        any_vec_as_vec_u8(&times),
+        translations_1d,
+        rotations_1d,
+        scales_1d,
+    ]);
+
+    let animation_buffer_view =
+        make_buffer_view(animation_total_data.len(), Some(*buffer_view_offset), None);
+    let animation_buffer_view_index = *buffer_view_index;
+    *buffer_view_index += 1;
+    *buffer_view_offset += animation_total_data.len();
+
+    let times_accessor = make_accessor(
+        frame_count,
+        None,
+        animation_buffer_view_index,
+        accessor::Type::Scalar,
+        accessor::ComponentType::F32,
+        Some(Value::from(vec![*times.first().unwrap()])),
+        Some(Value::from(vec![*times.last().unwrap()])),
+    );
+    let accessor_times = *accessor_index;
+    *accessor_index += 1;
+
+    let mut sampler_index = 0;
+    let mut animation_offset = times_length;
+
+    let (translations_channels, translations_samplers, translations_accessors) = make_trs(
+        &filtered_animation_joints,
+        accessor::Type::Vec3,
+        accessor::ComponentType::F32,
+        animation::Property::Translation,
+        frame_count,
+        mem::size_of::<glam::Vec3>(),
+        accessor_times,
+        &mut sampler_index,
+        &mut animation_offset,
+        animation_buffer_view_index,
+        accessor_index,
+    );
+
+    let (rotations_channels, rotations_samplers, rotations_accessors) = make_trs(
+        &filtered_animation_joints,
+        accessor::Type::Vec4,
+        accessor::ComponentType::F32,
+        animation::Property::Rotation,
+        frame_count,
+        mem::size_of::<glam::Vec4>(),
+        accessor_times,
+        &mut sampler_index,
+        &mut animation_offset,
+        animation_buffer_view_index,
+        accessor_index,
+    );
+
+    let (scales_channels, scales_samplers, scales_accessors) = make_trs(
+        &filtered_animation_joints,
+        accessor::Type::Vec3,
+        accessor::ComponentType::F32,
+        animation::Property::Scale,
+        frame_count,
+        mem::size_of::<glam::Vec3>(),
+        accessor_times,
+        &mut sampler_index,
+        &mut animation_offset,
+        animation_buffer_view_index,
+        accessor_index,
+    );
+
+    let mut accessors = vec![times_accessor];
+    accessors.extend_from_slice(&translations_accessors);
+    accessors.extend_from_slice(&rotations_accessors);
+    accessors.extend_from_slice(&scales_accessors);
+
+    let mut channels = vec![];
+    channels.extend_from_slice(&translations_channels);
+    channels.extend_from_slice(&rotations_channels);
+    channels.extend_from_slice(&scales_channels);
+
+    let mut samplers = vec![];
+    samplers.extend_from_slice(&translations_samplers);
+    samplers.extend_from_slice(&rotations_samplers);
+    samplers.extend_from_slice(&scales_samplers);
+
+    let animation = GltfAnimation {
+        extensions: None,
+        extras: extras::to_extras(&extras::AnimationExtras {
+            fps: animation.fps,
+            format: animation.format.clone(),
+        }),
+        channels,
+        name: Some(animations_file_name.to_owned()),
+        samplers,
+    };
+
+    (
+        animation,
+        animation_total_data,
+        animation_buffer_view,
+        accessors,
+    )
+}
+
+pub(crate) fn animation_export_of(
+    options: &OptionsJson,
+    mind_model: &MindModel,
+    animation_index: usize,
+) -> AnimationExportJson {
+    let name = &mind_model.animations_file_names[animation_index];
+    options.export_animation(name).cloned().unwrap_or_else(|| {
+        AnimationExportJson::new(name, mind_model.animations[animation_index].duration)
+    })
+}
+
+// 返回(采样时间, 输出时间)：裁剪范围限制在动画时长内，速度倍率只影响输出的关键帧时间
+pub(crate) fn animation_times(
+    animation: &Animation,
+    animation_export: &AnimationExportJson,
+) -> (Vec<f32>, Vec<f32>) {
+    let start = animation_export.start.clamp(0.0f32, animation.duration);
+    let end = animation_export.end.clamp(start, animation.duration);
+    let speed = animation_export.speed.max(0.00001f32);
+
+    let frame_count = 1.max(((end - start) / animation.frame_delay).ceil() as usize);
+    let mut sample_times: Vec<f32> = (0..frame_count)
+        .map(|i| (start + animation.frame_delay * i as f32).min(end))
+        .collect();
+    // 结束时间通常不在 frame_delay 的整数倍上，单独采样，保证导出设置的 End 帧
+    if sample_times.last().is_some_and(|last| end - last > 1e-4f32) {
+        sample_times.push(end);
+    }
+    let times: Vec<f32> = sample_times
+        .iter()
+        .map(|time| (time - start) / speed)
+        .collect();
+
+    (sample_times, times)
+}
+
+fn make_trs(
+    animation_joints: &[(usize, &anm::JointAnm)],
+    type_: accessor::Type,
+    component_type: accessor::ComponentType,
+    animation_property: animation::Property,
+    times_count: usize,
+    trs_byte_stride: usize,
+    accessor_times: u32,
+    sampler_index: &mut u32,
+    animation_offset: &mut usize,
+    buffer_view_index: u32,
+    accessor_index: &mut u32,
+) -> (
+    Vec<animation::Channel>,
+    Vec<animation::Sampler>,
+    Vec<accessor::Accessor>,
+) {
+    let mut channels = vec![];
+    let mut samplers = vec![];
+    let mut accessors = vec![];
+
+    for (joint_index, _) in animation_joints {
+        accessors.push(make_accessor(
+            times_count,
+            Some(*animation_offset),
+            buffer_view_index,
+            type_,
+            component_type,
+            None,
+            None,
+        ));
+        let accessor_trs = *accessor_index;
+        *accessor_index += 1;
+
+        *animation_offset += times_count * trs_byte_stride;
+
+        channels.push(animation::Channel {
+            sampler: Index::new(*sampler_index),
+            target: animation::Target {
+                extensions: None,
+                extras: None,
+                node: Index::new(*joint_index as u32),
+                path: Valid(animation_property),
+            },
+            extensions: None,
+            extras: None,
+        });
+        samplers.push(animation::Sampler {
+            extensions: None,
+            extras: None,
+            input: Index::new(accessor_times),
+            interpolation: Valid(animation::Interpolation::Linear),
+            output: Index::new(accessor_trs),
+        });
+        *sampler_index += 1;
+    }
+
+    (channels, samplers, accessors)
+}
+
+fn make_mesh(
+    skin: &Skin,
+    texture_selecteds: &[usize],
+    accessor_index: &mut u32,
+    buffer_view_index: &mut u32,
+    buffer_view_offset: &mut usize,
+) -> (Vec<buffer::View>, Vec<Accessor>, Mesh, Vec<u8>) {
+    let vec2_length = mem::size_of::<glam::Vec2>();
+    let vec3_length = mem::size_of::<glam::Vec3>();
+    let vec4_length = mem::size_of::<glam::Vec4>();
+    let u16vec4_length = mem::size_of::<glam_read::U16Vec4>();
+
+    let vertex_count = skin.vertices.len();
+
+    let vertices_length = vertex_count * vec3_length;
+    let normals_length = vertex_count * vec3_length;
+    let uvs_length = vertex_count * vec2_length;
+    let influences_length = vertex_count * u16vec4_length;
+    let weights_length = vertex_count * vec4_length;
+    let indices_length = skin.indices.len() * mem::size_of::<u16>();
+
+    let mut influences = skin.influences.clone();
+    for i in 0..vertex_count {
+        for j in 0..4 {
+            if skin.weights[i][j] == 0.0f32 && skin.influences[i][j] != 0 {
+                influences[i][j] = 0;
+            }
+        }
+    }
+
+    let mut indices_padded = any_vec_as_vec_u8(&skin.indices);
+    vec_4_byte_padded(&mut indices_padded);
+
+    let total_buffers = vec_2d_to_vec_1d(&[
+        any_vec_as_vec_u8(&skin.vertices),
+        any_vec_as_vec_u8(&skin.normals),
+        any_vec_as_vec_u8(&skin.uvs),
+        any_vec_as_vec_u8(&influences),
+        any_vec_as_vec_u8(&skin.weights),
+        indices_padded,
+    ]);
+
+    *buffer_view_offset = total_buffers.len();
+
+    let mut total_buffers_offset = 0;
+
+    let vertices_buffer_view =
+        make_buffer_view(vertices_length, None, Some(buffer::Target::ArrayBuffer));
+    let vertices_accessor = make_accessor(
+        vertex_count,
+        None,
+        *buffer_view_index,
+        accessor::Type::Vec3,
+        accessor::ComponentType::F32,
+        Some(Value::from(skin.bounding_box[0].to_array().to_vec())),
+        Some(Value::from(skin.bounding_box[1].to_array().to_vec())),
+    );
+    *accessor_index += 1;
+    *buffer_view_index += 1;
+
+    total_buffers_offset += vertices_length;
+
+    let normals_buffer_view = make_buffer_view(
+        vertices_length,
+        Some(total_buffers_offset),
+        Some(buffer::Target::ArrayBuffer),
+    );
+    let normals_accessor = make_accessor(
+        vertex_count,
+        None,
+        *buffer_view_index,
+        accessor::Type::Vec3,
+        accessor::ComponentType::F32,
+        None,
+        None,
+    );
+    *accessor_index += 1;
+    *buffer_view_index += 1;
+
+    total_buffers_offset += normals_length;
+
+    let uvs_buffer_view = make_buffer_view(
+        uvs_length,
+        Some(total_buffers_offset),
+        Some(buffer::Target::ArrayBuffer),
+    );
+    let uvs_accessor = make_accessor(
+        vertex_count,
+        None,
+        *buffer_view_index,
+        accessor::Type::Vec2,
+        accessor::ComponentType::F32,
+        None,
+        None,
+    );
+    *accessor_index += 1;
+    *buffer_view_index += 1;
+
+    total_buffers_offset += uvs_length;
+
+    let influences_buffer_view = make_buffer_view(
+        influences_length,
+        Some(total_buffers_offset),
+        Some(buffer::Target::ArrayBuffer),
+    );
+    let influences_accessor = make_accessor(
+        vertex_count,
+        None,
+        *buffer_view_index,
+        accessor::Type::Vec4,
+        accessor::ComponentType::U16,
+        None,
+        None,
+    );
+    *accessor_index += 1;
+    *buffer_view_index += 1;
+
+    total_buffers_offset += influences_length;
+
+    let weights_buffer_view = make_buffer_view(
+        weights_length,
+        Some(total_buffers_offset),
+        Some(buffer::Target::ArrayBuffer),
+    );
+    let weights_accessor = make_accessor(
+        vertex_count,
+        None,
+        *buffer_view_index,
+        accessor::Type::Vec4,
+        accessor::ComponentType::F32,
+        None,
+        None,
+    );
+    *accessor_index += 1;
+    *buffer_view_index += 1;
+
+    total_buffers_offset += weights_length;
+
+    let (indices_buffer_view, indices_accessors, primitives) = make_primitives(
+        skin,
+        texture_selecteds,
+        indices_length,
+        total_buffers_offset,
+        buffer_view_index,
+        accessor_index,
+    );
+
+    let buffer_views = vec![
+        vertices_buffer_view,
+        normals_buffer_view,
+        uvs_buffer_view,
+        influences_buffer_view,
+        weights_buffer_view,
+        indices_buffer_view,
+    ];
+
+    let mut accessors = vec![
+        vertices_accessor,
+        normals_accessor,
+        uvs_accessor,
+        influences_accessor,
+        weights_accessor,
+    ];
+    accessors.extend_from_slice(&indices_accessors);
+
+    let mesh = Mesh {
+        extensions: None,
+        extras: extras::to_extras(&extras::MeshExtras {
+            skn_major: skin.major,
+            skn_minor: skin.minor,
+            submeshes: skin.meshes.iter().map(|mesh| mesh.submesh.name.clone()).collect(),
+        }),
+        name: None,
+        primitives,
+        weights: None,
+    };
+
+    (buffer_views, accessors, mesh, total_buffers)
+}
+
+fn make_primitives(
+    skin: &Skin,
+    texture_selecteds: &[usize],  // 这个参数现在可以移除了，因为我们使用独立索引
+    byte_length: usize,
+    byte_offset: usize,
+    buffer_view_index: &mut u32,
+    accessor_index: &mut u32,
+) -> (buffer::View, Vec<Accessor>, Vec<Primitive>) {
+    let indices_buffer_view = make_buffer_view(
+        byte_length,
+        Some(byte_offset),
+        Some(buffer::Target::ElementArrayBuffer),
+    );
+    let indices_buffer_view_index = *buffer_view_index;
+    *buffer_view_index += 1;
+
+    let mut primitives = vec![];
+    let mut indices_accessors = vec![];
+
+    // 关键修改：为每个网格使用其索引作为材质索引
+    for (mesh_idx, mesh) in skin.meshes.iter().enumerate() {
+        indices_accessors.push(make_accessor(
+            mesh.submesh.indices_count as usize,
+            Some(mesh.submesh.indices_offset as usize * 2),
+            indices_buffer_view_index,
+            accessor::Type::Scalar,
+            accessor::ComponentType::U16,
+            None,
+            None,
+        ));
+
+        primitives.push(Primitive {
+            attributes: {
+                let mut map = BTreeMap::new();
+                map.insert(Valid(Semantic::Positions), Index::new(0));
+                map.insert(Valid(Semantic::Normals), Index::new(1));
+                map.insert(Valid(Semantic::TexCoords(0)), Index::new(2));
+                map.insert(Valid(Semantic::Joints(0)), Index::new(3));
+                map.insert(Valid(Semantic::Weights(0)), Index::new(4));
+                map
+            },
+            extensions: None,
+            extras: None,
+            indices: Some(Index::new(*accessor_index)),
+            material: Some(Index::new(mesh_idx as u32)),  // 关键修改：使用网格索引作为材质索引
+            mode: Valid(Mode::Triangles),
+            targets: None,
+        });
+        *accessor_index += 1;
+    }
+
+    (indices_buffer_view, indices_accessors, primitives)
+}
+
+
+
+
+
+fn make_skeleton(
+    model_name: &String,
+    skeleton: &Skeleton,
+    accessor_index: &mut u32,
+    buffer_view_index: &mut u32,
+    buffer_view_offset: &mut usize,
+) -> (Vec<Node>, GltfSkin, Vec<u8>, buffer::View, Accessor) {
+    let mut nodes = vec![];
+
+    for i in 0..skeleton.joints.len() {
+        let (scale, rotation, translation) = skeleton.joints[i]
+            .local_matrix
+            .to_scale_rotation_translation();
+        let children = if !skeleton.joints[i].children.is_empty() {
+            Some(
+                skeleton.joints[i]
+                    .children
+                    .iter()
+                    .map(|i| Index::new(*i as u32))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        nodes.push(Node {
+            camera: None,
+            children,
+            extensions: None,
+            extras: extras::to_extras(&extras::JointExtras {
+                hash: skeleton.joints[i].hash,
+            }),
+            matrix: None,
+            mesh: None,
+            name: Some(skeleton.joints[i].name.to_owned()),
+            rotation: Some(scene::UnitQuaternion(rotation.to_array())),
+            scale: Some(scale.to_array()),
+            translation: Some(translation.to_array()),
+            skin: None,
+            weights: None,
+        });
+    }
+
+    nodes.push(Node {
+        camera: None,
+        children: Some(
+            skeleton
+                .joints
+                .iter()
+                .filter(|joint| joint.parent_id < 0)
+                .map(|joint| Index::new(joint.id as u32))
+                .collect(),
+        ),
+        extensions: None,
+        extras: extras::to_extras(&extras::SkeletonExtras::new(
+            &skeleton.stype,
+            skeleton.version,
+        )),
+        matrix: None,
+        mesh: Some(Index::new(0)),
+        name: Some(format!("RootMaster{model_name}")),
+        rotation: None,
+        scale: None,
+        translation: None,
+        skin: Some(Index::new(0)),
+        weights: None,
+    });
+
+    let joints = (0..skeleton.joints.len())
+        .map(|i| Index::new(i as u32))
+        .collect();
+
+    let ibm_data = any_vec_as_vec_u8(
+        &skeleton
+            .joints
+            .iter()
+            .map(|joint| {
+                let mut igm = joint.inverse_global_matrix;
+                igm.x_axis.w = 0.0f32;
+                igm.y_axis.w = 0.0f32;
+                igm.z_axis.w = 0.0f32;
+                igm.w_axis.w = 1.0f32;
+                igm
+            })
+            .collect(),
+    );
+
+    let ibm_buffer_view = make_buffer_view(ibm_data.len(), Some(*buffer_view_offset), None);
+    let ibm_accessor = make_accessor(
+        skeleton.joints.len(),
+        None,
+        *buffer_view_index,
+        accessor::Type::Mat4,
+        accessor::ComponentType::F32,
+        None,
+        None,
+    );
+    let ibm_accessor_index = *accessor_index;
+    *accessor_index += 1;
+    *buffer_view_index += 1;
+
+    *buffer_view_offset += ibm_data.len();
+
+    let gltf_skin = GltfSkin {
+        extensions: None,
+        extras: None,
+        inverse_bind_matrices: Some(Index::new(ibm_accessor_index)),
+        joints,
+        name: Some(model_name.to_owned()),
+        skeleton: Some(Index::new(skeleton.joints.len() as u32)),
+    };
+
+    (nodes, gltf_skin, ibm_data, ibm_buffer_view, ibm_accessor)
+}
+
+fn make_material(
+    textures_paths: &[String],
+    textures_file_names: &[String],
+    skin: &Skin,
+    export_path: &Path,
+    texture_dir: &str,
+    export_as: u8,
+    buffer_view_index: &mut u32,
+    buffer_view_offset: &mut usize,
+    textures_selecteds: &[usize],  // 新增参数
+    alpha_modes: &BTreeMap<String, usize>,
+    texture_format: usize,
+) -> Result<(
+    Vec<Material>,
+    Vec<Texture>,
+    Vec<Image>,
+    Option<(Vec<u8>, Vec<buffer::View>)>,
+    Vec<Option<u32>>,
+), String> {
+    let mut images = vec![];
+    let mut textures = vec![];
+    let mut materials = vec![];
+
+    let mut buffer_views = vec![];
+    let mut total_buffers = vec![];
+
+    let texture_export_path = export_path.join(texture_dir);
+    if export_as == 0 {
+        fs::create_dir_all(&texture_export_path)
+            .map_err(|error| format!("Could not create texture export dirs: {error}"))?;
+    }
+
+    // 第一步：加载子网格实际使用的纹理资源，未被选择的纹理不导出
+    let used_textures: BTreeSet<usize> = textures_selecteds
+        .iter()
+        .copied()
+        .filter(|i| *i < textures_paths.len())
+        .collect();
+    let mut texture_remap = BTreeMap::new();
+    let mut texture_resources = vec![];
+    let mut texture_alpha_modes = vec![];
+    let mut texture_extension_sources = vec![];
+    for i in used_textures {
+        texture_remap.insert(i, textures.len());
+
+        // 损坏的纹理在解析时会 panic，通过 parse_file 转换为错误
+        let texture_path = Path::new(&textures_paths[i]);
+        let (texture_images, width, height) = crate::parse_file(texture_path, |contents| {
+            crate::gls::texture::load_source(&mut Cursor::new(contents.as_slice()))
+        })?;
+        let compressed_source = || {
+            crate::parse_file(texture_path, |contents| {
+                crate::gls::texture::load_compressed_source(&mut Cursor::new(contents.as_slice()))
+            })
+        };
+
+        let mut buffer = vec![];
+        let encoder = image::codecs::png::PngEncoder::new(&mut buffer);
+        image::ImageEncoder::write_image(
+            encoder,
+            &texture_images[0],
+            width as u32,
+            height as u32,
+            image::ColorType::Rgba8,
+        ).map_err(|error| format!("Could not encode image: {error}"))?;
+
+        let texture_file_stem = Path::new(texture_path.file_stem().unwrap());
+        images.push(make_image(
+            buffer,
+            &texture_file_stem.with_extension("png"),
+            mime::IMAGE_PNG.as_ref(),
+            &textures_file_names[i],
+            &texture_export_path,
+            texture_dir,
+            export_as,
+            &mut buffer_views,
+            &mut total_buffers,
+            buffer_view_index,
+            buffer_view_offset,
+        )?);
+        let png_image_index = images.len() as u32 - 1;
+
+        // 原始压缩数据作为扩展图像，PNG 作为不支持扩展时的回退
+        let compressed_image = match texture_format {
+            1 => compressed::write_dds(&compressed_source()?).map(|data| (data, "dds", "image/vnd-ms.dds")),
+            _ => None,
+        };
+        let extension_source = match compressed_image {
+            Some((data, extension, mime_type)) => {
+                images.push(make_image(
+                    data,
+                    &texture_file_stem.with_extension(extension),
+                    mime_type,
+                    &textures_file_names[i],
+                    &texture_export_path,
+                    texture_dir,
+                    export_as,
+                    &mut buffer_views,
+                    &mut total_buffers,
+                    buffer_view_index,
+                    buffer_view_offset,
+                )?);
+                Some(images.len() as u32 - 1)
+            }
+            None => None,
+        };
+        texture_extension_sources.push(extension_source);
+
+        textures.push(Texture {
+            name: Some(textures_file_names[i].clone()),
+            sampler: Some(Index::new(0)),
+            source: Index::new(png_image_index),
+            extensions: None,
+            extras: None,
+        });
+
+        texture_alpha_modes.push(detect_alpha_mode(&texture_images[0]));
+        texture_resources.push((texture_images, width, height));
+    }
+
+    // 第二步：为每个网格创建独立材质，并正确关联纹理
+    for (mesh_idx, mesh) in skin.meshes.iter().enumerate() {
+        // 确保材质索引在有效范围内
+        // let tex_index = if mesh.submesh.material_index < textures.len() {
+        //     mesh.submesh.material_index
+        // } else {
+        //     // 日志记录或错误处理
+        //     println!("Warning: Material index {} out of bounds for mesh {}, using last texture",
+        //              mesh.submesh.material_index, mesh.submesh.name);
+        //     textures.len().saturating_sub(1)
+        // };
+        // 模型没有纹理时 tex_index 为 None，材质不使用纹理
+        let tex_index = textures_selecteds
+            .get(mesh_idx)
+            .and_then(|texture_index| texture_remap.get(texture_index))
+            .copied();
+        let mesh_name = &mesh.submesh.name;
+
+        // 创建唯一材质名称
+        let material_name = match tex_index {
+            Some(_) => format!("{}_{}", textures_file_names[textures_selecteds[mesh_idx]], mesh_name),
+            None => mesh_name.to_owned(),
+        };
+
+        let texture_alpha_mode = tex_index
+            .map(|tex_index| texture_alpha_modes[tex_index])
+            .unwrap_or((AlphaMode::Opaque, None));
+        let (alpha_mode, alpha_cutoff) = match alpha_modes.get(mesh_name).copied().unwrap_or(0) {
+            1 => (AlphaMode::Opaque, None),
+            2 => (AlphaMode::Mask, Some(texture_alpha_mode.1.unwrap_or(0.5f32))),
+            3 => (AlphaMode::Blend, None),
+            _ => texture_alpha_mode,
+        };
+
+        materials.push(Material {
+            alpha_cutoff: alpha_cutoff.map(material::AlphaCutoff),
+            alpha_mode: Valid(alpha_mode),
+            double_sided: true,
+            name: Some(material_name),
+            pbr_metallic_roughness: material::PbrMetallicRoughness {
+                base_color_factor: material::PbrBaseColorFactor([1.0f32, 1.0f32, 1.0f32, 1.0f32]),
+                base_color_texture: tex_index.map(|tex_index| texture::Info {
+                    index: Index::new(tex_index as u32),  // 使用校正后的纹理索引
+                    tex_coord: 0,
+                    extensions: None,
+                    extras: None,
+                }),
+                metallic_factor: material::StrengthFactor(0.0f32),
+                roughness_factor: material::StrengthFactor(0.0f32),
+                metallic_roughness_texture: None,
+                extensions: None,
+                extras: None,
+            },
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+            emissive_factor: material::EmissiveFactor([0.0f32, 0.0f32, 0.0f32]),
+            extensions: Some(extensions::material::Material {
+                unlit: Some(extensions::material::Unlit {}),
+            }),
+            extras: extras::to_extras(&extras::MaterialExtras {
+                submesh: mesh_name.to_owned(),
+                texture: textures_selecteds
+                    .get(mesh_idx)
+                    .and_then(|texture_index| textures_paths.get(*texture_index))
+                    .and_then(|texture_path| Path::new(texture_path).file_name())
+                    .map(|x| x.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            }),
+        });
+    }
+
+    let total_buffers_buffer_views = if export_as == 1 {
+        Some((vec_2d_to_vec_1d(&total_buffers), buffer_views))
+    } else {
+        None
+    };
+
+    Ok((materials, textures, images, total_buffers_buffer_views, texture_extension_sources))
+}
+
+// gltf 写入 textures 目录并引用文件，glb 则嵌入到缓冲区
+fn make_image(
+    data: Vec<u8>,
+    file_name: &Path,
+    mime_type: &str,
+    name: &str,
+    texture_export_path: &Path,
+    texture_dir: &str,
+    export_as: u8,
+    buffer_views: &mut Vec<buffer::View>,
+    total_buffers: &mut Vec<Vec<u8>>,
+    buffer_view_index: &mut u32,
+    buffer_view_offset: &mut usize,
+) -> Result<Image, String> {
+    let mut uri = None;
+    let mut buffer_view = None;
+
+    if export_as == 0 {
+        write_file(&texture_export_path.join(file_name), &data)?;
+        uri = Some(format!("{texture_dir}/{}", file_name.display()));
+    } else {
+        let mut buffer = data;
+        buffer_views.push(make_buffer_view(
+            buffer.len(),
+            Some(*buffer_view_offset),
+            None,
+        ));
+
+        buffer_view = Some(Index::new(*buffer_view_index));
+        *buffer_view_index += 1;
+
+        vec_4_byte_padded(&mut buffer);
+        *buffer_view_offset += buffer.len();
+
+        total_buffers.push(buffer);
+    }
+
+    Ok(Image {
+        buffer_view,
+        mime_type: Some(gltf::json::image::MimeType(String::from(mime_type))),
+        name: Some(String::from(name)),
+        uri,
+        extensions: None,
+        extras: None,
+    })
+}
+
+// gltf crate 没有 MSFT_texture_dds 的类型，序列化后再补上纹理扩展
+fn add_texture_extensions(
+    root: &Root,
+    texture_extension_sources: &[Option<u32>],
+    texture_format: usize,
+) -> Result<serde_json::Value, String> {
+    let mut value =
+        serde_json::to_value(root).map_err(|error| format!("Could not serialize gltf: {error}"))?;
+
+    let extension_name = match texture_format {
+        1 => "MSFT_texture_dds",
+        _ => return Ok(value),
+    };
+
+    let mut used = false;
+    if let Some(textures) = value.get_mut("textures").and_then(|x| x.as_array_mut()) {
+        for (texture, source) in textures.iter_mut().zip(texture_extension_sources) {
+            if let (Some(source), Some(texture)) = (source, texture.as_object_mut()) {
+                texture.insert(
+                    String::from("extensions"),
+                    serde_json::json!({ extension_name: { "source": source } }),
+                );
+                used = true;
+            }
+        }
+    }
+
+    if used {
+        // 只加入 extensionsUsed，不支持的查看器会回退到 PNG
+        let root_object = value.as_object_mut().unwrap();
+        let extensions_used = root_object
+            .entry("extensionsUsed")
+            .or_insert_with(|| serde_json::json!([]));
+        if let Some(extensions_used) = extensions_used.as_array_mut() {
+            extensions_used.push(serde_json::json!(extension_name));
+        }
+    }
+
+    Ok(value)
+}
+
+
+
+// 根据 RGBA8 纹理的 alpha 通道选择透明模式：
+// 没有透明像素为 OPAQUE，透明像素几乎都是全透明/全不透明为 MASK，否则为 BLEND
+fn detect_alpha_mode(rgba: &[u8]) -> (AlphaMode, Option<f32>) {
+    let mut histogram = [0usize; 256];
+    for pixel in rgba.chunks_exact(4) {
+        histogram[pixel[3] as usize] += 1;
+    }
+
+    let total: usize = histogram.iter().sum();
+    let translucent = total - histogram[255];
+    if total == 0 || (translucent as f32) < total as f32 * 0.001f32 {
+        return (AlphaMode::Opaque, None);
+    }
+
+    let partial: usize = histogram[8..248].iter().sum();
+    if (partial as f32) > translucent as f32 * 0.1f32 {
+        return (AlphaMode::Blend, None);
+    }
+
+    // Otsu 阈值作为 alphaCutoff
+    let weighted_total: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(alpha, count)| alpha as f64 * *count as f64)
+        .sum();
+    let mut weight_low = 0.0f64;
+    let mut weighted_low = 0.0f64;
+    let mut best_variance = 0.0f64;
+    // 方差相同的阈值区间，取中点。只有 0/255 两种 alpha 时所有阈值方差相同，中点为 127
+    let mut threshold_range = (127usize, 127usize);
+    for alpha in 0..255 {
+        weight_low += histogram[alpha] as f64;
+        weighted_low += alpha as f64 * histogram[alpha] as f64;
+        let weight_high = total as f64 - weight_low;
+        if weight_low == 0.0 || weight_high == 0.0 {
+            continue;
+        }
+        let mean_low = weighted_low / weight_low;
+        let mean_high = (weighted_total - weighted_low) / weight_high;
+        let variance = weight_low * weight_high * (mean_low - mean_high).powi(2);
+        if variance > best_variance * (1.0f64 + 1e-9f64) {
+            best_variance = variance;
+            threshold_range = (alpha, alpha);
+        } else if variance >= best_variance * (1.0f64 - 1e-9f64) && threshold_range.1 + 1 == alpha {
+            threshold_range.1 = alpha;
+        }
+    }
+    let threshold = (threshold_range.0 + threshold_range.1) as f32 / 2.0f32;
+
+    (AlphaMode::Mask, Some((threshold + 0.5f32) / 255.0f32))
+}
+
+pub(crate) fn save_texture_png(texture_path: &str, texture_export_path: &Path) -> Result<String, String> {
+    let texture_path = Path::new(texture_path);
+    let (texture_images, width, height) = crate::parse_file(texture_path, |contents| {
+        crate::gls::texture::load_source(&mut Cursor::new(contents.as_slice()))
+    })?;
+
+    let texture_file_name = Path::new(&texture_path.file_stem().unwrap()).with_extension("png");
+
+    image::save_buffer(
+        texture_export_path.join(&texture_file_name),
+        &texture_images[0],
+        width as u32,
+        height as u32,
+        image::ColorType::Rgba8,
+    )
+    .map_err(|error| format!("Could not save image: {error}"))?;
+
+    Ok(texture_file_name.display().to_string())
+}
+
+fn make_buffer_view(
+    byte_length: usize,
+    byte_offset: Option<usize>,
+    target: Option<buffer::Target>,
+) -> buffer::View {
+    buffer::View {
+        buffer: Index::new(0),
+        byte_length: USize64::from(byte_length),
+        byte_offset: byte_offset.map(USize64::from),
+        byte_stride: None,
+        extensions: None,
+        extras: None,
+        name: None,
+        target: target.map(Valid),
+    }
+}
+
+fn make_accessor(
+    count: usize,
+    byte_offset: Option<usize>,
+    buffer_view_index: u32,
+    type_: accessor::Type,
+    component_type: accessor::ComponentType,
+    min: Option<Value>,
+    max: Option<Value>,
+) -> Accessor {
+    Accessor {
+        buffer_view: Some(Index::new(buffer_view_index)),
+        byte_offset: byte_offset.map(USize64::from),
+        count: USize64::from(count),
+        component_type: Valid(accessor::GenericComponentType(component_type)),
+        extensions: None,
+        extras: None,
+        type_: Valid(type_),
+        min,
+        max,
+        name: None,
+        normalized: false,
+        sparse: None,
+    }
+}
+
+fn any_vec_as_vec_u8<T: Clone>(vec: &Vec<T>) -> Vec<u8> {
+    let byte_length = vec.len() * mem::size_of::<T>();
+    let byte_capacity = vec.capacity() * mem::size_of::<T>();
+    let ptr = Box::<[T]>::into_raw(vec.to_vec().into_boxed_slice()) as *mut u8;
+    unsafe { Vec::from_raw_parts(ptr, byte_length, byte_capacity) }
+}
+
+fn vec_2d_to_vec_1d(vecs: &[Vec<u8>]) -> Vec<u8> {
+    let mut new_vec = vec![];
+    for vec in vecs {
+        new_vec.extend_from_slice(vec);
+    }
+    new_vec
+}
+
+fn vec_4_byte_padded(vec: &mut Vec<u8>) {
+    while vec.len() % 4 != 0 {
+        vec.push(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trimmed_animation_includes_the_end_frame() {
+        let animation = Animation {
+            format: String::new(),
+            fps: 10.0f32 / 3.0f32,
+            duration: 1.0f32,
+            frame_delay: 0.3f32,
+            joints: vec![],
+        };
+        let mut animation_export = AnimationExportJson::new("idle", animation.duration);
+        animation_export.start = 0.2f32;
+        animation_export.end = 0.9f32;
+        animation_export.speed = 2.0f32;
+
+        let (sample_times, times) = animation_times(&animation, &animation_export);
+        let expected = [0.2f32, 0.5f32, 0.8f32, 0.9f32];
+        assert_eq!(sample_times.len(), expected.len());
+        for (time, expected) in sample_times.iter().zip(expected) {
+            assert!((time - expected).abs() < 1e-5f32, "{sample_times:?}");
+        }
+        assert!((times.last().unwrap() - 0.35f32).abs() < 1e-5f32, "{times:?}");
+
+        // 结束时间正好在帧上时不重复
+        animation_export.end = 0.8f32;
+        let (sample_times, _) = animation_times(&animation, &animation_export);
+        assert_eq!(sample_times.len(), 3);
+    }
+
+    #[test]
+    fn binary_alpha_cutoff_is_centered() {
+        let mut rgba = vec![];
+        for i in 0..64 {
+            rgba.extend_from_slice(&[255, 255, 255, if i % 2 == 0 { 0 } else { 255 }]);
+        }
+
+        let (alpha_mode, cutoff) = detect_alpha_mode(&rgba);
+        assert!(matches!(alpha_mode, AlphaMode::Mask));
+        let cutoff = cutoff.unwrap();
+        assert!((cutoff - 0.5f32).abs() < 1.0f32 / 255.0f32, "cutoff {cutoff}");
+    }
+}
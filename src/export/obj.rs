@@ -0,0 +1,142 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::{
+    config_json::OptionsJson,
+    export::{self, conversion::Conversion},
+    lol::anm,
+    MindModel,
+};
+
+// 将当前动画时间的姿势烘焙为静态网格，导出为 OBJ + MTL
+pub fn export_obj(
+    export_path: &Path,
+    model_name: &str,
+    mind_model: &MindModel,
+    options: &OptionsJson,
+    only_visible_meshes: bool,
+    conversion: &Conversion,
+) -> Result<(), String> {
+    let texture_export_path = export_path.join("textures");
+    fs::create_dir_all(&texture_export_path)
+        .map_err(|error| format!("Could not create texture export dirs: {error}"))?;
+
+    let skin = &mind_model.skin;
+    let skeleton = &mind_model.skeleton;
+
+    let mut joints_transforms = vec![glam::Mat4::IDENTITY; skeleton.joints.len()];
+    if options.use_animation && !mind_model.animations.is_empty() {
+        anm::run_animation(
+            &mut joints_transforms,
+            &mind_model.animations[mind_model.animation_selected],
+            skeleton,
+            options.animation_time,
+        );
+    }
+
+    let model_matrix = glam::Mat4::from_translation(glam::Vec3::from(options.position_offset))
+        * glam::Mat4::from_euler(
+            glam::EulerRot::XYZ,
+            options.rotation_angles[0].to_radians(),
+            options.rotation_angles[1].to_radians(),
+            options.rotation_angles[2].to_radians(),
+        );
+
+    let (vertices, normals) = skin.skinned(&joints_transforms);
+
+    // 热重载后子网格可能比显示状态和纹理选择多，缺少的按显示、第一个纹理处理
+    let exported = |mesh_idx: usize| {
+        !only_visible_meshes || mind_model.show_meshes.get(mesh_idx).copied().unwrap_or(true)
+    };
+
+    // 只保留导出的子网格引用到的顶点，并重新编号
+    let mut vertex_remap: Vec<Option<usize>> = vec![None; vertices.len()];
+    let mut used_vertices = vec![];
+    for (mesh_idx, mesh) in skin.meshes.iter().enumerate() {
+        if !exported(mesh_idx) {
+            continue;
+        }
+        let indices_offset = mesh.submesh.indices_offset as usize;
+        let indices_count = mesh.submesh.indices_count as usize;
+        for index in &skin.indices[indices_offset..indices_offset + indices_count] {
+            let index = *index as usize;
+            if vertex_remap[index].is_none() {
+                vertex_remap[index] = Some(used_vertices.len());
+                used_vertices.push(index);
+            }
+        }
+    }
+
+    let mut obj = format!("mtllib {model_name}.mtl\no {model_name}\n");
+    for index in used_vertices.iter() {
+        let vertex = conversion.point(model_matrix.transform_point3(vertices[*index]));
+        obj.push_str(&format!("v {} {} {}\n", vertex.x, vertex.y, vertex.z));
+    }
+    for index in used_vertices.iter() {
+        let uv = skin.uvs[*index];
+        obj.push_str(&format!("vt {} {}\n", uv.x, 1.0f32 - uv.y));
+    }
+    for index in used_vertices.iter() {
+        let normal = conversion.normal(
+            model_matrix
+                .transform_vector3(normals[*index])
+                .normalize_or_zero(),
+        );
+        obj.push_str(&format!("vn {} {} {}\n", normal.x, normal.y, normal.z));
+    }
+
+    let mut mtl = String::new();
+    let mut textures_file_names: BTreeMap<usize, String> = BTreeMap::new();
+
+    for (mesh_idx, mesh) in skin.meshes.iter().enumerate() {
+        if !exported(mesh_idx) {
+            continue;
+        }
+
+        // 模型没有纹理时材质不写 map_Kd
+        let texture_index = mind_model.textures_selecteds.get(mesh_idx).copied().unwrap_or(0);
+        let texture_file_name = match mind_model.textures_paths.get(texture_index) {
+            Some(texture_path) => {
+                if !textures_file_names.contains_key(&texture_index) {
+                    let texture_file_name = export::save_texture_png(texture_path, &texture_export_path)?;
+                    textures_file_names.insert(texture_index, texture_file_name);
+                }
+                Some(&textures_file_names[&texture_index])
+            }
+            None => None,
+        };
+        let material_name = match texture_file_name {
+            Some(_) => format!(
+                "{}_{}",
+                mind_model.textures_file_names[texture_index], mesh.submesh.name
+            ),
+            None => mesh.submesh.name.to_owned(),
+        };
+
+        mtl.push_str(&format!("newmtl {material_name}\n"));
+        mtl.push_str("Ka 0 0 0\nKd 1 1 1\nKs 0 0 0\nd 1\nillum 1\n");
+        if let Some(texture_file_name) = texture_file_name {
+            mtl.push_str(&format!("map_Kd textures/{texture_file_name}\n"));
+        }
+        mtl.push('\n');
+
+        obj.push_str(&format!("g {}\nusemtl {material_name}\n", mesh.submesh.name));
+
+        let indices_offset = mesh.submesh.indices_offset as usize;
+        let indices_count = mesh.submesh.indices_count as usize;
+        for triangle in skin.indices[indices_offset..indices_offset + indices_count].chunks(3) {
+            if triangle.len() < 3 {
+                break;
+            }
+            obj.push('f');
+            let triangle = conversion.triangle([triangle[0], triangle[1], triangle[2]]);
+            for index in triangle.iter() {
+                let index = vertex_remap[*index as usize].unwrap() + 1;
+                obj.push_str(&format!(" {index}/{index}/{index}"));
+            }
+            obj.push('\n');
+        }
+    }
+
+    export::write_file(&export_path.join(format!("{model_name}.obj")), obj.as_bytes())?;
+    export::write_file(&export_path.join(format!("{model_name}.mtl")), mtl.as_bytes())
+}
@@ -0,0 +1,254 @@
+use std::{mem, path::PathBuf};
+
+use gltf::json::{scene, validation::USize64, Animation as GltfAnimation, Index, Root};
+
+use crate::{config_json::OptionsJson, export, MindModel};
+
+use super::ExportOptions;
+
+// 将所有显示中的模型导出到同一个 gltf/glb，每个模型带上自己的位置和旋转，
+// 各模型当前选择的动画合并为一个同步播放的动画
+pub fn export_scene(
+    export_options: &ExportOptions,
+    names: &[String],
+    mind_models: &[MindModel],
+    options: &[OptionsJson],
+    export_animations: bool,
+) -> Result<PathBuf, String> {
+    if export_options.scene_export_as > 1 {
+        return Err(String::from("Scene export only supports gltf and glb"));
+    }
+    let mut export_options = export_options.clone();
+    export_options.export_as = export_options.scene_export_as;
+    let export_options = &export_options;
+
+    let (export_path, file_name) = export_options.resolve_export_path("Scene", "")?;
+
+    let mut scene_root: Option<Root> = None;
+    let mut data = vec![];
+    let mut texture_extension_sources = vec![];
+
+    for i in 0..mind_models.len() {
+        if !options[i].show {
+            continue;
+        }
+
+        let mind_model = &mind_models[i];
+        let (visible_skin, textures_selecteds) = export::export_skin(export_options, mind_model);
+        let skin = visible_skin.as_ref().unwrap_or(&mind_model.skin);
+
+        let animation_indices = if export_animations && !mind_model.animations.is_empty() {
+            vec![mind_model.animation_selected]
+        } else {
+            vec![]
+        };
+
+        let (mut root, model_data, model_texture_extension_sources) = export::make_model_root(
+            &names[i],
+            mind_model,
+            skin,
+            &textures_selecteds,
+            &animation_indices,
+            &options[i],
+            &export_path,
+            &texture_dir(i, &names[i]),
+            export_options,
+        )?;
+
+        // RootMaster 节点位于所有关节之后
+        let root_master = &mut root.nodes[mind_model.skeleton.joints.len()];
+        let rotation = glam::Quat::from_euler(
+            glam::EulerRot::XYZ,
+            options[i].rotation_angles[0].to_radians(),
+            options[i].rotation_angles[1].to_radians(),
+            options[i].rotation_angles[2].to_radians(),
+        );
+        let conversion = export_options.conversion();
+        root_master.translation = Some(
+            conversion
+                .translation(glam::Vec3::from(options[i].position_offset))
+                .to_array(),
+        );
+        root_master.rotation = Some(scene::UnitQuaternion(conversion.rotation(rotation).to_array()));
+
+        match scene_root.as_mut() {
+            Some(scene_root) => merge_root(
+                scene_root,
+                &mut data,
+                &mut texture_extension_sources,
+                root,
+                model_data,
+                model_texture_extension_sources,
+            ),
+            None => {
+                scene_root = Some(root);
+                data = model_data;
+                texture_extension_sources = model_texture_extension_sources;
+            }
+        }
+    }
+
+    let mut root = scene_root.ok_or_else(|| String::from("No visible model to export"))?;
+
+    let animations = mem::take(&mut root.animations);
+    if !animations.is_empty() {
+        let mut combined = GltfAnimation {
+            extensions: None,
+            extras: None,
+            channels: vec![],
+            name: Some(String::from("Scene")),
+            samplers: vec![],
+        };
+        for animation in animations {
+            let sampler_offset = combined.samplers.len();
+            for mut channel in animation.channels {
+                channel.sampler = offset(channel.sampler, sampler_offset);
+                combined.channels.push(channel);
+            }
+            combined.samplers.extend(animation.samplers);
+        }
+        root.animations = vec![combined];
+    }
+
+    root.buffers[0].byte_length = USize64::from(data.len());
+    root.scenes[0].name = Some(String::from("Scene"));
+
+    export::write_root(
+        root,
+        data,
+        &texture_extension_sources,
+        &export_path,
+        &file_name,
+        export_options,
+    )
+}
+
+// 把 source 的所有对象追加到 target，并平移其中的索引和缓冲区偏移
+fn merge_root(
+    target: &mut Root,
+    target_data: &mut Vec<u8>,
+    target_texture_extension_sources: &mut Vec<Option<u32>>,
+    source: Root,
+    source_data: Vec<u8>,
+    source_texture_extension_sources: Vec<Option<u32>>,
+) {
+    while target_data.len() % 4 != 0 {
+        target_data.push(0);
+    }
+
+    let data_offset = target_data.len() as u64;
+    let accessors_offset = target.accessors.len();
+    let buffer_views_offset = target.buffer_views.len();
+    let meshes_offset = target.meshes.len();
+    let nodes_offset = target.nodes.len();
+    let skins_offset = target.skins.len();
+    let materials_offset = target.materials.len();
+    let textures_offset = target.textures.len();
+    let images_offset = target.images.len();
+
+    target_data.extend_from_slice(&source_data);
+
+    for mut buffer_view in source.buffer_views {
+        let byte_offset = buffer_view.byte_offset.map(|x| x.0).unwrap_or(0);
+        buffer_view.byte_offset = Some(USize64(byte_offset + data_offset));
+        target.buffer_views.push(buffer_view);
+    }
+
+    for mut accessor in source.accessors {
+        accessor.buffer_view = accessor
+            .buffer_view
+            .map(|x| offset(x, buffer_views_offset));
+        target.accessors.push(accessor);
+    }
+
+    for mut mesh in source.meshes {
+        for primitive in mesh.primitives.iter_mut() {
+            for accessor in primitive.attributes.values_mut() {
+                *accessor = offset(*accessor, accessors_offset);
+            }
+            primitive.indices = primitive.indices.map(|x| offset(x, accessors_offset));
+            primitive.material = primitive.material.map(|x| offset(x, materials_offset));
+        }
+        target.meshes.push(mesh);
+    }
+
+    for mut node in source.nodes {
+        node.children = node.children.map(|children| {
+            children
+                .into_iter()
+                .map(|x| offset(x, nodes_offset))
+                .collect()
+        });
+        node.mesh = node.mesh.map(|x| offset(x, meshes_offset));
+        node.skin = node.skin.map(|x| offset(x, skins_offset));
+        target.nodes.push(node);
+    }
+
+    for mut skin in source.skins {
+        skin.inverse_bind_matrices = skin
+            .inverse_bind_matrices
+            .map(|x| offset(x, accessors_offset));
+        skin.joints = skin
+            .joints
+            .into_iter()
+            .map(|x| offset(x, nodes_offset))
+            .collect();
+        skin.skeleton = skin.skeleton.map(|x| offset(x, nodes_offset));
+        target.skins.push(skin);
+    }
+
+    for mut material in source.materials {
+        if let Some(info) = material.pbr_metallic_roughness.base_color_texture.as_mut() {
+            info.index = offset(info.index, textures_offset);
+        }
+        target.materials.push(material);
+    }
+
+    // 所有模型共用第一个模型的采样器
+    for mut texture in source.textures {
+        texture.source = offset(texture.source, images_offset);
+        texture.sampler = Some(Index::new(0));
+        target.textures.push(texture);
+    }
+
+    for mut image in source.images {
+        image.buffer_view = image.buffer_view.map(|x| offset(x, buffer_views_offset));
+        target.images.push(image);
+    }
+
+    target_texture_extension_sources.extend(
+        source_texture_extension_sources
+            .into_iter()
+            .map(|x| x.map(|x| x + images_offset as u32)),
+    );
+
+    for mut animation in source.animations {
+        for channel in animation.channels.iter_mut() {
+            channel.target.node = offset(channel.target.node, nodes_offset);
+        }
+        for sampler in animation.samplers.iter_mut() {
+            sampler.input = offset(sampler.input, accessors_offset);
+            sampler.output = offset(sampler.output, accessors_offset);
+        }
+        target.animations.push(animation);
+    }
+
+    for scene in source.scenes {
+        target.scenes[0]
+            .nodes
+            .extend(scene.nodes.into_iter().map(|x| offset(x, nodes_offset)));
+    }
+}
+
+fn offset<T>(index: Index<T>, offset: usize) -> Index<T> {
+    Index::new((index.value() + offset) as u32)
+}
+
+// 每个模型的图像写入单独的目录，不同模型的同名纹理不会互相覆盖
+fn texture_dir(index: usize, name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    format!("textures/{index}_{name}")
+}
@@ -0,0 +1,80 @@
+use std::{
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+use crate::gls::texture;
+
+pub const TEXTURE_EXPORT_FORMATS: [&str; 2] = ["PNG", "TGA"];
+const EXTENSIONS: [&str; 2] = ["png", "tga"];
+
+// 把 dds/tex 纹理解码后保存到输出目录，all_mipmaps 时每一级另存为 name_mip1.png ...，
+// 单个纹理失败不会中断其他纹理，返回保存的文件数量
+pub fn export_textures(
+    texture_paths: &[&str],
+    output_dir: &Path,
+    format: usize,
+    all_mipmaps: bool,
+) -> Result<(PathBuf, usize), String> {
+    fs::create_dir_all(output_dir).map_err(|error| format!("Could not create export dirs: {error}"))?;
+
+    let extension = EXTENSIONS[format.min(EXTENSIONS.len() - 1)];
+    let mut written = 0;
+    let mut errors = vec![];
+
+    for texture_path in texture_paths {
+        match export_texture(Path::new(texture_path), output_dir, extension, all_mipmaps) {
+            Ok(count) => written += count,
+            Err(error) => {
+                println!("{error}");
+                errors.push(error);
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(format!(
+            "{} of {} textures failed: {}",
+            errors.len(),
+            texture_paths.len(),
+            errors.join("; ")
+        ));
+    }
+
+    println!("Exported {written} texture files to {}", output_dir.display());
+    Ok((output_dir.to_path_buf(), written))
+}
+
+fn export_texture(texture_path: &Path, output_dir: &Path, extension: &str, all_mipmaps: bool) -> Result<usize, String> {
+    let source = crate::parse_file(texture_path, |contents| {
+        texture::load_compressed_source(&mut Cursor::new(contents.as_slice()))
+    })?;
+    let mipmaps = texture::decode_mipmaps(&source)
+        .map_err(|error| format!("{}: {error}", texture_path.display()))?;
+
+    let file_stem = texture_path
+        .file_stem()
+        .and_then(|x| x.to_str())
+        .unwrap_or("texture");
+
+    let count = if all_mipmaps { mipmaps.len() } else { mipmaps.len().min(1) };
+    for (level, mipmap) in mipmaps.iter().take(count).enumerate() {
+        let file_name = if level == 0 {
+            format!("{file_stem}.{extension}")
+        } else {
+            format!("{file_stem}_mip{level}.{extension}")
+        };
+        let path = output_dir.join(file_name);
+        image::save_buffer(
+            &path,
+            &mipmap.pixels,
+            mipmap.width as u32,
+            mipmap.height as u32,
+            image::ColorType::Rgba8,
+        )
+        .map_err(|error| format!("Could not save image {}: {error}", path.display()))?;
+    }
+
+    Ok(count)
+}
@@ -0,0 +1,484 @@
+use std::{collections::BTreeSet, fs, path::Path};
+
+use crate::{
+    config_json::OptionsJson,
+    export::{self, conversion::Conversion},
+    lol::{anm, Animation, Skeleton, Skin},
+    MindModel,
+};
+
+// 动画时间（秒）按此帧率换算为 USD 的 timeCode
+const TIME_CODES_PER_SECOND: f32 = 30.0f32;
+
+pub fn export_usd(
+    export_path: &Path,
+    file_name: &str,
+    model_name: &str,
+    mind_model: &MindModel,
+    skin: &Skin,
+    textures_selecteds: &[usize],
+    export_animations: bool,
+    options: &OptionsJson,
+    conversion: &Conversion,
+) -> Result<(), String> {
+    let texture_export_path = export_path.join("textures");
+    fs::create_dir_all(&texture_export_path)
+        .map_err(|error| format!("Could not create texture export dirs: {error}"))?;
+
+    let model_id = usd_name(model_name);
+    let skin = &conversion.skin(skin);
+    let skeleton = &conversion.skeleton(&mind_model.skeleton);
+    let up_axis = if conversion.point(glam::Vec3::Y).y.abs() > 0.5f32 { "Y" } else { "Z" };
+    let joint_paths = joint_paths(skeleton);
+
+    let animations = if export_animations {
+        make_animations(mind_model, skeleton, &joint_paths, options, conversion)
+    } else {
+        vec![]
+    };
+    let end_time_code = animations
+        .iter()
+        .map(|(_, _, end)| *end)
+        .fold(0.0f32, f32::max);
+
+    let mut usd = String::new();
+    usd.push_str("#usda 1.0\n(\n");
+    usd.push_str(&format!("    defaultPrim = \"{model_id}\"\n"));
+    usd.push_str("    metersPerUnit = 1\n");
+    usd.push_str(&format!("    upAxis = \"{up_axis}\"\n"));
+    if !animations.is_empty() {
+        usd.push_str("    startTimeCode = 0\n");
+        usd.push_str(&format!("    endTimeCode = {end_time_code}\n"));
+        usd.push_str(&format!("    timeCodesPerSecond = {TIME_CODES_PER_SECOND}\n"));
+    }
+    usd.push_str(")\n\n");
+
+    usd.push_str(&format!("def SkelRoot \"{model_id}\"\n{{\n"));
+    usd.push_str(&make_skeleton(
+        &model_id,
+        skeleton,
+        &joint_paths,
+        animations.first().map(|(name, _, _)| name.as_str()),
+    ));
+
+    if !animations.is_empty() {
+        usd.push_str("    def Scope \"Animations\"\n    {\n");
+        for (_, animation, _) in animations.iter() {
+            usd.push_str(animation);
+        }
+        usd.push_str("    }\n\n");
+    }
+
+    usd.push_str(&make_mesh(&model_id, skin, conversion));
+    usd.push_str(&make_materials(
+        &model_id,
+        skin,
+        &mind_model.textures_paths,
+        textures_selecteds,
+        &texture_export_path,
+    )?);
+    usd.push_str("}\n");
+
+    export::write_file(&export_path.join(format!("{file_name}.usda")), usd.as_bytes())
+}
+
+// UsdSkel 用从根关节开始的路径表示层级，父关节必须排在子关节之前
+fn joint_paths(skeleton: &Skeleton) -> Vec<String> {
+    let names = unique_names(skeleton.joints.iter().map(|joint| joint.name.as_str()));
+    let mut paths: Vec<String> = Vec::with_capacity(skeleton.joints.len());
+    for (joint_index, (joint, name)) in skeleton.joints.iter().zip(names).enumerate() {
+        let parent_index = joint.parent_id;
+        let path = if parent_index >= 0 && (parent_index as usize) < joint_index {
+            format!("{}/{name}", paths[parent_index as usize])
+        } else {
+            name
+        };
+        paths.push(path);
+    }
+    paths
+}
+
+fn make_skeleton(
+    model_id: &str,
+    skeleton: &Skeleton,
+    joint_paths: &[String],
+    animation_source: Option<&str>,
+) -> String {
+    let bind_transforms: Vec<String> = skeleton
+        .joints
+        .iter()
+        .map(|joint| {
+            let mut igm = joint.inverse_global_matrix;
+            igm.x_axis.w = 0.0f32;
+            igm.y_axis.w = 0.0f32;
+            igm.z_axis.w = 0.0f32;
+            igm.w_axis.w = 1.0f32;
+            matrix4d(&igm.inverse())
+        })
+        .collect();
+    let rest_transforms: Vec<String> = skeleton
+        .joints
+        .iter()
+        .map(|joint| matrix4d(&joint.local_matrix))
+        .collect();
+
+    let mut usd = String::from(
+        "    def Skeleton \"Skeleton\" (\n        prepend apiSchemas = [\"SkelBindingAPI\"]\n    )\n    {\n",
+    );
+    usd.push_str(&format!(
+        "        uniform token[] joints = [{}]\n",
+        tokens(joint_paths)
+    ));
+    usd.push_str(&format!(
+        "        uniform matrix4d[] bindTransforms = [{}]\n",
+        bind_transforms.join(", ")
+    ));
+    usd.push_str(&format!(
+        "        uniform matrix4d[] restTransforms = [{}]\n",
+        rest_transforms.join(", ")
+    ));
+    if let Some(animation_source) = animation_source {
+        usd.push_str(&format!(
+            "        rel skel:animationSource = </{model_id}/Animations/{animation_source}>\n"
+        ));
+    }
+    usd.push_str("    }\n\n");
+    usd
+}
+
+// 返回(动画名, SkelAnimation 文本, 结束 timeCode)，第一个动画作为骨骼的 animationSource
+fn make_animations(
+    mind_model: &MindModel,
+    skeleton: &Skeleton,
+    joint_paths: &[String],
+    options: &OptionsJson,
+    conversion: &Conversion,
+) -> Vec<(String, String, f32)> {
+    let mut animations = vec![];
+    let mut names: BTreeSet<String> = BTreeSet::new();
+
+    for i in 0..mind_model.animations.len() {
+        let animation_export = export::animation_export_of(options, mind_model, i);
+        if !animation_export.export {
+            continue;
+        }
+
+        let animation = &conversion.animation(&mind_model.animations[i]);
+        let (sample_times, times) = export::animation_times(animation, &animation_export);
+
+        let mut name = usd_name(&mind_model.animations_file_names[i]);
+        while !names.insert(name.clone()) {
+            name.push('_');
+        }
+
+        let mut translations = String::new();
+        let mut rotations = String::new();
+        let mut scales = String::new();
+        for (sample_time, time) in sample_times.iter().zip(times.iter()) {
+            let time_code = time * TIME_CODES_PER_SECOND;
+            let poses: Vec<(glam::Vec3, glam::Quat, glam::Vec3)> = (0..skeleton.joints.len())
+                .map(|joint_index| joint_local_trs(animation, skeleton, joint_index, *sample_time))
+                .collect();
+
+            translations.push_str(&format!(
+                "                {time_code}: [{}],\n",
+                poses
+                    .iter()
+                    .map(|(translation, _, _)| vec3(translation))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+            // USD 的四元数按 (w, x, y, z) 书写
+            rotations.push_str(&format!(
+                "                {time_code}: [{}],\n",
+                poses
+                    .iter()
+                    .map(|(_, rotation, _)| format!(
+                        "({}, {}, {}, {})",
+                        rotation.w, rotation.x, rotation.y, rotation.z
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+            scales.push_str(&format!(
+                "                {time_code}: [{}],\n",
+                poses
+                    .iter()
+                    .map(|(_, _, scale)| vec3(scale))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+
+        let mut usd = format!("        def SkelAnimation \"{name}\"\n        {{\n");
+        usd.push_str(&format!(
+            "            uniform token[] joints = [{}]\n",
+            tokens(joint_paths)
+        ));
+        usd.push_str(&format!(
+            "            float3[] translations.timeSamples = {{\n{translations}            }}\n"
+        ));
+        usd.push_str(&format!(
+            "            quatf[] rotations.timeSamples = {{\n{rotations}            }}\n"
+        ));
+        usd.push_str(&format!(
+            "            half3[] scales.timeSamples = {{\n{scales}            }}\n"
+        ));
+        usd.push_str("        }\n");
+
+        let end_time_code = times.last().copied().unwrap_or(0.0f32) * TIME_CODES_PER_SECOND;
+        animations.push((name, usd, end_time_code));
+    }
+
+    animations
+}
+
+fn make_mesh(model_id: &str, skin: &Skin, conversion: &Conversion) -> String {
+    let face_vertex_counts = vec!["3"; skin.indices.len() / 3].join(", ");
+    let points: Vec<String> = skin.vertices.iter().map(vec3).collect();
+    let normals: Vec<String> = skin.normals.iter().map(vec3).collect();
+    // USD 的纹理坐标原点在左下角
+    let uvs: Vec<String> = skin
+        .uvs
+        .iter()
+        .map(|x| format!("({}, {})", x.x, 1.0f32 - x.y))
+        .collect();
+    let joint_indices: Vec<u16> = skin
+        .influences
+        .iter()
+        .flat_map(|x| [x.x, x.y, x.z, x.w])
+        .collect();
+    let joint_weights: Vec<f32> = skin.weights.iter().flat_map(|x| x.to_array()).collect();
+
+    let mut usd = String::from(
+        "    def Mesh \"Mesh\" (\n        prepend apiSchemas = [\"SkelBindingAPI\", \"MaterialBindingAPI\"]\n    )\n    {\n",
+    );
+    usd.push_str("        uniform bool doubleSided = 1\n");
+    // 未镜像时保持游戏中的环绕方向，按左手系解释
+    let orientation = if conversion.flips_winding() { "rightHanded" } else { "leftHanded" };
+    usd.push_str(&format!("        uniform token orientation = \"{orientation}\"\n"));
+    usd.push_str("        uniform token subdivisionScheme = \"none\"\n");
+    usd.push_str(&format!("        int[] faceVertexCounts = [{face_vertex_counts}]\n"));
+    usd.push_str(&format!("        int[] faceVertexIndices = [{}]\n", join(&skin.indices)));
+    usd.push_str(&format!(
+        "        float3[] extent = [{}, {}]\n",
+        vec3(&skin.bounding_box[0]),
+        vec3(&skin.bounding_box[1])
+    ));
+    usd.push_str(&format!("        point3f[] points = [{}]\n", points.join(", ")));
+    usd.push_str(&format!(
+        "        normal3f[] normals = [{}] (\n            interpolation = \"vertex\"\n        )\n",
+        normals.join(", ")
+    ));
+    usd.push_str(&format!(
+        "        texCoord2f[] primvars:st = [{}] (\n            interpolation = \"vertex\"\n        )\n",
+        uvs.join(", ")
+    ));
+    usd.push_str(&format!(
+        "        int[] primvars:skel:jointIndices = [{}] (\n            elementSize = 4\n            interpolation = \"vertex\"\n        )\n",
+        join(&joint_indices)
+    ));
+    usd.push_str(&format!(
+        "        float[] primvars:skel:jointWeights = [{}] (\n            elementSize = 4\n            interpolation = \"vertex\"\n        )\n",
+        join(&joint_weights)
+    ));
+    usd.push_str(
+        "        matrix4d primvars:skel:geomBindTransform = ( (1, 0, 0, 0), (0, 1, 0, 0), (0, 0, 1, 0), (0, 0, 0, 1) )\n",
+    );
+    usd.push_str(&format!("        rel skel:skeleton = </{model_id}/Skeleton>\n"));
+    usd.push_str("        uniform token subsetFamily:materialBind:familyType = \"partition\"\n");
+
+    // 每个子网格是一个 GeomSubset，绑定各自的材质
+    for (mesh, subset_name) in skin.meshes.iter().zip(submesh_names(skin)) {
+        let face_offset = mesh.submesh.indices_offset as usize / 3;
+        let face_count = mesh.submesh.indices_count as usize / 3;
+        let faces: Vec<usize> = (face_offset..face_offset + face_count).collect();
+
+        usd.push_str(&format!(
+            "\n        def GeomSubset \"{subset_name}\" (\n            prepend apiSchemas = [\"MaterialBindingAPI\"]\n        )\n        {{\n"
+        ));
+        usd.push_str("            uniform token elementType = \"face\"\n");
+        usd.push_str("            uniform token familyName = \"materialBind\"\n");
+        usd.push_str(&format!("            int[] indices = [{}]\n", join(&faces)));
+        usd.push_str(&format!(
+            "            rel material:binding = </{model_id}/Materials/{subset_name}>\n"
+        ));
+        usd.push_str("        }\n");
+    }
+
+    usd.push_str("    }\n\n");
+    usd
+}
+
+fn make_materials(
+    model_id: &str,
+    skin: &Skin,
+    textures_paths: &[String],
+    textures_selecteds: &[usize],
+    texture_export_path: &Path,
+) -> Result<String, String> {
+    // 只导出子网格实际使用的纹理
+    let used_textures: BTreeSet<usize> = textures_selecteds.iter().copied().collect();
+    let mut texture_file_names = vec![None; textures_paths.len()];
+    for texture_index in used_textures {
+        if texture_index >= textures_paths.len() {
+            continue;
+        }
+        texture_file_names[texture_index] = Some(export::save_texture_png(
+            &textures_paths[texture_index],
+            texture_export_path,
+        )?);
+    }
+
+    let mut usd = String::from("    def Scope \"Materials\"\n    {\n");
+    for (mesh_idx, material_name) in submesh_names(skin).into_iter().enumerate() {
+        let material_path = format!("/{model_id}/Materials/{material_name}");
+
+        usd.push_str(&format!("        def Material \"{material_name}\"\n        {{\n"));
+        usd.push_str(&format!(
+            "            token outputs:surface.connect = <{material_path}/PreviewSurface.outputs:surface>\n\n"
+        ));
+
+        usd.push_str("            def Shader \"PreviewSurface\"\n            {\n");
+        usd.push_str("                uniform token info:id = \"UsdPreviewSurface\"\n");
+        let texture_file_name = textures_selecteds
+            .get(mesh_idx)
+            .and_then(|texture_index| texture_file_names.get(*texture_index))
+            .cloned()
+            .flatten();
+        if texture_file_name.is_some() {
+            usd.push_str(&format!(
+                "                color3f inputs:diffuseColor.connect = <{material_path}/DiffuseTexture.outputs:rgb>\n"
+            ));
+        } else {
+            usd.push_str("                color3f inputs:diffuseColor = (0.8, 0.8, 0.8)\n");
+        }
+        usd.push_str("                float inputs:metallic = 0\n");
+        usd.push_str("                float inputs:roughness = 1\n");
+        usd.push_str("                token outputs:surface\n");
+        usd.push_str("            }\n");
+
+        if let Some(texture_file_name) = texture_file_name {
+            usd.push_str("\n            def Shader \"PrimvarReader\"\n            {\n");
+            usd.push_str("                uniform token info:id = \"UsdPrimvarReader_float2\"\n");
+            usd.push_str("                string inputs:varname = \"st\"\n");
+            usd.push_str("                float2 outputs:result\n");
+            usd.push_str("            }\n");
+
+            usd.push_str("\n            def Shader \"DiffuseTexture\"\n            {\n");
+            usd.push_str("                uniform token info:id = \"UsdUVTexture\"\n");
+            usd.push_str(&format!(
+                "                asset inputs:file = @textures/{}@\n",
+                texture_file_name.replace('@', "_")
+            ));
+            usd.push_str(&format!(
+                "                float2 inputs:st.connect = <{material_path}/PrimvarReader.outputs:result>\n"
+            ));
+            usd.push_str("                token inputs:sourceColorSpace = \"sRGB\"\n");
+            usd.push_str("                token inputs:wrapS = \"repeat\"\n");
+            usd.push_str("                token inputs:wrapT = \"repeat\"\n");
+            usd.push_str("                float3 outputs:rgb\n");
+            usd.push_str("                float outputs:a\n");
+            usd.push_str("            }\n");
+        }
+
+        usd.push_str("        }\n");
+    }
+    usd.push_str("    }\n");
+    Ok(usd)
+}
+
+fn joint_local_trs(
+    animation: &Animation,
+    skeleton: &Skeleton,
+    joint_index: usize,
+    time: f32,
+) -> (glam::Vec3, glam::Quat, glam::Vec3) {
+    let skeleton_joint = &skeleton.joints[joint_index];
+    match animation
+        .joints
+        .iter()
+        .find(|joint| joint.hash == skeleton_joint.hash)
+    {
+        Some(joint) => {
+            let (translation_min, translation_max, translation_lerp_value) =
+                anm::find_in_nearest_time(&joint.translations, time);
+            let (rotation_min, rotation_max, rotation_lerp_value) =
+                anm::find_in_nearest_time(&joint.rotations, time);
+            let (scale_min, scale_max, scale_lerp_value) =
+                anm::find_in_nearest_time(&joint.scales, time);
+
+            (
+                translation_min.lerp(translation_max, translation_lerp_value),
+                rotation_min.lerp(rotation_max, rotation_lerp_value).normalize(),
+                scale_min.lerp(scale_max, scale_lerp_value),
+            )
+        }
+        None => {
+            let (scale, rotation, translation) =
+                skeleton_joint.local_matrix.to_scale_rotation_translation();
+            (translation, rotation, scale)
+        }
+    }
+}
+
+// USD 的 matrix4d 按行书写且平移在最后一行，正好对应 glam 的各列
+fn matrix4d(matrix: &glam::Mat4) -> String {
+    let rows: Vec<String> = matrix
+        .to_cols_array_2d()
+        .iter()
+        .map(|row| format!("({}, {}, {}, {})", row[0], row[1], row[2], row[3]))
+        .collect();
+    format!("( {} )", rows.join(", "))
+}
+
+fn vec3(vector: &glam::Vec3) -> String {
+    format!("({}, {}, {})", vector.x, vector.y, vector.z)
+}
+
+fn tokens(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|x| format!("\"{x}\""))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// USD 的 prim 名只能包含字母、数字和下划线，且不能以数字开头
+// 子网格名称转换后可能重复，与动画名称一样添加后缀，GeomSubset 和 Material 使用相同的名称
+fn submesh_names(skin: &Skin) -> Vec<String> {
+    unique_names(skin.meshes.iter().map(|mesh| mesh.submesh.name.as_str()))
+}
+
+// 不同的名称转换后可能相同，例如 "R Arm" 和 "R_Arm"，重复的名称加上 '_' 后缀
+fn unique_names<'a>(names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut used: BTreeSet<String> = BTreeSet::new();
+    names
+        .map(|name| {
+            let mut name = usd_name(name);
+            while !used.insert(name.clone()) {
+                name.push('_');
+            }
+            name
+        })
+        .collect()
+}
+
+fn usd_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    match name.chars().next() {
+        Some(c) if !c.is_ascii_digit() => name,
+        _ => format!("_{name}"),
+    }
+}
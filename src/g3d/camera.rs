@@ -0,0 +1,59 @@
+use crate::MindModel;
+
+// 查看器的初始相机参数，软件渲染使用同样的取景
+pub const FOV_DEGREES: f32 = 45.0f32;
+pub const YAW_PITCH: [f32; 2] = [90.0f32, 70.0f32];
+pub const ZOOM: f32 = 500.0f32;
+pub const NEAR: f32 = 0.1f32;
+
+// 相机对准所有模型中心高度的平均值
+pub fn center_y(mind_models: &[MindModel]) -> f32 {
+    if mind_models.is_empty() {
+        return 0.0f32;
+    }
+    -(mind_models
+        .iter()
+        .map(|mind_model| mind_model.skin.center.y)
+        .sum::<f32>()
+        / mind_models.len() as f32)
+}
+
+pub fn initial_translation(mind_models: &[MindModel]) -> glam::Vec3 {
+    glam::vec3(0.0f32, center_y(mind_models), 0.0f32)
+}
+
+// 绕目标旋转的相机，返回(视图矩阵, 相机位置)
+pub fn view_matrix(translation: glam::Vec3, yaw_pitch: glam::Vec2, zoom: f32) -> (glam::Mat4, glam::Vec3) {
+    let position = glam::vec3(
+        yaw_pitch[1].to_radians().sin() * yaw_pitch[0].to_radians().cos(),
+        yaw_pitch[1].to_radians().cos(),
+        yaw_pitch[1].to_radians().sin() * yaw_pitch[0].to_radians().sin(),
+    )
+    .normalize();
+
+    let right = position.cross(glam::Vec3::Y).normalize();
+    let up = right.cross(position).normalize();
+
+    let eye = position * zoom;
+    let camera_pos = eye - translation;
+
+    let mut view_matrix = glam::Mat4::look_to_rh(eye, -eye, up);
+    view_matrix *= glam::Mat4::from_translation(translation);
+
+    (view_matrix, camera_pos)
+}
+
+pub fn projection_matrix(width: f32, height: f32) -> glam::Mat4 {
+    glam::Mat4::perspective_infinite_rh(FOV_DEGREES.to_radians(), width / height, NEAR)
+}
+
+// 与 Model::render 相同的模型矩阵：先旋转再平移
+pub fn model_matrix(position_offset: [f32; 3], rotation_angles: [f32; 3]) -> glam::Mat4 {
+    glam::Mat4::from_translation(glam::Vec3::from(position_offset))
+        * glam::Mat4::from_euler(
+            glam::EulerRot::XYZ,
+            rotation_angles[0].to_radians(),
+            rotation_angles[1].to_radians(),
+            rotation_angles[2].to_radians(),
+        )
+}
@@ -1,8 +1,10 @@
 pub mod bones;
+pub mod camera;
 pub mod floor;
 pub mod joints;
 pub mod model;
 pub mod names;
+pub mod rasterizer;
 pub mod skybox;
 pub mod refs_shaders;
 
@@ -117,20 +117,9 @@ impl Model {
 
             gl::BindVertexArray(self.vao);
 
-            // 应用位置偏移
-            let model_matrix = glam::Mat4::from_translation(glam::Vec3::new(
-                options.position_offset[0],
-                options.position_offset[1],
-                options.position_offset[2],
-            ));
-            let rotation_matrix = glam::Mat4::from_euler(
-                glam::EulerRot::XYZ,
-                options.rotation_angles[0].to_radians(),
-                options.rotation_angles[1].to_radians(),
-                options.rotation_angles[2].to_radians(),
-            );
-
-            let mvp_matrix = *projection_view_matrix * model_matrix *rotation_matrix;
+            // 应用位置偏移和旋转
+            let mvp_matrix = *projection_view_matrix
+                * super::camera::model_matrix(options.position_offset, options.rotation_angles);
 
             gl::UniformMatrix4fv(
                 self.mvp_ref,
@@ -0,0 +1,291 @@
+use std::{io::Cursor, path::Path};
+
+use crate::{config_json::OptionsJson, gls::texture, lol::anm, MindModel};
+
+// 不需要 GPU 的软件渲染器，输出与 Model::render 相同的画面：
+// 无光照的纹理采样、深度测试(LESS)、SRC_ALPHA 混合、不剔除背面
+pub struct Framebuffer {
+    pub width: usize,
+    pub height: usize,
+    // RGBA，从上到下逐行存放
+    pub color: Vec<u8>,
+    depth: Vec<f32>,
+}
+
+// load_source 解码后的第 0 级 RGBA 数据
+struct TextureImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+#[derive(Clone, Copy)]
+struct ClipVertex {
+    position: glam::Vec4,
+    uv: glam::Vec2,
+}
+
+impl Framebuffer {
+    // 与截图相同，清除为完全透明，深度为 1.0
+    pub fn new(width: usize, height: usize) -> Framebuffer {
+        Framebuffer {
+            width,
+            height,
+            color: vec![0u8; width * height * 4],
+            depth: vec![1.0f32; width * height],
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        image::save_buffer(
+            path,
+            &self.color,
+            self.width as u32,
+            self.height as u32,
+            image::ColorType::Rgba8,
+        )
+        .map_err(|error| format!("Could not save image {}: {error}", path.display()))
+    }
+}
+
+// 按各模型自己的显示、动画和位置设置渲染所有显示中的模型
+pub fn render_models(
+    framebuffer: &mut Framebuffer,
+    mind_models: &[MindModel],
+    options: &[OptionsJson],
+    projection_view_matrix: &glam::Mat4,
+) -> Result<(), String> {
+    for (mind_model, options) in mind_models.iter().zip(options.iter()) {
+        if options.show {
+            render_model(framebuffer, mind_model, options, projection_view_matrix)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn render_model(
+    framebuffer: &mut Framebuffer,
+    mind_model: &MindModel,
+    options: &OptionsJson,
+    projection_view_matrix: &glam::Mat4,
+) -> Result<(), String> {
+    let skin = &mind_model.skin;
+
+    // 与着色器一致，只有开启动画时才使用关节矩阵
+    let vertices = if options.use_animation && !mind_model.animations.is_empty() {
+        let mut joints_transforms = vec![glam::Mat4::IDENTITY; mind_model.skeleton.joints.len()];
+        anm::run_animation(
+            &mut joints_transforms,
+            &mind_model.animations[mind_model.animation_selected],
+            &mind_model.skeleton,
+            options.animation_time,
+        );
+        skin.skinned(&joints_transforms).0
+    } else {
+        skin.vertices.clone()
+    };
+
+    let mvp_matrix = *projection_view_matrix
+        * super::camera::model_matrix(options.position_offset, options.rotation_angles);
+    let clip_positions: Vec<glam::Vec4> = vertices
+        .iter()
+        .map(|vertex| mvp_matrix * vertex.extend(1.0f32))
+        .collect();
+
+    let mut textures: Vec<Option<TextureImage>> =
+        (0..mind_model.textures_paths.len()).map(|_| None).collect();
+
+    for (mesh_index, mesh) in skin.meshes.iter().enumerate() {
+        if !mind_model.show_meshes[mesh_index] {
+            continue;
+        }
+
+        let texture_index = mind_model.textures_selecteds[mesh_index];
+        if texture_index < textures.len() && textures[texture_index].is_none() {
+            textures[texture_index] = Some(load_texture(&mind_model.textures_paths[texture_index])?);
+        }
+        let texture = textures.get(texture_index).and_then(Option::as_ref);
+
+        let indices_offset = mesh.submesh.indices_offset as usize;
+        let indices_count = mesh.submesh.indices_count as usize;
+        for triangle in skin.indices[indices_offset..indices_offset + indices_count].chunks_exact(3) {
+            let triangle = [0, 1, 2].map(|i| ClipVertex {
+                position: clip_positions[triangle[i] as usize],
+                uv: skin.uvs[triangle[i] as usize],
+            });
+            draw_triangle(framebuffer, triangle, texture);
+        }
+    }
+
+    Ok(())
+}
+
+fn load_texture(path: &str) -> Result<TextureImage, String> {
+    let (images, width, height) = crate::parse_file(Path::new(path), |contents| {
+        texture::load_source(&mut Cursor::new(contents.as_slice()))
+    })?;
+    let width = width.max(0) as usize;
+    let height = height.max(0) as usize;
+
+    let mut pixels = images.into_iter().next().unwrap_or_default();
+    pixels.resize(width * height * 4, 255);
+
+    Ok(TextureImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+// 先用近平面(z >= -w)裁剪，其余平面在光栅化时按屏幕范围处理
+fn draw_triangle(framebuffer: &mut Framebuffer, triangle: [ClipVertex; 3], texture: Option<&TextureImage>) {
+    let inside = |vertex: &ClipVertex| vertex.position.z + vertex.position.w >= 0.0f32;
+
+    if triangle.iter().all(inside) {
+        rasterize(framebuffer, triangle, texture);
+        return;
+    }
+    if !triangle.iter().any(inside) {
+        return;
+    }
+
+    let mut polygon: Vec<ClipVertex> = Vec::with_capacity(4);
+    for i in 0..3 {
+        let current = triangle[i];
+        let next = triangle[(i + 1) % 3];
+        let current_distance = current.position.z + current.position.w;
+        let next_distance = next.position.z + next.position.w;
+
+        if current_distance >= 0.0f32 {
+            polygon.push(current);
+        }
+        if (current_distance >= 0.0f32) != (next_distance >= 0.0f32) {
+            let t = current_distance / (current_distance - next_distance);
+            polygon.push(ClipVertex {
+                position: current.position.lerp(next.position, t),
+                uv: current.uv.lerp(next.uv, t),
+            });
+        }
+    }
+
+    for i in 1..polygon.len().saturating_sub(1) {
+        rasterize(framebuffer, [polygon[0], polygon[i], polygon[i + 1]], texture);
+    }
+}
+
+fn rasterize(framebuffer: &mut Framebuffer, triangle: [ClipVertex; 3], texture: Option<&TextureImage>) {
+    let width = framebuffer.width as f32;
+    let height = framebuffer.height as f32;
+
+    // 屏幕坐标 y 向下，与截图翻转后的图像方向一致
+    let mut screen = [glam::Vec3::ZERO; 3];
+    let mut inverse_w = [0.0f32; 3];
+    for i in 0..3 {
+        let position = triangle[i].position;
+        if position.w <= 0.0f32 {
+            return;
+        }
+        inverse_w[i] = 1.0f32 / position.w;
+        let ndc = position.truncate() * inverse_w[i];
+        screen[i] = glam::vec3(
+            (ndc.x * 0.5f32 + 0.5f32) * width,
+            (0.5f32 - ndc.y * 0.5f32) * height,
+            ndc.z,
+        );
+    }
+
+    let area = edge(screen[0], screen[1], screen[2]);
+    if area == 0.0f32 || !area.is_finite() {
+        return;
+    }
+
+    let min_x = screen.iter().map(|x| x.x).fold(f32::MAX, f32::min).floor().max(0.0f32) as usize;
+    let min_y = screen.iter().map(|x| x.y).fold(f32::MAX, f32::min).floor().max(0.0f32) as usize;
+    let max_x = screen.iter().map(|x| x.x).fold(f32::MIN, f32::max).ceil().min(width) as usize;
+    let max_y = screen.iter().map(|x| x.y).fold(f32::MIN, f32::max).ceil().min(height) as usize;
+
+    // 透视校正插值 uv/w
+    let uv_over_w = [0, 1, 2].map(|i| triangle[i].uv * inverse_w[i]);
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let point = glam::vec3(x as f32 + 0.5f32, y as f32 + 0.5f32, 0.0f32);
+            let b0 = edge(screen[1], screen[2], point) / area;
+            let b1 = edge(screen[2], screen[0], point) / area;
+            let b2 = edge(screen[0], screen[1], point) / area;
+            if b0 < 0.0f32 || b1 < 0.0f32 || b2 < 0.0f32 {
+                continue;
+            }
+
+            let depth = b0 * screen[0].z + b1 * screen[1].z + b2 * screen[2].z;
+            let pixel = y * framebuffer.width + x;
+            if !(-1.0f32..=1.0f32).contains(&depth) || depth >= framebuffer.depth[pixel] {
+                continue;
+            }
+
+            let w = b0 * inverse_w[0] + b1 * inverse_w[1] + b2 * inverse_w[2];
+            let uv = (uv_over_w[0] * b0 + uv_over_w[1] * b1 + uv_over_w[2] * b2) / w;
+
+            let source = match texture {
+                Some(texture) => sample(texture, uv),
+                None => glam::Vec4::ONE,
+            };
+            // 开启 SAMPLE_ALPHA_TO_COVERAGE 时完全透明的片段不写入深度
+            if source.w <= 0.0f32 {
+                continue;
+            }
+
+            framebuffer.depth[pixel] = depth;
+            blend(&mut framebuffer.color[pixel * 4..pixel * 4 + 4], source);
+        }
+    }
+}
+
+fn edge(a: glam::Vec3, b: glam::Vec3, point: glam::Vec3) -> f32 {
+    (b.x - a.x) * (point.y - a.y) - (b.y - a.y) * (point.x - a.x)
+}
+
+// GL_LINEAR + GL_REPEAT 的双线性采样
+fn sample(texture: &TextureImage, uv: glam::Vec2) -> glam::Vec4 {
+    if texture.width == 0 || texture.height == 0 {
+        return glam::Vec4::ONE;
+    }
+
+    let x = uv.x * texture.width as f32 - 0.5f32;
+    let y = uv.y * texture.height as f32 - 0.5f32;
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let fx = x - x0;
+    let fy = y - y0;
+
+    let texel = |x: f32, y: f32| {
+        let x = (x as i64).rem_euclid(texture.width as i64) as usize;
+        let y = (y as i64).rem_euclid(texture.height as i64) as usize;
+        let offset = (y * texture.width + x) * 4;
+        glam::Vec4::new(
+            texture.pixels[offset] as f32,
+            texture.pixels[offset + 1] as f32,
+            texture.pixels[offset + 2] as f32,
+            texture.pixels[offset + 3] as f32,
+        ) / 255.0f32
+    };
+
+    let top = texel(x0, y0).lerp(texel(x0 + 1.0f32, y0), fx);
+    let bottom = texel(x0, y0 + 1.0f32).lerp(texel(x0 + 1.0f32, y0 + 1.0f32), fx);
+    top.lerp(bottom, fy)
+}
+
+// glBlendFunc(SRC_ALPHA, ONE_MINUS_SRC_ALPHA)，alpha 通道使用同样的混合方式
+fn blend(destination: &mut [u8], source: glam::Vec4) {
+    let alpha = source.w;
+    let destination_color = glam::Vec4::new(
+        destination[0] as f32,
+        destination[1] as f32,
+        destination[2] as f32,
+        destination[3] as f32,
+    ) / 255.0f32;
+    let result = source * alpha + destination_color * (1.0f32 - alpha);
+    for (channel, value) in destination.iter_mut().zip(result.to_array()) {
+        *channel = (value.clamp(0.0f32, 1.0f32) * 255.0f32).round() as u8;
+    }
+}
@@ -1,8 +1,8 @@
 use glfw::Glfw;
 use native_dialog::FileDialog;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use imgui::StyleColor;
-use crate::{config_json::{ConfigJson, OptionsJson}, export, MindModel};
+use crate::{config_json::{ConfigJson, OptionsJson, PathJson}, export, import, preset::PresetJson, MindModel};
 
 
 #[derive(Debug, Clone)]
@@ -116,7 +116,7 @@ pub fn settings(
     let _button_style = ui.push_style_color(imgui::StyleColor::Button, [0.3, 0.7, 1.0, 1.0]);
     if ui.button("重置镜头坐标(Reset)") {
         *camera_pos = glam::Vec3::new(0.0, 0.0, 5.0);
-        *camera_rotation = glam::Vec2::new(90.0, 70.0);
+        *camera_rotation = glam::Vec2::from(crate::g3d::camera::YAW_PITCH);
     }
     if ui.is_item_hovered() {
         ui.tooltip(|| { ui.text("将相机重置到默认位置和角度"); });
@@ -137,13 +137,19 @@ pub fn settings(
     _button_style2.pop();
 }
 
+pub enum PresetAction {
+    Import(PathBuf),
+    Export(PathBuf),
+}
+
 pub fn model(
     ui: &imgui::Ui,
     options: &mut OptionsJson,
     mind_model: &mut MindModel,
-    export_as: &mut u8,
+    export_options: &mut export::ExportOptions,
     name: &String,
-
+    open_texture_browser: &mut bool,
+    preset_action: &mut Option<PresetAction>,
 ) {
 
     let _header_style = ui.push_style_color(StyleColor::Header, [0.2, 0.5, 0.3, 1.0]);      // 深绿
@@ -160,6 +166,9 @@ pub fn model(
         .flags(imgui::TreeNodeFlags::SPAN_AVAIL_WIDTH)
         .framed(true)
         .build(|| {
+            if ui.button_with_size("纹理浏览器(Texture Browser)", [ui.content_region_avail()[0], 0.0f32]) {
+                *open_texture_browser = true;
+            }
             for i in 0..mind_model.skin.meshes.len() {
                 let _meshes_id = ui.push_id_usize(i);
                 ui.checkbox(
@@ -297,20 +306,329 @@ pub fn model(
         .flags(imgui::TreeNodeFlags::SPAN_AVAIL_WIDTH)
         .framed(true)
         .build(|| {
-            ui.radio_button("导出为gltf(Export as gltf)", export_as, 0);
-            ui.radio_button("导出为glb(Export as glb)", export_as, 1);
+            ui.radio_button("导出为gltf(Export as gltf)", &mut export_options.export_as, 0);
+            ui.radio_button("导出为glb(Export as glb)", &mut export_options.export_as, 1);
+            ui.radio_button("导出为dae(Export as dae)", &mut export_options.export_as, 2);
+            ui.radio_button("导出当前姿势为obj(Export current pose as obj)", &mut export_options.export_as, 3);
+            ui.radio_button("导出为usda(Export as usda)", &mut export_options.export_as, 4);
+
+            ui.align_text_to_frame_padding();
+            ui.text("输出目录(Output): ");
+            ui.same_line();
+            ui.input_text("##export_output_dir", &mut export_options.output_dir).build();
+            ui.same_line();
+            if ui.button("选择(Select)##export_output_dir") {
+                let path = FileDialog::new()
+                    .set_location(&export_options.output_dir)
+                    .show_open_single_dir()
+                    .unwrap_or(None);
+                if let Some(path) = path {
+                    export_options.output_dir = path.to_string_lossy().into_owned();
+                }
+            }
+
+            ui.align_text_to_frame_padding();
+            ui.text("文件名(File Name): ");
+            ui.same_line();
+            ui.input_text("##export_file_name", &mut export_options.file_name).build();
+            if ui.is_item_hovered() {
+                ui.tooltip(|| {
+                    ui.text("{name} 模型名称(Model name)");
+                    ui.text("{animation} 当前动画名称(Current animation name)");
+                });
+            }
+
+            ui.align_text_to_frame_padding();
+            ui.text("文件已存在(If Exists): ");
+            ui.same_line();
+            ui.combo_simple_string("##export_conflict", &mut export_options.conflict, &export::CONFLICT_MODES);
+
+            if export_options.export_as <= 1 {
+                ui.align_text_to_frame_padding();
+                ui.text("纹理格式(Texture Format): ");
+                ui.same_line();
+                ui.combo_simple_string("##export_texture_format", &mut export_options.texture_format, &export::TEXTURE_FORMATS);
+            }
+
+            ui.align_text_to_frame_padding();
+            ui.text("单位缩放(Unit Scale): ");
+            ui.same_line();
+            ui.input_float("##export_unit_scale", &mut export_options.unit_scale)
+                .step(0.01f32)
+                .display_format("%.4f")
+                .build();
+            ui.same_line();
+            if ui.button("米(Meters)") {
+                export_options.unit_scale = 0.01f32;
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip(|| {
+                    ui.text("游戏单位约为厘米，0.01 转换为米");
+                });
+            }
+            if export_options.unit_scale <= 0.0f32 {
+                export_options.unit_scale = 1.0f32;
+            }
+
+            ui.align_text_to_frame_padding();
+            ui.text("向上轴(Up Axis): ");
+            ui.same_line();
+            ui.combo_simple_string("##export_up_axis", &mut export_options.up_axis, &export::UP_AXES);
+
+            ui.checkbox("镜像X轴(Flip Handedness)", &mut export_options.flip_handedness);
+
+            ui.checkbox("仅导出可见网格(Only Visible Meshes)", &mut export_options.only_visible_meshes);
+            ui.checkbox("导出后打开目录(Open Folder After Export)", &mut export_options.open_folder);
+
             let export_animations = mind_model.export_animations.unwrap_or(true);
             if ui.checkbox("导出动画(Export Animations)", &mut mind_model.export_animations.get_or_insert(true)) {
                 // 状态变更自动保存到模型中
             }
+            if export_animations {
+                export_animations_list(ui, options, mind_model);
+            }
+            export_alpha_modes_list(ui, options, mind_model);
             if ui.button_with_size("导出模型(Export Model)", [ui.content_region_avail()[0], 0.0f32]) {
-                export::export_model(*export_as, name, mind_model, export_animations);
+                let result = export::catch_panic(|| {
+                    export::export_model(export_options, name, mind_model, export_animations, options)
+                });
+                export_options.message = match result {
+                    Ok(path) => format!("导出完成(Export completed): {}", path.display()),
+                    Err(error) => {
+                        println!("{error}");
+                        format!("导出失败(Export failed): {error}")
+                    }
+                };
             }
             if ui.is_item_hovered() {
                 ui.tooltip(|| {
                     ui.text("默认保存到软件目录export目录下");
                 });
             }
+            if !export_options.message.is_empty() {
+                ui.text_wrapped(&export_options.message);
+            }
+        });
+
+    ui.tree_node_config("预设(Preset)")
+        .flags(imgui::TreeNodeFlags::SPAN_AVAIL_WIDTH)
+        .framed(true)
+        .build(|| {
+            let location = std::env::current_dir().unwrap_or_default();
+            let button_width = (ui.content_region_avail()[0] - ui.clone_style().item_spacing[0]) / 2.0f32;
+            if ui.button_with_size("导入预设(Import Preset)", [button_width, 0.0f32]) {
+                let path = FileDialog::new()
+                    .set_location(&location)
+                    .add_filter("预设(Preset)", &["json"])
+                    .show_open_single_file()
+                    .unwrap_or(None);
+                if let Some(path) = path {
+                    *preset_action = Some(PresetAction::Import(path));
+                }
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip(|| {
+                    ui.text("应用预设的文件路径、选项和子网格纹理(Apply the paths, options and submesh textures of a preset)");
+                });
+            }
+            ui.same_line();
+            if ui.button_with_size("导出预设(Export Preset)", [button_width, 0.0f32]) {
+                let path = FileDialog::new()
+                    .set_location(&location)
+                    .set_filename(&format!("{name}.json"))
+                    .add_filter("预设(Preset)", &["json"])
+                    .show_save_single_file()
+                    .unwrap_or(None);
+                if let Some(mut path) = path {
+                    if path.extension().is_none() {
+                        path.set_extension("json");
+                    }
+                    *preset_action = Some(PresetAction::Export(path));
+                }
+            }
+        });
+}
+
+fn export_animations_list(ui: &imgui::Ui, options: &mut OptionsJson, mind_model: &MindModel) {
+    ui.tree_node_config("选择动画(Select Animations)")
+        .flags(imgui::TreeNodeFlags::SPAN_AVAIL_WIDTH)
+        .framed(true)
+        .build(|| {
+            if ui.button("全选(All)") {
+                for i in 0..mind_model.animations.len() {
+                    options
+                        .export_animation_mut(
+                            &mind_model.animations_file_names[i],
+                            mind_model.animations[i].duration,
+                        )
+                        .export = true;
+                }
+            }
+            ui.same_line();
+            if ui.button("全不选(None)") {
+                for i in 0..mind_model.animations.len() {
+                    options
+                        .export_animation_mut(
+                            &mind_model.animations_file_names[i],
+                            mind_model.animations[i].duration,
+                        )
+                        .export = false;
+                }
+            }
+
+            for i in 0..mind_model.animations.len() {
+                let _animation_id = ui.push_id_usize(i);
+                let duration = mind_model.animations[i].duration;
+                let animation_export =
+                    options.export_animation_mut(&mind_model.animations_file_names[i], duration);
+
+                ui.checkbox(
+                    mind_model.animations_file_names[i].as_str(),
+                    &mut animation_export.export,
+                );
+                if animation_export.export {
+                    ui.align_text_to_frame_padding();
+                    ui.text("开始(Start): ");
+                    ui.same_line();
+                    ui.slider_config("##start", 0.0f32, duration)
+                        .display_format("%.3f")
+                        .flags(imgui::SliderFlags::ALWAYS_CLAMP)
+                        .build(&mut animation_export.start);
+
+                    ui.align_text_to_frame_padding();
+                    ui.text("结束(End):   ");
+                    ui.same_line();
+                    ui.slider_config("##end", 0.0f32, duration)
+                        .display_format("%.3f")
+                        .flags(imgui::SliderFlags::ALWAYS_CLAMP)
+                        .build(&mut animation_export.end);
+
+                    ui.align_text_to_frame_padding();
+                    ui.text("速度(Speed): ");
+                    ui.same_line();
+                    ui.slider_config("##speed", 0.01f32, 10.0f32)
+                        .display_format("%.2f")
+                        .flags(imgui::SliderFlags::ALWAYS_CLAMP)
+                        .build(&mut animation_export.speed);
+
+                    if animation_export.end < animation_export.start {
+                        animation_export.end = animation_export.start;
+                    }
+                }
+            }
+        });
+}
+
+fn export_alpha_modes_list(ui: &imgui::Ui, options: &mut OptionsJson, mind_model: &MindModel) {
+    ui.tree_node_config("透明模式(Alpha Mode)")
+        .flags(imgui::TreeNodeFlags::SPAN_AVAIL_WIDTH)
+        .framed(true)
+        .build(|| {
+            for mesh in mind_model.skin.meshes.iter() {
+                let name = &mesh.submesh.name;
+                let mut alpha_mode = options.alpha_modes.get(name).copied().unwrap_or(0);
+
+                ui.align_text_to_frame_padding();
+                ui.text(name);
+                ui.same_line();
+                if ui.combo_simple_string(format!("##alpha_mode_{name}"), &mut alpha_mode, &export::ALPHA_MODES) {
+                    if alpha_mode == 0 {
+                        options.alpha_modes.remove(name);
+                    } else {
+                        options.alpha_modes.insert(name.to_owned(), alpha_mode);
+                    }
+                }
+            }
+        });
+}
+
+pub fn export_scene(
+    ui: &imgui::Ui,
+    export_options: &mut export::ExportOptions,
+    export_animations: &mut bool,
+    config_json: &ConfigJson,
+    mind_models: &[MindModel],
+) {
+    ui.tree_node_config("导出场景(Export Scene)")
+        .flags(imgui::TreeNodeFlags::SPAN_AVAIL_WIDTH)
+        .framed(true)
+        .build(|| {
+            ui.radio_button("导出为gltf(Export as gltf)##scene", &mut export_options.scene_export_as, 0);
+            ui.radio_button("导出为glb(Export as glb)##scene", &mut export_options.scene_export_as, 1);
+            ui.checkbox("导出当前动画(Export Current Animations)##scene", export_animations);
+
+            if ui.button_with_size("导出场景(Export Scene)", [ui.content_region_avail()[0], 0.0f32]) {
+                let names: Vec<String> = config_json.paths.iter().map(|path| path.name.clone()).collect();
+                let result = export::catch_panic(|| {
+                    export::scene::export_scene(
+                        export_options,
+                        &names,
+                        mind_models,
+                        &config_json.options,
+                        *export_animations,
+                    )
+                });
+                export_options.message = match result {
+                    Ok(path) => format!("导出完成(Export completed): {}", path.display()),
+                    Err(error) => {
+                        println!("{error}");
+                        format!("导出失败(Export failed): {error}")
+                    }
+                };
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip(|| {
+                    ui.text("导出所有显示中的模型，输出目录和文件名使用模型导出面板中的设置");
+                });
+            }
+            if !export_options.message.is_empty() {
+                ui.text_wrapped(&export_options.message);
+            }
+        });
+}
+
+pub fn export_batch(
+    ui: &imgui::Ui,
+    export_options: &mut export::ExportOptions,
+    export_animations: &mut bool,
+    config_json: &ConfigJson,
+) {
+    ui.tree_node_config("批量导出(Batch Export)")
+        .flags(imgui::TreeNodeFlags::SPAN_AVAIL_WIDTH)
+        .framed(true)
+        .build(|| {
+            ui.radio_button("导出为gltf(Export as gltf)##batch", &mut export_options.export_as, 0);
+            ui.radio_button("导出为glb(Export as glb)##batch", &mut export_options.export_as, 1);
+            ui.radio_button("导出为dae(Export as dae)##batch", &mut export_options.export_as, 2);
+            ui.radio_button("导出当前姿势为obj(Export current pose as obj)##batch", &mut export_options.export_as, 3);
+            ui.radio_button("导出为usda(Export as usda)##batch", &mut export_options.export_as, 4);
+            ui.checkbox("导出动画(Export Animations)##batch", export_animations);
+
+            if ui.button_with_size("导出全部模型(Export All Models)", [ui.content_region_avail()[0], 0.0f32]) {
+                export_options.message =
+                    match export::batch::export_batch(export_options, config_json, *export_animations) {
+                        Ok((path, report)) => format!(
+                            "批量导出完成(Batch export completed): {} 成功(succeeded), {} 失败(failed), {:.2}s\n{}",
+                            report.succeeded,
+                            report.failed,
+                            report.total_seconds,
+                            path.display()
+                        ),
+                        Err(error) => {
+                            println!("{error}");
+                            format!("导出失败(Export failed): {error}")
+                        }
+                    };
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip(|| {
+                    ui.text("按已保存的配置重新读取并导出 config.json 中的所有模型，报告写入输出目录下的 batch_report.json");
+                    ui.text("Reloads and exports every model in config.json with its saved settings");
+                });
+            }
+            if !export_options.message.is_empty() {
+                ui.text_wrapped(&export_options.message);
+            }
         });
 }
 
@@ -320,6 +638,10 @@ pub struct AddModel {
     pub skeleton: String,
     pub textures: String,
     pub animations: String,
+    pub extra_textures: Vec<String>,
+    pub extra_animations: Vec<String>,
+    // 导入的预设，添加模型后应用其中的显示选项和子网格设置
+    pub preset: Option<PresetJson>,
 }
 
 impl AddModel {
@@ -330,7 +652,71 @@ impl AddModel {
             skeleton: String::new(),
             textures: String::new(),
             animations: String::new(),
+            extra_textures: vec![],
+            extra_animations: vec![],
+            preset: None,
+        }
+    }
+
+    pub fn from_path(path: &PathJson) -> Self {
+        Self {
+            name: path.name.clone(),
+            skin: path.skin.clone(),
+            skeleton: path.skeleton.clone(),
+            textures: path.textures.clone(),
+            animations: path.animations.clone(),
+            extra_textures: path.extra_textures.clone(),
+            extra_animations: path.extra_animations.clone(),
+            preset: None,
+        }
+    }
+
+    // 名称为空时使用 "model"
+    pub fn path_json(&self) -> PathJson {
+        PathJson {
+            name: if self.name.is_empty() {
+                String::from("model")
+            } else {
+                self.name.clone()
+            },
+            skin: self.skin.clone(),
+            skeleton: self.skeleton.clone(),
+            textures: self.textures.clone(),
+            animations: self.animations.clone(),
+            extra_textures: self.extra_textures.clone(),
+            extra_animations: self.extra_animations.clone(),
+        }
+    }
+
+    // 根据 skn 路径推导其他文件路径：同名 skl，同目录下的 textures 和 animations 目录
+    pub fn from_skin(path: &Path) -> Self {
+        let mut add_model = Self::new();
+        add_model.skin = path.to_string_lossy().into_owned();
+
+        if let Some(parent_dir) = path.parent() {
+            let file_stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("");
+            add_model.name = String::from(file_stem);
+
+            // 推导 Skeleton 路径
+            let skeleton_path = parent_dir.join(format!("{}.skl", file_stem));
+            if skeleton_path.exists() {
+                add_model.skeleton = skeleton_path.to_string_lossy().into_owned();
+            }
+
+            // 推导 Textures 路径
+            let textures_path = parent_dir.join("textures"); // 假设纹理在textures目录下
+            if textures_path.exists() {
+                add_model.textures = textures_path.to_string_lossy().into_owned();
+            }
+
+            // 推导 Animations 路径
+            let animations_path = parent_dir.join("animations");
+            if animations_path.exists() {
+                add_model.animations = animations_path.to_string_lossy().into_owned();
+            }
         }
+
+        add_model
     }
 }
 
@@ -364,41 +750,7 @@ pub fn add_model<F>(
                     .unwrap();
 
                 if let Some(path) = file_dialog_path {
-                    // 更新 Skin 字段
-                    add_model.skin.clear();
-                    add_model.skin.insert_str(0, path.to_str().unwrap());
-
-                    // 解析路径并推导其他文件路径
-                    if let Some(parent_dir) = path.parent() {
-                        let file_stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("");
-
-
-                        add_model.name.clear();
-                        add_model.name.insert_str(0, file_stem);
-
-                        // 推导 Skeleton 路径
-                        let skeleton_path = parent_dir.join(format!("{}.skl", file_stem));
-                        if skeleton_path.exists() {
-                            add_model.skeleton.clear();
-                            add_model.skeleton.insert_str(0, skeleton_path.to_str().unwrap());
-                        }
-
-                        // 推导 Textures 路径
-                        let textures_path = parent_dir.join("textures"); // 假设纹理在textures目录下
-                        if textures_path.exists() {
-                            add_model.textures.clear();
-                            add_model.textures.insert_str(0, textures_path.to_str().unwrap());
-                        } else {
-                            add_model.textures.clear(); // 如果路径不存在，则保持为空
-                        }
-
-                        // 推导 Animations 路径
-                        let animations_path = parent_dir.join("animations");
-                        if animations_path.exists() {
-                            add_model.animations.clear();
-                            add_model.animations.insert_str(0, animations_path.to_str().unwrap());
-                        }
-                    }
+                    *add_model = AddModel::from_skin(&path);
                 }
             }
 
@@ -456,35 +808,76 @@ pub fn add_model<F>(
             }
 
             ui.separator();
-            if ui.button_with_size("导入JSON配置(Import JSON)", [ui.content_region_avail()[0], 0.0f32]) {
+            if ui.button_with_size("导入预设(Import Preset)", [ui.content_region_avail()[0], 0.0f32]) {
                 if let Some(path) = FileDialog::new()
                     .set_location(&working_dir)
-                    .add_filter("JSON配置", &["json"])
+                    .add_filter("预设(Preset)", &["json"])
                     .show_open_single_file()
                     .unwrap()
                 {
-                    if let Ok(json_str) = std::fs::read_to_string(&path) {
-                        if let Ok(config) = serde_json::from_str::<ConfigJson>(&json_str) {
-                            if !config.paths.is_empty() {
-                                let path = &config.paths[0];
-                                add_model.name = path.name.clone();
-                                add_model.skin = path.skin.clone();
-                                add_model.skeleton = path.skeleton.clone();
-                                add_model.textures = path.textures.clone();
-                                add_model.animations = path.animations.clone();
-
-                                // 如果配置中有MESHES数据，保存到临时文件供后续使用
-                                if !config.meshes.is_empty() {
-                                    let _ = std::fs::write(
-                                        "temp_meshes.json",
-                                        serde_json::to_string_pretty(&config.meshes[0]).unwrap()
-                                    );
-                                }
-                            }
+                    match PresetJson::read(&path) {
+                        Ok(preset) => *add_model = preset.add_model(),
+                        Err(error) => {
+                            println!("{error}");
+                            ui.open_popup("##preset_error");
+                        }
+                    }
+                }
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip(|| {
+                    ui.text("单个模型的预设或配置文件中的第一个模型(A model preset or the first model of a config file)");
+                });
+            }
+            ui.popup("##preset_error", || {
+                ui.text("错误: 无法读取预设, 详见控制台输出!(Could not read preset)");
+                if ui.button("确定") { ui.close_current_popup(); }
+            });
+
+            if ui.button_with_size("导入glTF(Import glTF)", [ui.content_region_avail()[0], 0.0f32]) {
+                if let Some(path) = FileDialog::new()
+                    .set_location(&working_dir)
+                    .add_filter("glTF", &["gltf", "glb"])
+                    .show_open_single_file()
+                    .unwrap()
+                {
+                    let name = path
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .unwrap_or("model")
+                        .to_owned();
+                    let output_dir = working_dir.join("import").join(&name);
+
+                    match import::import_gltf(&path, &output_dir) {
+                        Ok(imported) => {
+                            add_model.name = imported.name;
+                            add_model.skin = imported.skin;
+                            add_model.skeleton = imported.skeleton;
+                            add_model.textures = imported.textures;
+                            add_model.animations = imported.animations;
+
+                            // 通过预设传递子网格与纹理的对应关系
+                            add_model.preset = Some(PresetJson {
+                                version: crate::preset::PRESET_VERSION,
+                                path: Default::default(),
+                                options: OptionsJson::new(),
+                                meshes: imported.meshes,
+                            });
+
+                            add_funct(add_model);
+                            *add_model = AddModel::new();
+                        }
+                        Err(error) => {
+                            println!("Could not import gltf: {error}");
+                            ui.open_popup("##import_error");
                         }
                     }
                 }
             }
+            ui.popup("##import_error", || {
+                ui.text("错误: 无法导入glTF, 详见控制台输出!(Could not import glTF)");
+                if ui.button("确定") { ui.close_current_popup(); }
+            });
 
             if ui.button_with_size("添加(Add)", [ui.content_region_avail()[0], 0.0f32]) {
                 // 添加路径空值检查
@@ -494,11 +887,7 @@ pub fn add_model<F>(
                     ui.open_popup("##missing_skeleton");
                 } else {
                     add_funct(add_model);
-                    add_model.name.clear();
-                    add_model.skin.clear();
-                    add_model.skeleton.clear();
-                    add_model.textures.clear();
-                    add_model.animations.clear();
+                    *add_model = AddModel::new();
                 }
             }
             // 添加错误提示弹窗
@@ -556,8 +945,546 @@ pub fn screenshot(
         });
 }
 
+pub fn texture_browser(
+    ui: &imgui::Ui,
+    texture_browser: &mut super::TextureBrowser,
+    config_json: &ConfigJson,
+    mind_models: &[MindModel],
+) {
+    if !texture_browser.opened {
+        return;
+    }
+    let Some(mind_model) = mind_models.get(texture_browser.model) else {
+        texture_browser.opened = false;
+        return;
+    };
+    texture_browser.update(mind_model);
+
+    let mut opened = texture_browser.opened;
+    ui.window(format!(
+        "纹理浏览器(Texture Browser) - {}###texture_browser",
+        config_json.paths[texture_browser.model].name
+    ))
+    .size([900.0f32, 640.0f32], imgui::Condition::FirstUseEver)
+    .opened(&mut opened)
+    .build(|| {
+        if texture_browser.entries.is_empty() {
+            ui.text("模型没有纹理(The model has no textures)");
+            return;
+        }
+
+        const THUMBNAIL_SIZE: f32 = 96.0f32;
+        let style = ui.clone_style();
+
+        ui.child_window("##texture_grid")
+            .size([THUMBNAIL_SIZE * 2.0f32 + 48.0f32, 0.0f32])
+            .border(true)
+            .build(|| {
+                let columns = ((ui.content_region_avail()[0] + style.item_spacing[0])
+                    / (THUMBNAIL_SIZE + style.frame_padding[0] * 2.0f32 + style.item_spacing[0]))
+                    .floor()
+                    .max(1.0f32) as usize;
+
+                for i in 0..texture_browser.entries.len() {
+                    let _texture_id = ui.push_id_usize(i);
+                    if i % columns != 0 {
+                        ui.same_line();
+                    }
+
+                    ui.group(|| {
+                        let entry = &mut texture_browser.entries[i];
+                        let selected = texture_browser.selected == i;
+                        let _selected_style = selected.then(|| {
+                            ui.push_style_color(StyleColor::Button, [0.2f32, 0.5f32, 0.3f32, 1.0f32])
+                        });
+
+                        let clicked = match mind_model.textures.get(i) {
+                            Some(texture) if entry.error.is_none() => ui.image_button(
+                                "##thumbnail",
+                                imgui::TextureId::new(texture.id as usize),
+                                [THUMBNAIL_SIZE, THUMBNAIL_SIZE],
+                            ),
+                            _ => ui.button_with_size("?##thumbnail", [THUMBNAIL_SIZE, THUMBNAIL_SIZE]),
+                        };
+                        if clicked {
+                            texture_browser.selected = i;
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip(|| {
+                                ui.text(&entry.name);
+                                match (&entry.error, entry.format) {
+                                    (Some(error), _) => ui.text(error),
+                                    (None, Some(format)) => ui.text(format!(
+                                        "{:?} {}x{}, {} mipmaps, {:.1} KB",
+                                        format,
+                                        entry.width,
+                                        entry.height,
+                                        entry.mipmap_count,
+                                        entry.file_size as f32 / 1024.0f32
+                                    )),
+                                    _ => {}
+                                }
+                            });
+                        }
+
+                        ui.checkbox("##checked", &mut entry.checked);
+                        ui.same_line();
+                        ui.text(truncate(&entry.name, 10));
+                    });
+                }
+            });
+
+        ui.same_line();
+
+        ui.group(|| {
+            let entry = &texture_browser.entries[texture_browser.selected];
+            ui.text(&entry.name);
+            ui.text_disabled(&entry.path);
+            match (&entry.error, entry.format) {
+                (Some(error), _) => ui.text_colored([1.0f32, 0.4f32, 0.4f32, 1.0f32], error),
+                (None, Some(format)) => ui.text(format!(
+                    "格式(Format): {:?}  尺寸(Size): {}x{}  Mipmaps: {}  文件(File): {:.1} KB",
+                    format,
+                    entry.width,
+                    entry.height,
+                    entry.mipmap_count,
+                    entry.file_size as f32 / 1024.0f32
+                )),
+                _ => {}
+            }
+
+            if !texture_browser.mipmap_views.is_empty() {
+                let mipmap_names: Vec<String> = texture_browser
+                    .mipmap_views
+                    .iter()
+                    .enumerate()
+                    .map(|(level, x)| format!("Mip {level} ({}x{})", x.width, x.height))
+                    .collect();
+
+                ui.align_text_to_frame_padding();
+                ui.text("Mipmap:");
+                ui.same_line();
+                ui.set_next_item_width(180.0f32);
+                ui.combo_simple_string("##mipmap", &mut texture_browser.mipmap, &mipmap_names);
+
+                ui.same_line();
+                ui.text("缩放(Zoom):");
+                ui.same_line();
+                ui.set_next_item_width(160.0f32);
+                ui.slider_config("##zoom", 0.05f32, 16.0f32)
+                    .display_format("%.2fx")
+                    .flags(imgui::SliderFlags::LOGARITHMIC | imgui::SliderFlags::ALWAYS_CLAMP)
+                    .build(&mut texture_browser.zoom);
+                ui.same_line();
+                let mipmap_view = &texture_browser.mipmap_views[texture_browser.mipmap];
+                if ui.button("1:1") {
+                    texture_browser.zoom = 1.0f32;
+                }
+                ui.same_line();
+                let fit = ui.button("适应(Fit)");
+
+                ui.child_window("##texture_view")
+                    .size([0.0f32, -ui.frame_height_with_spacing() * 4.0f32])
+                    .border(true)
+                    .horizontal_scrollbar(true)
+                    .build(|| {
+                        let available = ui.content_region_avail();
+                        if fit {
+                            texture_browser.zoom = (available[0] / mipmap_view.width as f32)
+                                .min(available[1] / mipmap_view.height as f32)
+                                .clamp(0.05f32, 16.0f32);
+                        }
+                        // 在预览区域内 CTRL+滚轮缩放
+                        if ui.is_window_hovered() && ui.io().key_ctrl && ui.io().mouse_wheel != 0.0f32 {
+                            texture_browser.zoom = (texture_browser.zoom
+                                * 1.25f32.powf(ui.io().mouse_wheel))
+                            .clamp(0.05f32, 16.0f32);
+                        }
+                        imgui::Image::new(
+                            imgui::TextureId::new(mipmap_view.texture.id as usize),
+                            [
+                                mipmap_view.width as f32 * texture_browser.zoom,
+                                mipmap_view.height as f32 * texture_browser.zoom,
+                            ],
+                        )
+                        .build(ui);
+                    });
+            }
+
+            ui.separator();
+
+            ui.align_text_to_frame_padding();
+            ui.text("输出目录(Output dir):");
+            ui.same_line();
+            ui.set_next_item_width(360.0f32);
+            ui.input_text("##texture_output_dir", &mut texture_browser.output_dir)
+                .build();
+            ui.same_line();
+            if ui.button("选择(Select)##texture_output_dir") {
+                let path = FileDialog::new()
+                    .set_location(&texture_browser.output_dir)
+                    .show_open_single_dir()
+                    .unwrap_or(None);
+                if let Some(path) = path {
+                    texture_browser.output_dir = path.to_string_lossy().into_owned();
+                }
+            }
+
+            ui.align_text_to_frame_padding();
+            ui.text("格式(Format):");
+            ui.same_line();
+            ui.set_next_item_width(100.0f32);
+            ui.combo_simple_string(
+                "##texture_export_format",
+                &mut texture_browser.export_format,
+                &export::textures::TEXTURE_EXPORT_FORMATS,
+            );
+            ui.same_line();
+            ui.checkbox("导出所有 mipmap(Export all mipmaps)", &mut texture_browser.export_mipmaps);
+
+            if ui.button("导出全部(Export All)") {
+                texture_browser.export(false);
+            }
+            ui.same_line();
+            if ui.button("导出选中(Export Selected)") {
+                texture_browser.export(true);
+            }
+            if !texture_browser.message.is_empty() {
+                ui.text_wrapped(&texture_browser.message);
+            }
+        });
+    });
+    texture_browser.opened = opened;
+}
+
+// 缩略图下显示的名称过长时截断
+fn truncate(name: &str, max_chars: usize) -> String {
+    if name.chars().count() <= max_chars {
+        return String::from(name);
+    }
+    let mut truncated: String = name.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+pub fn recording(
+    ui: &imgui::Ui,
+    recording: &mut super::Recording,
+    config_json: &mut ConfigJson,
+    mind_models: &mut [MindModel],
+) {
+    ui.tree_node_config("录制动画(Record Animation)")
+        .flags(imgui::TreeNodeFlags::SPAN_AVAIL_WIDTH)
+        .framed(true)
+        .build(|| {
+            if recording.is_active() {
+                let (frame, frame_count) = recording.progress();
+                imgui::ProgressBar::new(frame as f32 / frame_count.max(1) as f32)
+                    .overlay_text(format!("{frame} / {frame_count}"))
+                    .size([ui.content_region_avail()[0], 0.0f32])
+                    .build(ui);
+                if ui.button_with_size("停止录制(Stop Recording)", [ui.content_region_avail()[0], 0.0f32]) {
+                    recording.stop(mind_models, &mut config_json.options);
+                    recording.message = String::from("录制已取消(Recording cancelled)");
+                }
+                return;
+            }
+
+            if mind_models.is_empty() {
+                ui.text("没有模型(No models)");
+                return;
+            }
+
+            let names: Vec<&String> = config_json.paths.iter().map(|x| &x.name).collect();
+            ui.align_text_to_frame_padding();
+            ui.text("模型(Model):     ");
+            ui.same_line();
+            if ui.combo_simple_string("##recording_model", &mut recording.model, &names) {
+                recording.animation = mind_models[recording.model].animation_selected;
+            }
+            recording.model = recording.model.min(mind_models.len() - 1);
+
+            let mind_model = &mind_models[recording.model];
+            if mind_model.animations.is_empty() {
+                ui.text("模型没有动画(The model has no animations)");
+                return;
+            }
+            recording.animation = recording.animation.min(mind_model.animations.len() - 1);
+
+            ui.align_text_to_frame_padding();
+            ui.text("动画(Animation): ");
+            ui.same_line();
+            ui.combo_simple_string(
+                "##recording_animation",
+                &mut recording.animation,
+                &mind_model.animations_file_names,
+            );
+
+            let duration = mind_model.animations[recording.animation].duration;
+
+            ui.align_text_to_frame_padding();
+            ui.text("开始(Start):     ");
+            ui.same_line();
+            ui.slider_config("##recording_start", 0.0f32, duration)
+                .display_format("%.3f")
+                .flags(imgui::SliderFlags::ALWAYS_CLAMP)
+                .build(&mut recording.start_time);
+
+            ui.align_text_to_frame_padding();
+            ui.text("结束(End):       ");
+            ui.same_line();
+            ui.slider_config("##recording_end", 0.0f32, duration)
+                .display_format("%.3f")
+                .flags(imgui::SliderFlags::ALWAYS_CLAMP)
+                .build(&mut recording.end_time);
+            if ui.is_item_hovered() {
+                ui.tooltip(|| {
+                    ui.text("0 表示录制到动画结束(0 records until the end of the animation)");
+                });
+            }
+
+            ui.align_text_to_frame_padding();
+            ui.text("帧率(FPS):       ");
+            ui.same_line();
+            ui.slider_config("##recording_fps", 1u32, 60u32)
+                .flags(imgui::SliderFlags::ALWAYS_CLAMP)
+                .build(&mut recording.fps);
+
+            ui.align_text_to_frame_padding();
+            ui.text("分辨率(Resolution):");
+            ui.same_line();
+            ui.input_scalar_n("##recording_resolution", &mut recording.resolution)
+                .build();
+            recording.resolution[0] = recording.resolution[0].max(1);
+            recording.resolution[1] = recording.resolution[1].max(1);
+
+            ui.align_text_to_frame_padding();
+            ui.text("格式(Format):    ");
+            ui.same_line();
+            ui.combo_simple_string("##recording_format", &mut recording.format, &super::recording::FORMATS);
+
+            ui.align_text_to_frame_padding();
+            ui.text("文件名(File name): ");
+            ui.same_line();
+            ui.input_text("##recording_file_name", &mut recording.file_name)
+                .build();
+
+            if recording.is_sequence() {
+                ui.align_text_to_frame_padding();
+                ui.text("输出目录(Output dir):");
+                ui.same_line();
+                ui.input_text("##recording_output_dir", &mut recording.output_dir)
+                    .hint("桌面/文件名(Desktop/file name)")
+                    .build();
+                ui.same_line();
+                if ui.button("选择(Select)##recording_output_dir") {
+                    let path = FileDialog::new()
+                        .set_location(&recording.output_dir)
+                        .show_open_single_dir()
+                        .unwrap_or(None);
+                    if let Some(path) = path {
+                        recording.output_dir = path.to_string_lossy().into_owned();
+                    }
+                }
+            }
+
+            ui.checkbox("转台(Turntable)", &mut recording.turntable);
+            if recording.turntable {
+                ui.align_text_to_frame_padding();
+                ui.text("旋转角度(Degrees):");
+                ui.same_line();
+                ui.slider_config("##recording_turntable", -720.0f32, 720.0f32)
+                    .display_format("%.1f")
+                    .build(&mut recording.turntable_degrees);
+            }
+
+            ui.checkbox("透明背景(Transparent Background)", &mut recording.transparent);
+            if !recording.transparent {
+                ui.color_edit3("背景颜色(Background)##recording", &mut recording.background);
+            }
+
+            let record_label = if recording.is_sequence() {
+                "导出图片序列(Export Image Sequence)"
+            } else {
+                "录制到桌面(Record to Desktop)"
+            };
+            if ui.button_with_size(record_label, [ui.content_region_avail()[0], 0.0f32]) {
+                if let Err(error) = recording.start(mind_models, &mut config_json.options) {
+                    recording.message = format!("录制失败(Recording failed): {error}");
+                }
+            }
+            if !recording.message.is_empty() {
+                ui.text_wrapped(&recording.message);
+            }
+        });
+}
+
 const FORMATS: [&str; 4] = ["PNG", "JPG", "BMP", "TIFF"];
 
+pub enum SceneAction {
+    Save,
+    SaveAs(PathBuf),
+    Open(PathBuf),
+}
+
+// 当前场景文件的保存、另存为和打开，返回本帧要执行的操作。
+// scene_path 为 None 表示未命名的场景，保存时需要选择文件
+pub fn scene(ui: &imgui::Ui, scene_path: Option<&Path>) -> Option<SceneAction> {
+    let mut action = None;
+
+    match scene_path {
+        Some(scene_path) => ui.text(format!("场景(Scene): {}", scene_path.display())),
+        None => ui.text("场景(Scene): 未命名(Untitled)"),
+    }
+
+    let location = scene_path
+        .and_then(Path::parent)
+        .filter(|x| !x.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    let file_name = scene_path
+        .and_then(Path::file_name)
+        .and_then(|x| x.to_str())
+        .unwrap_or("scene.json");
+    let save_as = || {
+        let path = FileDialog::new()
+            .set_location(&location)
+            .set_filename(file_name)
+            .add_filter("场景(Scene)", &["json"])
+            .show_save_single_file()
+            .unwrap_or(None);
+        path.map(|mut path| {
+            if path.extension().is_none() {
+                path.set_extension("json");
+            }
+            SceneAction::SaveAs(path)
+        })
+    };
+
+    let button_width = (ui.content_region_avail()[0] - ui.clone_style().item_spacing[0] * 2.0f32) / 3.0f32;
+    if ui.button_with_size("保存(Save)", [button_width, 0.0f32]) {
+        action = match scene_path {
+            Some(_) => Some(SceneAction::Save),
+            None => save_as(),
+        };
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip(|| match scene_path {
+            Some(scene_path) => ui.text(format!("保存到(Save to) {}", scene_path.display())),
+            None => ui.text("选择保存的文件(Choose a file to save to)"),
+        });
+    }
+    ui.same_line();
+    if ui.button_with_size("另存为(Save As)", [button_width, 0.0f32]) {
+        action = save_as();
+    }
+    ui.same_line();
+    if ui.button_with_size("打开场景(Open Scene)", [button_width, 0.0f32]) {
+        let path = FileDialog::new()
+            .set_location(&location)
+            .add_filter("场景(Scene)", &["json"])
+            .show_open_single_file()
+            .unwrap_or(None);
+        if let Some(path) = path {
+            action = Some(SceneAction::Open(path));
+        }
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip(|| {
+            ui.text("未保存的修改将丢失(Unsaved changes will be lost)");
+        });
+    }
+
+    action
+}
+
+// 显示在窗口右下角的短暂提示
+pub struct Toast {
+    pub message: String,
+    pub error: bool,
+    pub expires: f32,
+}
+
+impl Toast {
+    const DURATION: f32 = 4.0f32;
+
+    pub fn info(message: String, current_time: f32) -> Toast {
+        Toast {
+            message,
+            error: false,
+            expires: current_time + Toast::DURATION,
+        }
+    }
+
+    // 错误提示显示更长时间
+    pub fn error(message: String, current_time: f32) -> Toast {
+        Toast {
+            message,
+            error: true,
+            expires: current_time + Toast::DURATION * 2.0f32,
+        }
+    }
+}
+
+pub fn toasts(ui: &imgui::Ui, toasts: &mut Vec<Toast>, current_time: f32) {
+    toasts.retain(|x| x.expires > current_time);
+    if toasts.is_empty() {
+        return;
+    }
+
+    let display_size = ui.io().display_size;
+    ui.window("##toasts")
+        .position(
+            [display_size[0] - 10.0f32, display_size[1] - 10.0f32],
+            imgui::Condition::Always,
+        )
+        .position_pivot([1.0f32, 1.0f32])
+        .bg_alpha(0.8f32)
+        .no_decoration()
+        .always_auto_resize(true)
+        .no_inputs()
+        .focus_on_appearing(false)
+        .build(|| {
+            for toast in toasts.iter() {
+                if toast.error {
+                    ui.text_colored([1.0f32, 0.4f32, 0.4f32, 1.0f32], &toast.message);
+                } else {
+                    ui.text(&toast.message);
+                }
+            }
+        });
+}
+
+// 读取配置文件时的问题，启动后弹出一次，关闭后清空
+pub fn config_errors(ui: &imgui::Ui, config_errors: &mut Vec<String>) {
+    if config_errors.is_empty() {
+        return;
+    }
+
+    const POPUP_ID: &str = "配置文件问题(Config File Problems)";
+    if !ui.is_popup_open(POPUP_ID) {
+        ui.open_popup(POPUP_ID);
+    }
+
+    ui.modal_popup_config(POPUP_ID)
+        .always_auto_resize(true)
+        .build(|| {
+            {
+                let _wrap = ui.push_text_wrap_pos_with_pos(ui.cursor_pos()[0] + 600.0f32);
+                let _text_style = ui.push_style_color(StyleColor::Text, [1.0f32, 0.6f32, 0.4f32, 1.0f32]);
+                for error in config_errors.iter() {
+                    ui.text_wrapped(error);
+                }
+            }
+            ui.separator();
+            ui.text("保存配置前请检查场景(Check the scene before saving the configuration)");
+            if ui.button_with_size("确定(OK)", [ui.content_region_avail()[0], 0.0f32]) {
+                ui.close_current_popup();
+                config_errors.clear();
+            }
+        });
+}
+
 pub fn confirm_delete_button(ui: &imgui::Ui) -> bool {
     let delete_button = ui.button("\u{F014}");
     if ui.is_item_hovered() {
@@ -1,11 +1,15 @@
 pub mod glam_read;
 pub mod imgui_glfw;
 pub mod imgui_layout;
+pub mod recording;
 pub mod screenshot;
 pub mod shader;
 pub mod texture;
+pub mod texture_browser;
 
 pub use self::imgui_glfw::ImguiGLFW;
+pub use self::recording::Recording;
 pub use self::screenshot::Screenshot;
 pub use self::shader::Shader;
 pub use self::texture::Texture;
+pub use self::texture_browser::TextureBrowser;
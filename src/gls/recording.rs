@@ -0,0 +1,336 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use gl::types::GLsizei;
+use image::codecs::gif::{GifEncoder, Repeat};
+
+use crate::{config_json::OptionsJson, MindModel};
+
+pub const FORMATS: [&str; 4] = ["GIF", "APNG", "PNG序列(PNG Sequence)", "EXR序列(EXR Sequence)"];
+const EXTENSIONS: [&str; 4] = ["gif", "png", "png", "exr"];
+
+// 按固定帧率逐帧录制动画，每帧的时间由帧序号决定，与实际的 delta_time 无关。
+// GIF/APNG 在录制结束后一次编码，图片序列每帧直接写入输出目录
+pub struct Recording {
+    pub model: usize,
+    pub animation: usize,
+    pub start_time: f32,
+    // 0 表示录制到动画结束
+    pub end_time: f32,
+    pub fps: u32,
+    pub resolution: [GLsizei; 2],
+    pub format: usize,
+    pub file_name: String,
+    pub transparent: bool,
+    pub background: [f32; 3],
+    // 图片序列的输出目录，为空时使用桌面上以文件名命名的目录
+    pub output_dir: String,
+    // 录制期间相机绕 Y 轴旋转的总角度
+    pub turntable: bool,
+    pub turntable_degrees: f32,
+    pub message: String,
+
+    active: bool,
+    frame: usize,
+    frame_count: usize,
+    frames: Vec<Vec<u8>>,
+    // 录制前模型的动画设置，录制结束后恢复
+    saved_state: Option<(bool, bool, f32, usize)>,
+}
+
+impl Recording {
+    // 所有帧在录制结束前都保存在内存中，默认使用较小的分辨率
+    pub fn new() -> Recording {
+        Recording {
+            model: 0,
+            animation: 0,
+            start_time: 0.0f32,
+            end_time: 0.0f32,
+            fps: 30,
+            resolution: [512, 512],
+            format: 0,
+            file_name: String::from("recording"),
+            transparent: true,
+            background: [0.5f32, 0.5f32, 0.5f32],
+            output_dir: String::new(),
+            turntable: false,
+            turntable_degrees: 360.0f32,
+            message: String::new(),
+
+            active: false,
+            frame: 0,
+            frame_count: 0,
+            frames: vec![],
+            saved_state: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    // 返回(已录制帧数, 总帧数)
+    pub fn progress(&self) -> (usize, usize) {
+        (self.frame, self.frame_count)
+    }
+
+    pub fn frame_delta_time(&self) -> f32 {
+        1.0f32 / self.fps.max(1) as f32
+    }
+
+    pub fn is_sequence(&self) -> bool {
+        self.format >= 2
+    }
+
+    // 转台模式下场景绕世界 Y 轴的旋转，最后一帧不会回到起始角度，循环播放时不重复
+    pub fn turntable_matrix(&self) -> glam::Mat4 {
+        if !self.active || !self.turntable {
+            return glam::Mat4::IDENTITY;
+        }
+        let angle = self.turntable_degrees * self.frame as f32 / self.frame_count.max(1) as f32;
+        glam::Mat4::from_rotation_y(angle.to_radians())
+    }
+
+    pub fn start(&mut self, mind_models: &[MindModel], options: &mut [OptionsJson]) -> Result<(), String> {
+        let mind_model = mind_models
+            .get(self.model)
+            .ok_or_else(|| String::from("No model selected"))?;
+        let animation = mind_model
+            .animations
+            .get(self.animation)
+            .ok_or_else(|| String::from("The model has no animation to record"))?;
+
+        let end_time = if self.end_time > 0.0f32 {
+            self.end_time.min(animation.duration)
+        } else {
+            animation.duration
+        };
+        let start_time = self.start_time.clamp(0.0f32, end_time);
+        let fps = self.fps.max(1);
+
+        self.start_time = start_time;
+        self.fps = fps;
+        self.frame = 0;
+        self.frame_count = (((end_time - start_time) * fps as f32).ceil() as usize).max(1);
+        self.frames = if self.is_sequence() {
+            self.create_sequence_dir()?;
+            vec![]
+        } else {
+            Vec::with_capacity(self.frame_count)
+        };
+
+        let options = &mut options[self.model];
+        self.saved_state = Some((
+            options.use_animation,
+            options.play_animation,
+            options.animation_time,
+            mind_model.animation_selected,
+        ));
+        self.active = true;
+        self.message = String::new();
+        Ok(())
+    }
+
+    // 删除模型之前调用，正在录制的模型被删除时取消录制，后面的模型索引前移
+    pub fn remove_model(&mut self, model: usize, mind_models: &mut [MindModel], options: &mut [OptionsJson]) {
+        if self.model == model {
+            if self.active {
+                self.saved_state = None;
+                self.stop(mind_models, options);
+                self.message = String::from("录制已取消(Recording cancelled)");
+            }
+        } else if self.model > model {
+            self.model -= 1;
+        }
+    }
+
+    // 渲染当前帧之前调用，把录制的模型设置到这一帧的时间，模型被删除时取消录制
+    pub fn prepare_frame(&mut self, mind_models: &mut [MindModel], options: &mut [OptionsJson]) {
+        if self.model >= mind_models.len() || self.model >= options.len() {
+            self.saved_state = None;
+            self.stop(mind_models, options);
+            self.message = String::from("录制已取消(Recording cancelled)");
+            return;
+        }
+
+        let options = &mut options[self.model];
+        options.use_animation = true;
+        options.play_animation = false;
+        options.animation_time = self.start_time + self.frame as f32 / self.fps as f32;
+        mind_models[self.model].animation_selected = self.animation;
+    }
+
+    // 返回 Some 表示录制结束
+    pub fn push_frame(
+        &mut self,
+        pixels: Vec<u8>,
+        mind_models: &mut [MindModel],
+        options: &mut [OptionsJson],
+    ) -> Option<Result<PathBuf, String>> {
+        if self.is_sequence() {
+            if let Err(error) = self.save_sequence_frame(pixels) {
+                self.stop(mind_models, options);
+                self.message = format!("录制失败(Recording failed): {error}");
+                return Some(Err(error));
+            }
+        } else {
+            self.frames.push(pixels);
+        }
+        self.frame += 1;
+        if self.frame < self.frame_count {
+            return None;
+        }
+
+        let result = if self.is_sequence() {
+            Ok(self.sequence_dir())
+        } else {
+            self.save()
+        };
+        self.stop(mind_models, options);
+        self.message = match &result {
+            Ok(path) => format!("录制完成(Recording saved): {}", path.display()),
+            Err(error) => format!("录制失败(Recording failed): {error}"),
+        };
+        Some(result)
+    }
+
+    pub fn stop(&mut self, mind_models: &mut [MindModel], options: &mut [OptionsJson]) {
+        if let Some((use_animation, play_animation, animation_time, animation_selected)) =
+            self.saved_state.take()
+        {
+            if let (Some(options), Some(mind_model)) =
+                (options.get_mut(self.model), mind_models.get_mut(self.model))
+            {
+                options.use_animation = use_animation;
+                options.play_animation = play_animation;
+                options.animation_time = animation_time;
+                mind_model.animation_selected = animation_selected;
+            }
+        }
+        self.active = false;
+        self.frames = vec![];
+    }
+
+    fn sequence_dir(&self) -> PathBuf {
+        if !self.output_dir.is_empty() {
+            return PathBuf::from(&self.output_dir);
+        }
+        dirs::desktop_dir()
+            .unwrap_or_default()
+            .join(if self.file_name.is_empty() { "recording" } else { &self.file_name })
+    }
+
+    fn create_sequence_dir(&mut self) -> Result<(), String> {
+        if self.file_name.is_empty() {
+            self.file_name = String::from("recording");
+        }
+        let sequence_dir = self.sequence_dir();
+        std::fs::create_dir_all(&sequence_dir)
+            .map_err(|error| format!("Could not create {}: {error}", sequence_dir.display()))?;
+        println!("文件将保存到: {:?}", sequence_dir);
+        Ok(())
+    }
+
+    // 帧序号从 1 开始，至少 4 位，例如 name_0001.png
+    fn save_sequence_frame(&self, pixels: Vec<u8>) -> Result<(), String> {
+        let digits = self.frame_count.to_string().len().max(4);
+        let path = self.sequence_dir().join(format!(
+            "{}_{:0digits$}.{}",
+            self.file_name,
+            self.frame + 1,
+            EXTENSIONS[self.format]
+        ));
+        let pixels = if self.transparent {
+            pixels
+        } else {
+            fill_background(pixels, self.background)
+        };
+        super::screenshot::save_image(&path, pixels, self.resolution)
+    }
+
+    fn save(&mut self) -> Result<PathBuf, String> {
+        if self.file_name.is_empty() {
+            self.file_name = String::from("recording");
+        }
+        let desktop_path = dirs::desktop_dir().ok_or_else(|| String::from("无法获取桌面路径！"))?;
+        let path = desktop_path.join(format!("{}.{}", self.file_name, EXTENSIONS[self.format]));
+        println!("文件将保存到: {:?}", path);
+
+        let frames = std::mem::take(&mut self.frames);
+        let frames = if self.transparent {
+            frames
+        } else {
+            frames
+                .into_iter()
+                .map(|frame| fill_background(frame, self.background))
+                .collect()
+        };
+
+        let width = self.resolution[0] as u32;
+        let height = self.resolution[1] as u32;
+        match self.format {
+            0 => save_gif(&path, frames, width, height, self.fps),
+            _ => save_apng(&path, frames, width, height, self.fps),
+        }?;
+        Ok(path)
+    }
+}
+
+// 截图帧缓冲清除为透明，不透明背景时按 alpha 混合到背景色上
+fn fill_background(mut frame: Vec<u8>, background: [f32; 3]) -> Vec<u8> {
+    for pixel in frame.chunks_exact_mut(4) {
+        let alpha = pixel[3] as f32 / 255.0f32;
+        for channel in 0..3 {
+            let color = pixel[channel] as f32 * alpha + background[channel] * 255.0f32 * (1.0f32 - alpha);
+            pixel[channel] = color.round().clamp(0.0f32, 255.0f32) as u8;
+        }
+        pixel[3] = 255;
+    }
+    frame
+}
+
+pub fn save_gif(path: &Path, frames: Vec<Vec<u8>>, width: u32, height: u32, fps: u32) -> Result<(), String> {
+    let file = File::create(path).map_err(|error| format!("Could not create {}: {error}", path.display()))?;
+    let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|error| format!("Could not write gif: {error}"))?;
+
+    let delay = image::Delay::from_numer_denom_ms(1000, fps.max(1));
+    for frame in frames {
+        let buffer = image::RgbaImage::from_raw(width, height, frame)
+            .ok_or_else(|| String::from("Invalid frame size"))?;
+        encoder
+            .encode_frame(image::Frame::from_parts(buffer, 0, 0, delay))
+            .map_err(|error| format!("Could not write gif: {error}"))?;
+    }
+    Ok(())
+}
+
+pub fn save_apng(path: &Path, frames: Vec<Vec<u8>>, width: u32, height: u32, fps: u32) -> Result<(), String> {
+    let file = File::create(path).map_err(|error| format!("Could not create {}: {error}", path.display()))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(frames.len() as u32, 0)
+        .map_err(|error| format!("Could not write apng: {error}"))?;
+    encoder
+        .set_frame_delay(1, fps.max(1) as u16)
+        .map_err(|error| format!("Could not write apng: {error}"))?;
+
+    let mut writer = encoder
+        .write_header()
+        .map_err(|error| format!("Could not write apng: {error}"))?;
+    for frame in frames.iter() {
+        writer
+            .write_image_data(frame)
+            .map_err(|error| format!("Could not write apng: {error}"))?;
+    }
+    writer
+        .finish()
+        .map_err(|error| format!("Could not write apng: {error}"))
+}
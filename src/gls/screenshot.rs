@@ -13,6 +13,8 @@ pub struct Screenshot {
     pub file_name: String,
     pub resolution: [GLsizei; 2],
     pub use_samples: bool,
+    // 命令行 --screenshot 指定的路径，只用于下一次截图，格式由扩展名决定
+    pub output_path: Option<PathBuf>,
 }
 
 impl Screenshot {
@@ -26,6 +28,7 @@ impl Screenshot {
             file_name: String::from("screenshot"),
             resolution,
             use_samples,
+            output_path: None,
         }
     }
 
@@ -131,7 +134,9 @@ impl Screenshot {
         ) * glam::Mat4::from_scale(glam::vec3(1.0f32, -1.0f32, 1.0f32))
     }
 
-    pub fn save(&mut self, window_size: [GLint; 2]) {
+    // 读回截图帧缓冲的 RGBA 像素(从上到下)，并释放本次截图创建的帧缓冲
+    pub fn read_pixels(&self, window_size: [GLint; 2]) -> Vec<u8> {
+        let mut buffer = vec![0u8; (self.resolution[0] * self.resolution[1] * 4) as usize];
         unsafe {
             if self.use_samples {
                 gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo[1]);
@@ -151,7 +156,6 @@ impl Screenshot {
                 gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo[0]);
             }
 
-            let mut buffer = vec![0u8; (self.resolution[0] * self.resolution[1] * 4) as usize];
             gl::ReadPixels(
                 0,
                 0,
@@ -169,41 +173,86 @@ impl Screenshot {
             gl::DeleteFramebuffers(2, self.fbo.as_ptr());
             gl::DeleteRenderbuffers(3, self.rbo.as_ptr());
             gl::DeleteTextures(1, &self.texture_id);
+        }
+        buffer
+    }
 
-            if self.file_name.is_empty() {
-                self.file_name = String::from("screenshot");
-            }
-        
-            let file_name = format!(
-                "{}.{}",
-                self.file_name,
-                FORMATS[self.format].extensions_str()[0]
-            );
-            let mut full_path: Option<PathBuf> = None; // 定义一个 Option 类型变量
-            if let Some(desktop_path) = dirs::desktop_dir() {
-                full_path = Some(desktop_path.join(file_name)); // 将路径赋值给 full_path
+    // 只有命令行指定的路径会返回保存错误
+    pub fn save(&mut self, window_size: [GLint; 2]) -> Result<(), String> {
+        let buffer = self.read_pixels(window_size);
 
-                if let Some(ref path) = full_path {
-                    println!("文件将保存到: {:?}", path);
-                }
-            }
+        if let Some(path) = self.output_path.take() {
+            save_image(&path, buffer, self.resolution)?;
+            println!("文件将保存到: {:?}", path);
+            return Ok(());
+        }
 
-            // 确保 full_path 已被赋值
-            if let Some(path) = full_path {
-                image::save_buffer_with_format(
-                    Path::new(&path), // 使用 full_path
-                    &buffer,
-                    self.resolution[0] as u32,
-                    self.resolution[1] as u32,
-                    image::ColorType::Rgba8,
-                    FORMATS[self.format],
-                )
-                    .expect("无法保存文件");
-            } else {
-                eprintln!("无法获取桌面路径！");
+        if self.file_name.is_empty() {
+            self.file_name = String::from("screenshot");
+        }
+    
+        let file_name = format!(
+            "{}.{}",
+            self.file_name,
+            FORMATS[self.format].extensions_str()[0]
+        );
+        let mut full_path: Option<PathBuf> = None; // 定义一个 Option 类型变量
+        if let Some(desktop_path) = dirs::desktop_dir() {
+            full_path = Some(desktop_path.join(file_name)); // 将路径赋值给 full_path
+
+            if let Some(ref path) = full_path {
+                println!("文件将保存到: {:?}", path);
             }
+        }
 
+        // 确保 full_path 已被赋值
+        if let Some(path) = full_path {
+            image::save_buffer_with_format(
+                Path::new(&path), // 使用 full_path
+                &buffer,
+                self.resolution[0] as u32,
+                self.resolution[1] as u32,
+                image::ColorType::Rgba8,
+                FORMATS[self.format],
+            )
+                .expect("无法保存文件");
+        } else {
+            eprintln!("无法获取桌面路径！");
         }
+        Ok(())
+    }
+}
+
+// 格式由扩展名决定。EXR 按线性、预乘 alpha 读取，sRGB 颜色先转换为线性再乘以 alpha
+pub fn save_image(path: &Path, pixels: Vec<u8>, resolution: [GLsizei; 2]) -> Result<(), String> {
+    let buffer = image::RgbaImage::from_raw(resolution[0] as u32, resolution[1] as u32, pixels)
+        .ok_or_else(|| String::from("Invalid image size"))?;
+    let is_exr = path
+        .extension()
+        .and_then(|x| x.to_str())
+        .is_some_and(|x| x.eq_ignore_ascii_case("exr"));
+
+    let result = if is_exr {
+        let mut linear = image::Rgba32FImage::new(buffer.width(), buffer.height());
+        for (source, target) in buffer.pixels().zip(linear.pixels_mut()) {
+            let alpha = source[3] as f32 / 255.0f32;
+            for channel in 0..3 {
+                target[channel] = srgb_to_linear(source[channel] as f32 / 255.0f32) * alpha;
+            }
+            target[3] = alpha;
+        }
+        linear.save(path)
+    } else {
+        buffer.save(path)
+    };
+    result.map_err(|error| format!("Could not save image {}: {error}", path.display()))
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045f32 {
+        value / 12.92f32
+    } else {
+        ((value + 0.055f32) / 1.055f32).powf(2.4f32)
     }
 }
 
@@ -242,6 +242,44 @@ impl Texture {
         }
     }
 
+    // 只有一级的 RGBA 纹理，用于纹理浏览器单独显示某一级 mipmap
+    pub fn from_rgba(width: usize, height: usize, pixels: &[u8]) -> Texture {
+        unsafe {
+            let mut texture_id: GLuint = 0;
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_2D, texture_id);
+
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_BASE_LEVEL, 0);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 0);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+            // 放大查看时保留像素边界
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
+
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA8 as GLint,
+                width as GLint,
+                height as GLint,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr() as *const c_void,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+
+            Texture {
+                id: texture_id,
+                gltype: gl::TEXTURE_2D,
+            }
+        }
+    }
+
     pub unsafe fn delete(self) {
         gl::DeleteTextures(1, &self.id);
     }
@@ -260,8 +298,8 @@ impl Drop for Texture {
     }
 }
 
-#[derive(Debug)]
-enum Format {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
     BC1DXT1,
     BC2DXT3,
     BC3DXT5,
@@ -271,7 +309,7 @@ enum Format {
 }
 
 impl Format {
-    fn bytes_per_block(&self) -> usize {
+    pub fn bytes_per_block(&self) -> usize {
         match self {
             Format::BC1DXT1 => 8,
             Format::BC2DXT3 => 16,
@@ -282,6 +320,14 @@ impl Format {
         }
     }
 
+    // 一级 mipmap 的字节数
+    pub fn level_size(&self, width: usize, height: usize) -> usize {
+        match self {
+            Format::RGBA8 => 1.max(width) * 1.max(height) * self.bytes_per_block(),
+            _ => 1.max((width + 3) / 4) * 1.max((height + 3) / 4) * self.bytes_per_block(),
+        }
+    }
+
     fn decode_function(&self) -> fn(&[u8], usize, usize, &mut [u32]) -> Result<(), &'static str> {
         match self {
             Format::BC1DXT1 => texture2ddecoder::decode_bc1,
@@ -432,10 +478,14 @@ fn load_tex(reader: &mut Cursor<&[u8]>) -> (Vec<u8>, Format, i32, i32, usize) {
     let mut image_data_reversed = vec![];
 
     for _ in 0..mipmap_count {
-        let image_width = 1.max((current_width + 3) / 4);
-        let image_height = 1.max((current_height + 3) / 4);
-
-        let block_size = image_width * image_height * bytes_per_block;
+        let block_size = match format {
+            Format::RGBA8 => current_width * current_height * bytes_per_block,
+            _ => {
+                let image_width = 1.max((current_width + 3) / 4);
+                let image_height = 1.max((current_height + 3) / 4);
+                image_width * image_height * bytes_per_block
+            }
+        };
         let image = &image_data[offset - block_size..offset];
 
         image_data_reversed.extend_from_slice(image);
@@ -445,6 +495,13 @@ fn load_tex(reader: &mut Cursor<&[u8]>) -> (Vec<u8>, Format, i32, i32, usize) {
         current_height /= 2;
     }
 
+    // 格式 20 按 BGRA8 存储，转换为 RGBA8
+    if let Format::RGBA8 = format {
+        for pixel in image_data_reversed.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
     (image_data_reversed, format, height, width, mipmap_count)
 }
 
@@ -472,3 +529,93 @@ pub fn load_source(reader: &mut Cursor<&[u8]>) -> (Vec<Vec<u8>>, i32, i32) {
 
     (images, width, height)
 }
+
+// 未解码的纹理数据，用于原样导出压缩纹理，mipmaps 从最大一级开始
+pub struct CompressedSource {
+    pub format: Format,
+    pub width: usize,
+    pub height: usize,
+    pub mipmaps: Vec<Vec<u8>>,
+}
+
+pub fn load_compressed_source(reader: &mut Cursor<&[u8]>) -> CompressedSource {
+    let mut signature = vec![0u8; 4];
+    reader
+        .read_exact(&mut signature)
+        .expect("Could not read texture signature");
+
+    let (image_data, format, height, width, mipmap_count) = match signature.as_slice() {
+        b"DDS " => load_dds(reader),
+        b"TEX\0" => load_tex(reader),
+        _ => panic!("Unknown texture signature"),
+    };
+
+    let mut mipmaps = Vec::with_capacity(mipmap_count);
+    let mut offset = 0;
+    let mut current_width = width as usize;
+    let mut current_height = height as usize;
+    for _ in 0..mipmap_count {
+        let level_size = format.level_size(current_width, current_height);
+        if offset + level_size > image_data.len() {
+            break;
+        }
+        mipmaps.push(image_data[offset..offset + level_size].to_vec());
+
+        offset += level_size;
+        current_width /= 2;
+        current_height /= 2;
+    }
+
+    CompressedSource {
+        format,
+        width: width as usize,
+        height: height as usize,
+        mipmaps,
+    }
+}
+
+// 解码后的一级 mipmap，RGBA 从上到下
+pub struct DecodedMipmap {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+// 按 load_compressed_source 读取的宽高逐级解码所有 mipmap
+pub fn decode_mipmaps(source: &CompressedSource) -> Result<Vec<DecodedMipmap>, String> {
+    let mut mipmaps = Vec::with_capacity(source.mipmaps.len());
+    let mut width = source.width;
+    let mut height = source.height;
+
+    for mipmap in source.mipmaps.iter() {
+        let level_width = 1.max(width);
+        let level_height = 1.max(height);
+
+        let pixels = match source.format {
+            Format::RGBA8 => mipmap.clone(),
+            _ => {
+                let mut image_out = vec![0u32; level_width * level_height];
+                source.format.decode_function()(mipmap, level_width, level_height, &mut image_out)
+                    .map_err(|error| format!("Could not decode {:?} image data: {error}", source.format))?;
+                image_out
+                    .iter()
+                    .flat_map(|x| {
+                        let v = x.to_le_bytes();
+                        [v[2], v[1], v[0], v[3]]
+                    })
+                    .collect()
+            }
+        };
+
+        mipmaps.push(DecodedMipmap {
+            width: level_width,
+            height: level_height,
+            pixels,
+        });
+
+        width /= 2;
+        height /= 2;
+    }
+
+    Ok(mipmaps)
+}
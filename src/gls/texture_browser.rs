@@ -0,0 +1,196 @@
+use std::{io::Cursor, path::Path};
+
+use super::{texture, Texture};
+use crate::{export, MindModel};
+
+// 纹理浏览器中一张纹理的信息，只读取文件头和压缩数据，不解码
+pub struct TextureEntry {
+    pub path: String,
+    pub name: String,
+    pub file_size: u64,
+    pub format: Option<texture::Format>,
+    pub width: usize,
+    pub height: usize,
+    pub mipmap_count: usize,
+    pub error: Option<String>,
+    pub checked: bool,
+}
+
+// 选中纹理的每一级 mipmap，单独上传为 GL 纹理
+pub struct MipmapView {
+    pub width: usize,
+    pub height: usize,
+    pub texture: Texture,
+}
+
+pub struct TextureBrowser {
+    pub opened: bool,
+    pub model: usize,
+    pub entries: Vec<TextureEntry>,
+    pub selected: usize,
+    pub mipmap: usize,
+    pub zoom: f32,
+    pub mipmap_views: Vec<MipmapView>,
+
+    pub output_dir: String,
+    // export::textures::TEXTURE_EXPORT_FORMATS 的索引
+    pub export_format: usize,
+    pub export_mipmaps: bool,
+    pub message: String,
+
+    // 已加载的纹理路径，模型被删除或纹理改变时重新读取
+    loaded_paths: Vec<String>,
+    loaded_selected: Option<usize>,
+}
+
+impl TextureBrowser {
+    pub fn new() -> TextureBrowser {
+        let output_dir = std::env::current_dir()
+            .map(|dir| dir.join("export").join("textures"))
+            .unwrap_or_else(|_| Path::new("export").join("textures"));
+
+        TextureBrowser {
+            opened: false,
+            model: 0,
+            entries: vec![],
+            selected: 0,
+            mipmap: 0,
+            zoom: 1.0f32,
+            mipmap_views: vec![],
+
+            output_dir: output_dir.to_string_lossy().into_owned(),
+            export_format: 0,
+            export_mipmaps: false,
+            message: String::new(),
+
+            loaded_paths: vec![],
+            loaded_selected: None,
+        }
+    }
+
+    pub fn open(&mut self, model: usize) {
+        self.opened = true;
+        if self.model != model {
+            self.model = model;
+            self.loaded_paths.clear();
+        }
+    }
+
+    // 删除模型时调用，浏览的模型被删除时关闭，后面的模型索引前移
+    pub fn remove_model(&mut self, model: usize) {
+        if self.model == model {
+            self.opened = false;
+            self.loaded_paths.clear();
+        } else if self.model > model {
+            self.model -= 1;
+        }
+    }
+
+    // 模型的纹理文件在磁盘上改变后重新读取
+    pub fn reload(&mut self, model: usize) {
+        if self.model == model {
+            self.loaded_paths.clear();
+        }
+    }
+
+    // 每帧调用，纹理列表改变时重新读取信息，选中的纹理改变时重新解码
+    pub fn update(&mut self, mind_model: &MindModel) {
+        if self.loaded_paths != mind_model.textures_paths {
+            self.entries = mind_model
+                .textures_paths
+                .iter()
+                .zip(mind_model.textures_file_names.iter())
+                .map(|(path, name)| read_entry(path, name))
+                .collect();
+            self.loaded_paths = mind_model.textures_paths.clone();
+            self.loaded_selected = None;
+            self.selected = 0;
+            self.message = String::new();
+        }
+
+        if self.entries.is_empty() {
+            self.mipmap_views.clear();
+            return;
+        }
+        self.selected = self.selected.min(self.entries.len() - 1);
+
+        if self.loaded_selected != Some(self.selected) {
+            self.loaded_selected = Some(self.selected);
+            self.mipmap = 0;
+            self.mipmap_views = match decode_entry(&self.entries[self.selected]) {
+                Ok(mipmap_views) => mipmap_views,
+                Err(error) => {
+                    self.entries[self.selected].error = Some(error);
+                    vec![]
+                }
+            };
+        }
+        self.mipmap = self.mipmap.min(self.mipmap_views.len().saturating_sub(1));
+    }
+
+    pub fn export(&mut self, only_checked: bool) {
+        let texture_paths: Vec<&str> = self
+            .entries
+            .iter()
+            .filter(|x| !only_checked || x.checked)
+            .map(|x| x.path.as_str())
+            .collect();
+
+        if texture_paths.is_empty() {
+            self.message = String::from("没有选中的纹理(No textures selected)");
+            return;
+        }
+
+        self.message = match export::textures::export_textures(
+            &texture_paths,
+            Path::new(&self.output_dir),
+            self.export_format,
+            self.export_mipmaps,
+        ) {
+            Ok((path, count)) => format!("导出完成(Export completed): {count} 个文件(files) {}", path.display()),
+            Err(error) => format!("导出失败(Export failed): {error}"),
+        };
+    }
+}
+
+fn read_entry(path: &str, name: &str) -> TextureEntry {
+    let mut entry = TextureEntry {
+        path: path.to_owned(),
+        name: name.to_owned(),
+        file_size: std::fs::metadata(path).map(|x| x.len()).unwrap_or(0),
+        format: None,
+        width: 0,
+        height: 0,
+        mipmap_count: 0,
+        error: None,
+        checked: false,
+    };
+
+    match crate::parse_file(Path::new(path), |contents| {
+        texture::load_compressed_source(&mut Cursor::new(contents.as_slice()))
+    }) {
+        Ok(source) => {
+            entry.format = Some(source.format);
+            entry.width = source.width;
+            entry.height = source.height;
+            entry.mipmap_count = source.mipmaps.len();
+        }
+        Err(error) => entry.error = Some(error),
+    }
+    entry
+}
+
+fn decode_entry(entry: &TextureEntry) -> Result<Vec<MipmapView>, String> {
+    let source = crate::parse_file(Path::new(&entry.path), |contents| {
+        texture::load_compressed_source(&mut Cursor::new(contents.as_slice()))
+    })?;
+
+    Ok(texture::decode_mipmaps(&source)?
+        .into_iter()
+        .map(|mipmap| MipmapView {
+            width: mipmap.width,
+            height: mipmap.height,
+            texture: Texture::from_rgba(mipmap.width, mipmap.height, &mipmap.pixels),
+        })
+        .collect())
+}
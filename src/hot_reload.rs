@@ -0,0 +1,151 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::config_json::PathJson;
+
+// 两次检查之间的间隔(秒)
+const POLL_INTERVAL: f32 = 1.0f32;
+
+// 文件的修改时间和大小，任意一个变化都认为文件已改变
+type FileStamp = (Option<SystemTime>, u64);
+
+// 一个模型所监视的所有文件：skn、skl、纹理目录下的 dds/tex、动画目录下的 anm 以及拖放的文件
+#[derive(Clone, PartialEq, Default)]
+struct Snapshot {
+    files: BTreeMap<PathBuf, FileStamp>,
+}
+
+struct WatchedModel {
+    paths: PathJson,
+    // 当前已加载的文件状态
+    loaded: Snapshot,
+    // 上一次检查到的、还未加载的状态，连续两次相同才重新加载，避免读到写了一半的文件
+    pending: Option<Snapshot>,
+}
+
+// 轮询文件的修改时间，不依赖平台的文件通知
+pub struct AssetWatcher {
+    pub enabled: bool,
+    models: Vec<WatchedModel>,
+    last_poll: f32,
+}
+
+impl AssetWatcher {
+    pub fn new() -> AssetWatcher {
+        AssetWatcher {
+            enabled: true,
+            models: vec![],
+            last_poll: 0.0f32,
+        }
+    }
+
+    // 与 config_json.paths 保持一致，新增或路径改变的模型以当前文件状态作为已加载状态
+    pub fn sync(&mut self, paths: &[PathJson]) {
+        self.models.truncate(paths.len());
+        for (i, path) in paths.iter().enumerate() {
+            let changed = self.models.get(i).is_none_or(|x| !same_paths(&x.paths, path));
+            if !changed {
+                continue;
+            }
+            let watched_model = WatchedModel {
+                paths: path.clone(),
+                loaded: snapshot(path),
+                pending: None,
+            };
+            if i < self.models.len() {
+                self.models[i] = watched_model;
+            } else {
+                self.models.push(watched_model);
+            }
+        }
+    }
+
+    // 返回需要重新加载的模型索引
+    pub fn poll(&mut self, current_time: f32) -> Vec<usize> {
+        if !self.enabled || current_time - self.last_poll < POLL_INTERVAL {
+            return vec![];
+        }
+        self.last_poll = current_time;
+
+        let mut changed = vec![];
+        for (i, watched_model) in self.models.iter_mut().enumerate() {
+            let current = snapshot(&watched_model.paths);
+            if current == watched_model.loaded {
+                watched_model.pending = None;
+            } else if watched_model.pending.as_ref() == Some(&current) {
+                changed.push(i);
+            } else {
+                watched_model.pending = Some(current);
+            }
+        }
+        changed
+    }
+
+    // 重新加载完成后调用，失败时同样调用，文件再次改变时才重试
+    pub fn mark_loaded(&mut self, index: usize) {
+        if let Some(watched_model) = self.models.get_mut(index) {
+            watched_model.loaded = watched_model
+                .pending
+                .take()
+                .unwrap_or_else(|| snapshot(&watched_model.paths));
+        }
+    }
+}
+
+fn same_paths(a: &PathJson, b: &PathJson) -> bool {
+    a.skin == b.skin
+        && a.skeleton == b.skeleton
+        && a.textures == b.textures
+        && a.animations == b.animations
+        && a.extra_textures == b.extra_textures
+        && a.extra_animations == b.extra_animations
+}
+
+fn snapshot(paths: &PathJson) -> Snapshot {
+    let mut files = BTreeMap::new();
+
+    let files_paths = [&paths.skin, &paths.skeleton]
+        .into_iter()
+        .chain(paths.extra_textures.iter())
+        .chain(paths.extra_animations.iter());
+    for path in files_paths {
+        if !path.is_empty() {
+            files.insert(PathBuf::from(path), stamp(Path::new(path)));
+        }
+    }
+
+    for (dir, extensions) in [
+        (&paths.textures, &["dds", "tex"][..]),
+        (&paths.animations, &["anm"][..]),
+    ] {
+        if dir.is_empty() {
+            continue;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let matches = path
+                .extension()
+                .and_then(|x| x.to_str())
+                .is_some_and(|x| extensions.iter().any(|e| x.eq_ignore_ascii_case(e)));
+            if matches {
+                let file_stamp = stamp(&path);
+                files.insert(path, file_stamp);
+            }
+        }
+    }
+
+    Snapshot { files }
+}
+
+fn stamp(path: &Path) -> FileStamp {
+    match std::fs::metadata(path) {
+        Ok(metadata) => (metadata.modified().ok(), metadata.len()),
+        Err(_) => (None, 0),
+    }
+}
@@ -0,0 +1,787 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use gltf::animation::util::ReadOutputs;
+
+use crate::{
+    config_json::MeshJson,
+    export::extras::{self, AnimationExtras, JointExtras, MaterialExtras, MeshExtras},
+    gls::glam_read,
+    lol::{
+        anm::JointAnm,
+        hasher,
+        skl::{Joint, Type},
+        skn::{Mesh, SubMeshHeader},
+        Animation, Skeleton, Skin,
+    },
+};
+
+pub struct ImportedModel {
+    pub name: String,
+    pub skin: String,
+    pub skeleton: String,
+    pub textures: String,
+    pub animations: String,
+    pub meshes: Vec<MeshJson>,
+}
+
+// 读取只含一个蒙皮的 glTF/GLB，写出 SKN/SKL/ANM 以及 RGBA8 TEX 纹理到 output_dir
+pub fn import_gltf(path: &Path, output_dir: &Path) -> Result<ImportedModel, String> {
+    let (document, buffers, images) =
+        gltf::import(path).map_err(|error| format!("Could not read gltf: {error}"))?;
+
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("model")
+        .to_owned();
+
+    let gltf_skin = document
+        .skins()
+        .next()
+        .ok_or_else(|| String::from("glTF has no skin"))?;
+
+    let mesh_node = document
+        .nodes()
+        .find(|node| {
+            node.mesh().is_some() && node.skin().map(|x| x.index()) == Some(gltf_skin.index())
+        })
+        .ok_or_else(|| String::from("glTF has no mesh bound to the skin"))?;
+
+    let (skeleton, joint_remap) = make_skeleton(&document, &gltf_skin, &buffers)?;
+
+    let (skin, submesh_images) = make_skin(&mesh_node.mesh().unwrap(), &buffers, &joint_remap)?;
+
+    let mut node_to_joint: BTreeMap<usize, usize> = BTreeMap::new();
+    for (skin_joint_index, node) in gltf_skin.joints().enumerate() {
+        node_to_joint.insert(node.index(), joint_remap[skin_joint_index]);
+    }
+
+    let mut animations = vec![];
+    for (i, gltf_animation) in document.animations().enumerate() {
+        let animation_name = gltf_animation
+            .name()
+            .map(String::from)
+            .unwrap_or_else(|| format!("animation{i}"));
+        animations.push((
+            animation_name,
+            make_animation(&gltf_animation, &buffers, &skeleton, &node_to_joint),
+        ));
+    }
+
+    let textures_dir = output_dir.join("textures");
+    let animations_dir = output_dir.join("animations");
+    fs::create_dir_all(&textures_dir)
+        .map_err(|error| format!("Could not create textures dir: {error}"))?;
+    fs::create_dir_all(&animations_dir)
+        .map_err(|error| format!("Could not create animations dir: {error}"))?;
+
+    let skin_path = output_dir.join(format!("{name}.skn"));
+    let skeleton_path = output_dir.join(format!("{name}.skl"));
+
+    fs::write(&skin_path, skin.write()).map_err(|error| format!("Could not write skn: {error}"))?;
+    fs::write(&skeleton_path, skeleton.write())
+        .map_err(|error| format!("Could not write skl: {error}"))?;
+
+    for (animation_name, animation) in animations.iter() {
+        let contents = animation.write()?;
+        fs::write(
+            animations_dir.join(format!("{}.anm", file_safe(animation_name))),
+            contents,
+        )
+        .map_err(|error| format!("Could not write anm: {error}"))?;
+    }
+
+    // 优先使用导出时记录的原始纹理文件名
+    let mut original_textures_names: BTreeMap<usize, String> = BTreeMap::new();
+    for material in document.materials() {
+        let material_extras: Option<MaterialExtras> = extras::from_extras(material.extras());
+        if let (Some(material_extras), Some(info)) =
+            (material_extras, material.pbr_metallic_roughness().base_color_texture())
+        {
+            if let Some(stem) = Path::new(&material_extras.texture).file_stem() {
+                original_textures_names
+                    .entry(info.texture().source().index())
+                    .or_insert_with(|| file_safe(&stem.to_string_lossy()));
+            }
+        }
+    }
+
+    let mut textures_names: BTreeMap<usize, String> = BTreeMap::new();
+    for image_index in submesh_images.iter().flatten() {
+        if textures_names.contains_key(image_index) {
+            continue;
+        }
+        let image_name = original_textures_names
+            .get(image_index)
+            .cloned()
+            .or_else(|| {
+                document
+                    .images()
+                    .nth(*image_index)
+                    .and_then(|image| image.name().map(file_safe))
+            })
+            .unwrap_or_else(|| format!("texture{image_index}"));
+        let contents = make_tex(&images[*image_index])?;
+        fs::write(textures_dir.join(format!("{image_name}.tex")), contents)
+            .map_err(|error| format!("Could not write tex: {error}"))?;
+        textures_names.insert(*image_index, image_name);
+    }
+
+    let meshes = skin
+        .meshes
+        .iter()
+        .zip(submesh_images.iter())
+        .map(|(mesh, image_index)| {
+            let mut name_texture = BTreeMap::new();
+            if let Some(texture_name) = image_index.and_then(|x| textures_names.get(&x)) {
+                name_texture.insert(mesh.submesh.name.to_owned(), texture_name.to_owned());
+            }
+            MeshJson {
+                show: true,
+                name_texture,
+            }
+        })
+        .collect();
+
+    println!(
+        "glTF imported: {} joints, {} submeshes, {} animations",
+        skeleton.joints.len(),
+        skin.meshes.len(),
+        animations.len()
+    );
+
+    Ok(ImportedModel {
+        name,
+        skin: path_string(&skin_path),
+        skeleton: path_string(&skeleton_path),
+        textures: path_string(&textures_dir),
+        animations: path_string(&animations_dir),
+        meshes,
+    })
+}
+
+// 关节按父节点在前的顺序重新排列，返回 skin.joints 索引 -> 新关节索引
+fn make_skeleton(
+    document: &gltf::Document,
+    gltf_skin: &gltf::Skin,
+    buffers: &[gltf::buffer::Data],
+) -> Result<(Skeleton, Vec<usize>), String> {
+    let joint_nodes: Vec<gltf::Node> = gltf_skin.joints().collect();
+    if joint_nodes.is_empty() {
+        return Err(String::from("glTF skin has no joints"));
+    }
+    if joint_nodes.len() > 256 {
+        return Err(String::from("glTF skin has more than 256 joints"));
+    }
+
+    let reader = gltf_skin.reader(|buffer| Some(&buffers[buffer.index()]));
+    let inverse_bind_matrices: Option<Vec<glam::Mat4>> = reader
+        .read_inverse_bind_matrices()
+        .map(|x| x.map(|m| glam::Mat4::from_cols_array_2d(&m)).collect());
+
+    let mut node_parents: BTreeMap<usize, usize> = BTreeMap::new();
+    for node in document.nodes() {
+        for child in node.children() {
+            node_parents.insert(child.index(), node.index());
+        }
+    }
+
+    let node_to_skin_joint: BTreeMap<usize, usize> = joint_nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.index(), i))
+        .collect();
+
+    let skin_joint_parent: Vec<Option<usize>> = joint_nodes
+        .iter()
+        .map(|node| {
+            node_parents
+                .get(&node.index())
+                .and_then(|parent| node_to_skin_joint.get(parent).copied())
+        })
+        .collect();
+
+    let mut order = vec![];
+    let mut stack: Vec<usize> = (0..joint_nodes.len())
+        .filter(|i| skin_joint_parent[*i].is_none())
+        .rev()
+        .collect();
+    while let Some(skin_joint_index) = stack.pop() {
+        order.push(skin_joint_index);
+        let children: Vec<usize> = (0..joint_nodes.len())
+            .filter(|i| skin_joint_parent[*i] == Some(skin_joint_index))
+            .collect();
+        stack.extend(children.iter().rev());
+    }
+
+    let mut joint_remap = vec![0; joint_nodes.len()];
+    for (joint_index, skin_joint_index) in order.iter().enumerate() {
+        joint_remap[*skin_joint_index] = joint_index;
+    }
+
+    let mut joints: Vec<Joint> = Vec::with_capacity(order.len());
+    for (joint_index, skin_joint_index) in order.iter().enumerate() {
+        let node = &joint_nodes[*skin_joint_index];
+        let name = node
+            .name()
+            .map(String::from)
+            .unwrap_or_else(|| format!("joint{joint_index}"));
+        let parent_id = skin_joint_parent[*skin_joint_index]
+            .map(|parent| joint_remap[parent] as i16)
+            .unwrap_or(-1);
+
+        let local_matrix = glam::Mat4::from_cols_array_2d(&node.transform().matrix());
+        let global_matrix = if parent_id >= 0 {
+            joints[parent_id as usize].global_matrix * local_matrix
+        } else {
+            local_matrix
+        };
+        let inverse_global_matrix = match &inverse_bind_matrices {
+            Some(inverse_bind_matrices) => inverse_bind_matrices[*skin_joint_index],
+            None => global_matrix.inverse(),
+        };
+
+        // 没有记录哈希时才根据名称计算
+        let joint_extras: Option<JointExtras> = extras::from_extras(node.extras());
+        joints.push(Joint {
+            hash: joint_extras
+                .map(|x| x.hash)
+                .unwrap_or_else(|| hasher::string_to_hash(&name)),
+            name,
+            id: joint_index as i16,
+            parent_id,
+            local_matrix,
+            global_matrix: inverse_global_matrix.inverse(),
+            inverse_global_matrix,
+            children: vec![],
+        });
+    }
+
+    for i in 0..joints.len() {
+        let parent_id = joints[i].parent_id;
+        if parent_id != -1 {
+            joints[parent_id as usize].children.push(i);
+        }
+    }
+
+    let influences = (0..joints.len() as u16).collect();
+
+    Ok((
+        Skeleton {
+            // Skeleton::write 总是写入 Version2，原文件的类型和版本号不保留
+            stype: Type::Version2,
+            version: 0,
+            joints,
+            influences,
+        },
+        joint_remap,
+    ))
+}
+
+// 返回蒙皮以及每个子网格使用的图片索引
+fn make_skin(
+    gltf_mesh: &gltf::Mesh,
+    buffers: &[gltf::buffer::Data],
+    joint_remap: &[usize],
+) -> Result<(Skin, Vec<Option<usize>>), String> {
+    let mut vertices: Vec<glam::Vec3> = vec![];
+    let mut normals: Vec<glam::Vec3> = vec![];
+    let mut uvs: Vec<glam::Vec2> = vec![];
+    let mut influences: Vec<glam_read::U16Vec4> = vec![];
+    let mut weights: Vec<glam::Vec4> = vec![];
+    let mut indices: Vec<u16> = vec![];
+    let mut meshes: Vec<Mesh> = vec![];
+    let mut submesh_images = vec![];
+
+    let mesh_extras: Option<MeshExtras> = extras::from_extras(gltf_mesh.extras());
+
+    for (primitive_index, primitive) in gltf_mesh.primitives().enumerate() {
+        if primitive.mode() != gltf::mesh::Mode::Triangles {
+            return Err(String::from("glTF primitive is not a triangle list"));
+        }
+
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+        let positions: Vec<glam::Vec3> = reader
+            .read_positions()
+            .ok_or_else(|| String::from("glTF primitive has no positions"))?
+            .map(glam::Vec3::from)
+            .collect();
+        let vertex_count = positions.len();
+        let vertex_offset = vertices.len();
+
+        if vertex_offset + vertex_count > u16::MAX as usize + 1 {
+            return Err(String::from("glTF mesh has more than 65536 vertices"));
+        }
+
+        let primitive_normals: Vec<glam::Vec3> = match reader.read_normals() {
+            Some(iter) => iter
+                .map(|x| glam::Vec3::from(x).normalize_or_zero())
+                .collect(),
+            None => vec![glam::Vec3::Y; vertex_count],
+        };
+        let primitive_uvs: Vec<glam::Vec2> = match reader.read_tex_coords(0) {
+            Some(iter) => iter.into_f32().map(glam::Vec2::from).collect(),
+            None => vec![glam::Vec2::ZERO; vertex_count],
+        };
+
+        let mut vertex_influences: Vec<Vec<(u16, f32)>> = vec![vec![]; vertex_count];
+        for set in 0..2 {
+            if let (Some(joints_iter), Some(weights_iter)) =
+                (reader.read_joints(set), reader.read_weights(set))
+            {
+                for (i, (joint, weight)) in joints_iter
+                    .into_u16()
+                    .zip(weights_iter.into_f32())
+                    .enumerate()
+                {
+                    for j in 0..4 {
+                        if weight[j] > 0.0f32 {
+                            let joint_index = *joint_remap
+                                .get(joint[j] as usize)
+                                .ok_or_else(|| String::from("glTF joint index out of range"))?;
+                            vertex_influences[i].push((joint_index as u16, weight[j]));
+                        }
+                    }
+                }
+            }
+        }
+
+        for i in 0..vertex_count {
+            let (influence, weight) = cap_influences(&mut vertex_influences[i]);
+            influences.push(influence);
+            weights.push(weight);
+        }
+
+        let primitive_indices: Vec<u32> = match reader.read_indices() {
+            Some(iter) => iter.into_u32().collect(),
+            None => (0..vertex_count as u32).collect(),
+        };
+
+        let material = primitive.material();
+        let material_extras: Option<MaterialExtras> = extras::from_extras(material.extras());
+        let submesh_name = mesh_extras
+            .as_ref()
+            .and_then(|x| x.submeshes.get(primitive_index).cloned())
+            .or_else(|| material_extras.map(|x| x.submesh))
+            .or_else(|| material.name().map(String::from))
+            .unwrap_or_else(|| format!("submesh{primitive_index}"));
+        let submesh_name = unique_name(&meshes, submesh_name);
+
+        meshes.push(Mesh::new(SubMeshHeader {
+            name: submesh_name,
+            indices_offset: indices.len() as u32,
+            indices_count: primitive_indices.len() as u32,
+            material_index: 0,
+        }));
+        submesh_images.push(
+            material
+                .pbr_metallic_roughness()
+                .base_color_texture()
+                .map(|info| info.texture().source().index()),
+        );
+
+        indices.extend(
+            primitive_indices
+                .iter()
+                .map(|index| (*index as usize + vertex_offset) as u16),
+        );
+        vertices.extend_from_slice(&positions);
+        normals.extend_from_slice(&primitive_normals);
+        uvs.extend_from_slice(&primitive_uvs);
+    }
+
+    if meshes.is_empty() {
+        return Err(String::from("glTF mesh has no primitives"));
+    }
+
+    let mut bbmin = glam::Vec3::splat(f32::MAX);
+    let mut bbmax = glam::Vec3::splat(f32::MIN);
+    for pos in vertices.iter() {
+        bbmin = bbmin.min(*pos);
+        bbmax = bbmax.max(*pos);
+    }
+
+    Ok((
+        Skin {
+            // Skin::write 总是写入 4.1
+            major: 4,
+            minor: 1,
+            center: (bbmin + bbmax) / 2.0f32,
+            bounding_box: [bbmin, bbmax],
+            vertices,
+            normals,
+            uvs,
+            influences,
+            weights,
+            indices,
+            meshes,
+        },
+        submesh_images,
+    ))
+}
+
+// 保留权重最大的4个影响并归一化
+fn cap_influences(influences: &mut Vec<(u16, f32)>) -> (glam_read::U16Vec4, glam::Vec4) {
+    influences.sort_by(|a, b| b.1.total_cmp(&a.1));
+    influences.truncate(4);
+
+    let total: f32 = influences.iter().map(|x| x.1).sum();
+    if total <= 0.0f32 {
+        return (
+            glam_read::U16Vec4::new(0, 0, 0, 0),
+            glam::vec4(1.0f32, 0.0f32, 0.0f32, 0.0f32),
+        );
+    }
+
+    let mut influence = glam_read::U16Vec4::new(0, 0, 0, 0);
+    let mut weight = glam::Vec4::ZERO;
+    for (j, (joint_index, joint_weight)) in influences.iter().enumerate() {
+        influence[j] = *joint_index;
+        weight[j] = joint_weight / total;
+    }
+    (influence, weight)
+}
+
+fn make_animation(
+    gltf_animation: &gltf::Animation,
+    buffers: &[gltf::buffer::Data],
+    skeleton: &Skeleton,
+    node_to_joint: &BTreeMap<usize, usize>,
+) -> Animation {
+    let mut joints: Vec<JointAnm> = skeleton
+        .joints
+        .iter()
+        .map(|joint| JointAnm {
+            hash: joint.hash,
+            translations: vec![],
+            rotations: vec![],
+            scales: vec![],
+        })
+        .collect();
+
+    let mut duration = 0.0f32;
+
+    for channel in gltf_animation.channels() {
+        let joint_index = match node_to_joint.get(&channel.target().node().index()) {
+            Some(joint_index) => *joint_index,
+            None => continue,
+        };
+
+        let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+        let times: Vec<f32> = match reader.read_inputs() {
+            Some(iter) => iter.collect(),
+            None => continue,
+        };
+        duration = duration.max(times.last().copied().unwrap_or(0.0f32));
+
+        // 三次样条的输出为(入切线, 值, 出切线)，只取中间的值
+        let cubic_spline =
+            channel.sampler().interpolation() == gltf::animation::Interpolation::CubicSpline;
+        let step = if cubic_spline { 3 } else { 1 };
+        let skip = if cubic_spline { 1 } else { 0 };
+
+        let joint = &mut joints[joint_index];
+        match reader.read_outputs() {
+            Some(ReadOutputs::Translations(iter)) => {
+                let values: Vec<[f32; 3]> = iter.skip(skip).step_by(step).collect();
+                joint.translations = times
+                    .iter()
+                    .zip(values)
+                    .map(|(time, x)| (*time, glam::Vec3::from(x)))
+                    .collect();
+            }
+            Some(ReadOutputs::Rotations(iter)) => {
+                let values: Vec<[f32; 4]> = iter.into_f32().skip(skip).step_by(step).collect();
+                joint.rotations = times
+                    .iter()
+                    .zip(values)
+                    .map(|(time, x)| (*time, glam::Quat::from_array(x).normalize()))
+                    .collect();
+            }
+            Some(ReadOutputs::Scales(iter)) => {
+                let values: Vec<[f32; 3]> = iter.skip(skip).step_by(step).collect();
+                joint.scales = times
+                    .iter()
+                    .zip(values)
+                    .map(|(time, x)| (*time, glam::Vec3::from(x)))
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    // 没有动画轨道的关节保持静止姿势
+    for (joint_index, joint) in joints.iter_mut().enumerate() {
+        let (scale, rotation, translation) = skeleton.joints[joint_index]
+            .local_matrix
+            .to_scale_rotation_translation();
+        if joint.translations.is_empty() {
+            joint.translations.push((0.0f32, translation));
+        }
+        if joint.rotations.is_empty() {
+            joint.rotations.push((0.0f32, rotation));
+        }
+        if joint.scales.is_empty() {
+            joint.scales.push((0.0f32, scale));
+        }
+    }
+
+    let animation_extras: Option<AnimationExtras> = extras::from_extras(gltf_animation.extras());
+    let fps = animation_extras
+        .as_ref()
+        .map(|x| x.fps)
+        .filter(|fps| *fps > 0.0f32)
+        .unwrap_or(30.0f32);
+    let frame_delay = 1.0f32 / fps;
+
+    Animation {
+        format: animation_extras
+            .map(|x| x.format)
+            .unwrap_or_else(|| String::from("glTF")),
+        fps,
+        duration: duration.max(frame_delay),
+        frame_delay,
+        joints,
+    }
+}
+
+// 转换为 BGRA8 格式的 TEX (格式 20，无 mipmap)
+fn make_tex(image: &gltf::image::Data) -> Result<Vec<u8>, String> {
+    let bgra: Vec<u8> = match image.format {
+        gltf::image::Format::R8G8B8A8 => image
+            .pixels
+            .chunks(4)
+            .flat_map(|x| [x[2], x[1], x[0], x[3]])
+            .collect(),
+        gltf::image::Format::R8G8B8 => image
+            .pixels
+            .chunks(3)
+            .flat_map(|x| [x[2], x[1], x[0], 255])
+            .collect(),
+        format => return Err(format!("Unsupported glTF image format {format:?}")),
+    };
+
+    if image.width > u16::MAX as u32 || image.height > u16::MAX as u32 {
+        return Err(String::from("glTF image is too large for TEX"));
+    }
+
+    let mut tex = Vec::with_capacity(12 + bgra.len());
+    tex.extend_from_slice(b"TEX\0");
+    tex.extend_from_slice(&(image.width as u16).to_le_bytes());
+    tex.extend_from_slice(&(image.height as u16).to_le_bytes());
+    tex.extend_from_slice(&[1, 20, 0, 0]);
+    tex.extend_from_slice(&bgra);
+    Ok(tex)
+}
+
+fn unique_name(meshes: &[Mesh], name: String) -> String {
+    let mut unique = name.clone();
+    let mut i = 1;
+    while meshes.iter().any(|mesh| mesh.submesh.name == unique) {
+        unique = format!("{name}_{i}");
+        i += 1;
+    }
+    unique
+}
+
+fn file_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| if "\\/:*?\"<>|".contains(c) { '_' } else { c })
+        .collect()
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_str().unwrap().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        config_json::OptionsJson,
+        export::{self, ExportOptions},
+        gls::texture::{self, Format},
+        lol::anm,
+        MindModel,
+    };
+
+    #[test]
+    fn tex_is_written_as_bgra_and_read_back_as_rgba() {
+        let rgba = vec![
+            255, 0, 0, 255, //
+            0, 255, 0, 128, //
+            0, 0, 255, 0, //
+            10, 20, 30, 40,
+        ];
+        let image = gltf::image::Data {
+            pixels: rgba.clone(),
+            format: gltf::image::Format::R8G8B8A8,
+            width: 2,
+            height: 2,
+        };
+
+        let tex = make_tex(&image).unwrap();
+        assert_eq!(&tex[12..16], &[0, 0, 255, 255]);
+
+        let source = texture::load_compressed_source(&mut Cursor::new(tex.as_slice()));
+        assert!(matches!(source.format, Format::RGBA8));
+        assert_eq!((source.width, source.height), (2, 2));
+        assert_eq!(source.mipmaps, vec![rgba]);
+    }
+
+    fn test_joint(name: &str, id: i16, parent: Option<&Joint>, local_matrix: glam::Mat4) -> Joint {
+        let global_matrix = match parent {
+            Some(parent) => parent.global_matrix * local_matrix,
+            None => local_matrix,
+        };
+        Joint {
+            name: String::from(name),
+            hash: hasher::string_to_hash(name),
+            id,
+            parent_id: parent.map(|x| x.id).unwrap_or(-1),
+            local_matrix,
+            global_matrix,
+            inverse_global_matrix: global_matrix.inverse(),
+            children: vec![],
+        }
+    }
+
+    // 每个子网格的三角形顶点位置，顶点顺序与索引无关
+    fn triangles(skin: &Skin) -> Vec<(String, Vec<[glam::Vec3; 3]>)> {
+        skin.meshes
+            .iter()
+            .map(|mesh| {
+                let start = mesh.submesh.indices_offset as usize;
+                let end = start + mesh.submesh.indices_count as usize;
+                let triangles = skin.indices[start..end]
+                    .chunks(3)
+                    .map(|x| [0, 1, 2].map(|i| skin.vertices[x[i] as usize]))
+                    .collect();
+                (mesh.submesh.name.to_owned(), triangles)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exported_glb_is_imported_back() {
+        let root = test_joint(
+            "Root",
+            0,
+            None,
+            glam::Mat4::from_translation(glam::vec3(0.0f32, 1.0f32, 0.0f32)),
+        );
+        let arm = test_joint(
+            "R_Arm",
+            1,
+            Some(&root),
+            glam::Mat4::from_rotation_translation(
+                glam::Quat::from_rotation_z(0.5f32),
+                glam::vec3(1.0f32, 0.0f32, 0.0f32),
+            ),
+        );
+
+        let mut mind_model = MindModel::empty();
+        mind_model.skeleton.joints = vec![root, arm];
+        mind_model.skeleton.joints[0].children = vec![1];
+        mind_model.skeleton.influences = vec![0, 1];
+        mind_model.skin.vertices = vec![
+            glam::vec3(0.0f32, 0.0f32, 0.0f32),
+            glam::vec3(1.0f32, 0.0f32, 0.0f32),
+            glam::vec3(0.0f32, 2.0f32, 0.0f32),
+            glam::vec3(0.0f32, 0.0f32, -3.0f32),
+        ];
+        mind_model.skin.normals = vec![glam::Vec3::Z; 4];
+        mind_model.skin.uvs = vec![glam::Vec2::ZERO; 4];
+        mind_model.skin.influences = vec![glam_read::U16Vec4::new(0, 1, 0, 0); 4];
+        mind_model.skin.weights = vec![glam::vec4(0.5f32, 0.5f32, 0.0f32, 0.0f32); 4];
+        mind_model.skin.indices = vec![0, 1, 2, 0, 2, 3];
+        mind_model.skin.meshes = vec![
+            Mesh::new(SubMeshHeader {
+                name: String::from("Body"),
+                indices_offset: 0,
+                indices_count: 3,
+                material_index: 0,
+            }),
+            Mesh::new(SubMeshHeader {
+                name: String::from("Weapon"),
+                indices_offset: 3,
+                indices_count: 3,
+                material_index: 0,
+            }),
+        ];
+        mind_model.show_meshes = vec![true; 2];
+        mind_model.animations = vec![Animation {
+            format: String::from("glTF"),
+            fps: 10.0f32,
+            duration: 0.3f32,
+            frame_delay: 0.1f32,
+            joints: vec![JointAnm {
+                hash: hasher::string_to_hash("R_Arm"),
+                translations: vec![
+                    (0.0f32, glam::vec3(1.0f32, 0.0f32, 0.0f32)),
+                    (0.2f32, glam::vec3(1.0f32, 2.0f32, 0.0f32)),
+                ],
+                rotations: vec![(0.0f32, glam::Quat::from_rotation_z(0.5f32))],
+                scales: vec![(0.0f32, glam::Vec3::ONE)],
+            }],
+        }];
+        mind_model.animations_file_names = vec![String::from("idle")];
+
+        let dir = std::env::temp_dir().join(format!("import_round_trip_{}", std::process::id()));
+        let mut export_options = ExportOptions::new();
+        export_options.export_as = 1;
+        export_options.open_folder = false;
+        export_options.output_dir = path_string(&dir.join("export"));
+
+        let glb = export::export_model(
+            &export_options,
+            &String::from("model"),
+            &mind_model,
+            true,
+            &OptionsJson::new(),
+        )
+        .unwrap();
+        let imported = import_gltf(&glb, &dir.join("import"));
+        let skin = fs::read(dir.join("import").join("model.skn"));
+        let skeleton = fs::read(dir.join("import").join("model.skl"));
+        let animation = fs::read(dir.join("import").join("animations").join("idle.anm"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let imported = imported.unwrap();
+        assert_eq!(imported.meshes.len(), 2);
+
+        let skin = Skin::read(&skin.unwrap());
+        let expected = triangles(&mind_model.skin);
+        let read = triangles(&skin);
+        assert_eq!(read.len(), expected.len());
+        for ((read_name, read), (name, expected)) in read.iter().zip(expected.iter()) {
+            assert_eq!(read_name, name);
+            assert_eq!(read.len(), expected.len());
+            for (a, b) in read.iter().flatten().zip(expected.iter().flatten()) {
+                assert!(a.abs_diff_eq(*b, 1e-5f32));
+            }
+        }
+
+        let skeleton = Skeleton::read(&skeleton.unwrap());
+        assert_eq!(skeleton.joints.len(), 2);
+        for (a, b) in skeleton.joints.iter().zip(mind_model.skeleton.joints.iter()) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.hash, b.hash);
+            assert_eq!(a.parent_id, b.parent_id);
+            assert!(a.local_matrix.abs_diff_eq(b.local_matrix, 1e-5f32));
+        }
+
+        let animation = Animation::read(&animation.unwrap());
+        let joint = animation
+            .joints
+            .iter()
+            .find(|x| x.hash == hasher::string_to_hash("R_Arm"))
+            .unwrap();
+        let (start, end, lerp_value) = anm::find_in_nearest_time(&joint.translations, 0.2f32);
+        let translation = start.lerp(end, lerp_value);
+        assert!(translation.abs_diff_eq(glam::vec3(1.0f32, 2.0f32, 0.0f32), 1e-4f32));
+    }
+}
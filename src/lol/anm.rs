@@ -1,8 +1,8 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap},
     f32,
-    io::{Cursor, Read},
+    io::{Cursor, Read, Write},
 };
 
 use crate::{
@@ -41,6 +41,8 @@ pub struct JointAnm {
 }
 
 pub struct Animation {
+    // 来源格式，例如 "r3d2anmd v4"，只作为导出时的元数据
+    pub format: String,
     pub fps: f32,
     pub duration: f32,
     pub frame_delay: f32,
@@ -75,6 +77,123 @@ impl Animation {
         }
     }
 
+    // 以未压缩的 r3d2anmd v4 格式写出，按 frame_delay 对每个关节重新采样。
+    // 最后一帧取最后一个关键帧的时间，不是 frame_delay 整数倍的结束时间也会被写出
+    pub fn write(&self) -> Result<Vec<u8>, String> {
+        let end_time = self
+            .joints
+            .iter()
+            .flat_map(|joint| {
+                [
+                    joint.translations.last().map(|x| x.0),
+                    joint.rotations.last().map(|x| x.0),
+                    joint.scales.last().map(|x| x.0),
+                ]
+            })
+            .flatten()
+            .fold(0.0f32, f32::max);
+        // 读取时第 i 帧的时间为 i * frame_delay，容差避免浮点误差多出一帧
+        let frame_count = (end_time / self.frame_delay - 1e-3f32).ceil().max(0.0f32) as usize + 1;
+
+        let mut vectors: Vec<glam::Vec3> = vec![];
+        let mut vectors_map: HashMap<[u32; 3], u16> = HashMap::new();
+        let mut rotations: Vec<glam::Quat> = vec![];
+        let mut rotations_map: HashMap<[u32; 4], u16> = HashMap::new();
+
+        let mut frames: Vec<(u32, u16, u16, u16)> =
+            Vec::with_capacity(frame_count * self.joints.len());
+        for frame in 0..frame_count {
+            let time = (frame as f32 * self.frame_delay).min(end_time);
+            for joint in self.joints.iter() {
+                let (min, max, lerp_value) = find_in_nearest_time(&joint.translations, time);
+                let translation = min.lerp(max, lerp_value);
+                let (min, max, lerp_value) = find_in_nearest_time(&joint.scales, time);
+                let scale = min.lerp(max, lerp_value);
+                let (min, max, lerp_value) = find_in_nearest_time(&joint.rotations, time);
+                let rotation = min.lerp(max, lerp_value);
+
+                let translation_index = insert_unique(
+                    &mut vectors,
+                    &mut vectors_map,
+                    translation.to_array().map(f32::to_bits),
+                    translation,
+                )?;
+                let scale_index = insert_unique(
+                    &mut vectors,
+                    &mut vectors_map,
+                    scale.to_array().map(f32::to_bits),
+                    scale,
+                )?;
+                let rotation_index = insert_unique(
+                    &mut rotations,
+                    &mut rotations_map,
+                    rotation.to_array().map(f32::to_bits),
+                    rotation,
+                )?;
+
+                frames.push((joint.hash, translation_index, scale_index, rotation_index));
+            }
+        }
+
+        const HEADER_SIZE: usize = 64;
+        let vectors_offset = HEADER_SIZE;
+        let rotations_offset = vectors_offset + vectors.len() * 12;
+        let frames_offset = rotations_offset + rotations.len() * 16;
+        let file_size = frames_offset + frames.len() * 12;
+
+        let mut writer: Vec<u8> = Vec::with_capacity(file_size);
+
+        writer.write_all(b"r3d2anmd").unwrap();
+        writer.write_u32::<LittleEndian>(4).unwrap();
+        writer
+            .write_u32::<LittleEndian>((file_size - 12) as u32)
+            .unwrap();
+        writer.write_u32::<LittleEndian>(0xBE0794D3).unwrap();
+        writer.write_u32::<LittleEndian>(0).unwrap();
+        writer.write_u32::<LittleEndian>(0).unwrap();
+        writer
+            .write_u32::<LittleEndian>(self.joints.len() as u32)
+            .unwrap();
+        writer
+            .write_u32::<LittleEndian>(frame_count as u32)
+            .unwrap();
+        writer
+            .write_f32::<LittleEndian>(self.frame_delay)
+            .unwrap();
+        for _ in 0..3 {
+            writer.write_i32::<LittleEndian>(-1).unwrap();
+        }
+        writer
+            .write_u32::<LittleEndian>((vectors_offset - 12) as u32)
+            .unwrap();
+        writer
+            .write_u32::<LittleEndian>((rotations_offset - 12) as u32)
+            .unwrap();
+        writer
+            .write_u32::<LittleEndian>((frames_offset - 12) as u32)
+            .unwrap();
+
+        for vector in vectors.iter() {
+            for value in vector.to_array() {
+                writer.write_f32::<LittleEndian>(value).unwrap();
+            }
+        }
+        for rotation in rotations.iter() {
+            for value in rotation.to_array() {
+                writer.write_f32::<LittleEndian>(value).unwrap();
+            }
+        }
+        for (hash, translation_index, scale_index, rotation_index) in frames {
+            writer.write_u32::<LittleEndian>(hash).unwrap();
+            writer.write_u16::<LittleEndian>(translation_index).unwrap();
+            writer.write_u16::<LittleEndian>(scale_index).unwrap();
+            writer.write_u16::<LittleEndian>(rotation_index).unwrap();
+            writer.write_u16::<LittleEndian>(0).unwrap();
+        }
+
+        Ok(writer)
+    }
+
     fn read_compressed(reader: &mut Cursor<&Vec<u8>>) -> Animation {
         let version = reader
             .read_u32::<LittleEndian>()
@@ -224,6 +343,7 @@ impl Animation {
         //println!("Duration: {duration}");
 
         Animation {
+            format: format!("r3d2canm v{version}"),
             fps,
             duration,
             frame_delay,
@@ -341,6 +461,7 @@ impl Animation {
         //println!("Duration: {duration}");
 
         Animation {
+            format: String::from("r3d2anmd v5"),
             fps,
             duration,
             frame_delay,
@@ -459,6 +580,7 @@ impl Animation {
         //println!("Duration: {duration}");
 
         Animation {
+            format: String::from("r3d2anmd v4"),
             fps,
             duration,
             frame_delay,
@@ -526,6 +648,7 @@ impl Animation {
         //println!("Duration: {duration}");
 
         Animation {
+            format: format!("r3d2anmd v{version}"),
             fps,
             duration,
             frame_delay,
@@ -534,6 +657,24 @@ impl Animation {
     }
 }
 
+fn insert_unique<K: std::hash::Hash + Eq, T: Copy>(
+    values: &mut Vec<T>,
+    values_map: &mut HashMap<K, u16>,
+    key: K,
+    value: T,
+) -> Result<u16, String> {
+    if let Some(index) = values_map.get(&key) {
+        return Ok(*index);
+    }
+    if values.len() > u16::MAX as usize {
+        return Err(String::from("ANM has too many unique frame values"));
+    }
+    let index = values.len() as u16;
+    values.push(value);
+    values_map.insert(key, index);
+    Ok(index)
+}
+
 fn uncompress_quaternion(data: u64) -> glam::Quat {
     let index = ((data >> 45) & 0x0003) as u16;
     let v_a = ((data >> 30) & 0x7FFF) as u16;
@@ -650,3 +791,62 @@ pub fn run_animation(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframes(animation: &Animation) -> Vec<(u32, Vec<(f32, glam::Vec3)>, Vec<(f32, glam::Quat)>)> {
+        let mut keyframes: Vec<_> = animation
+            .joints
+            .iter()
+            .map(|joint| (joint.hash, joint.translations.clone(), joint.rotations.clone()))
+            .collect();
+        keyframes.sort_by_key(|x| x.0);
+        keyframes
+    }
+
+    #[test]
+    fn write_keeps_the_last_frame() {
+        let end_rotation = glam::Quat::from_rotation_y(1.0f32);
+        let animation = Animation {
+            format: String::from("glTF"),
+            fps: 10.0f32,
+            duration: 0.25f32,
+            frame_delay: 0.1f32,
+            joints: vec![JointAnm {
+                hash: hasher::string_to_hash("Root"),
+                translations: vec![
+                    (0.0f32, glam::Vec3::ZERO),
+                    (0.25f32, glam::Vec3::new(0.0f32, 2.5f32, 0.0f32)),
+                ],
+                rotations: vec![(0.0f32, glam::Quat::IDENTITY), (0.25f32, end_rotation)],
+                scales: vec![(0.0f32, glam::Vec3::ONE)],
+            }],
+        };
+
+        let read = Animation::read(&animation.write().unwrap());
+        let translations = &read.joints[0].translations;
+        assert_eq!(translations.len(), 4);
+        assert!(translations[3].1.abs_diff_eq(glam::Vec3::new(0.0f32, 2.5f32, 0.0f32), 1e-5f32));
+        assert!(read.joints[0].rotations[3].1.abs_diff_eq(end_rotation, 1e-5f32));
+
+        // 再次写出读取后关键帧不变，不会每次多出一帧
+        let read_again = Animation::read(&read.write().unwrap());
+        let (first, second) = (keyframes(&read), keyframes(&read_again));
+        assert_eq!(first.len(), second.len());
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.0, b.0);
+            assert_eq!(a.1.len(), b.1.len());
+            assert_eq!(a.2.len(), b.2.len());
+            for (x, y) in a.1.iter().zip(b.1.iter()) {
+                assert!((x.0 - y.0).abs() < 1e-5f32);
+                assert!(x.1.abs_diff_eq(y.1, 1e-5f32));
+            }
+            for (x, y) in a.2.iter().zip(b.2.iter()) {
+                assert!((x.0 - y.0).abs() < 1e-5f32);
+                assert!(x.1.abs_diff_eq(y.1, 1e-5f32));
+            }
+        }
+    }
+}
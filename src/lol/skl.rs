@@ -1,5 +1,5 @@
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::{Cursor, Read};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Cursor, Read, Write};
 
 use crate::{gls::glam_read, lol::hasher};
 
@@ -14,6 +14,7 @@ pub struct Joint {
     pub children: Vec<usize>,
 }
 
+#[derive(Clone, Copy)]
 pub enum Type {
     Classic = 0x746C6B73,
     Version2 = 0x22FD4FC3,
@@ -65,6 +66,100 @@ impl Skeleton {
         skeleton
     }
 
+    // 总是以 Version2 格式写出
+    pub fn write(&self) -> Vec<u8> {
+        const HEADER_SIZE: usize = 64;
+        const JOINT_SIZE: usize = 100;
+
+        let joint_count = self.joints.len();
+        let joints_offset = HEADER_SIZE;
+        let joint_indices_offset = joints_offset + joint_count * JOINT_SIZE;
+        let influences_offset = joint_indices_offset + joint_count * 8;
+        let names_offset = influences_offset + self.influences.len() * 2;
+
+        let mut name_offsets = Vec::with_capacity(joint_count);
+        let mut names_length = 0;
+        for joint in self.joints.iter() {
+            name_offsets.push(names_offset + names_length);
+            names_length += joint.name.len() + 1;
+        }
+        let file_size = names_offset + names_length;
+
+        let mut writer: Vec<u8> = Vec::with_capacity(file_size);
+
+        writer.write_u32::<LittleEndian>(file_size as u32).unwrap();
+        writer
+            .write_u32::<LittleEndian>(Type::Version2 as u32)
+            .unwrap();
+        writer.write_u32::<LittleEndian>(0).unwrap();
+        writer.write_u16::<LittleEndian>(0).unwrap();
+        writer.write_u16::<LittleEndian>(joint_count as u16).unwrap();
+        writer
+            .write_u32::<LittleEndian>(self.influences.len() as u32)
+            .unwrap();
+        writer.write_u32::<LittleEndian>(joints_offset as u32).unwrap();
+        writer
+            .write_u32::<LittleEndian>(joint_indices_offset as u32)
+            .unwrap();
+        writer
+            .write_u32::<LittleEndian>(influences_offset as u32)
+            .unwrap();
+        writer.write_i32::<LittleEndian>(-1).unwrap();
+        writer.write_i32::<LittleEndian>(-1).unwrap();
+        writer.write_i32::<LittleEndian>(names_offset as i32).unwrap();
+        for _ in 0..5 {
+            writer.write_u32::<LittleEndian>(0xFFFFFFFF).unwrap();
+        }
+
+        for (i, joint) in self.joints.iter().enumerate() {
+            writer.write_u16::<LittleEndian>(0).unwrap();
+            writer.write_i16::<LittleEndian>(joint.id).unwrap();
+            writer.write_i16::<LittleEndian>(joint.parent_id).unwrap();
+            writer.write_u16::<LittleEndian>(0).unwrap();
+            writer.write_u32::<LittleEndian>(joint.hash).unwrap();
+            writer.write_f32::<LittleEndian>(2.1f32).unwrap();
+
+            let (scale, rotation, translation) = joint.local_matrix.to_scale_rotation_translation();
+            let (inverse_scale, inverse_rotation, inverse_translation) =
+                joint.inverse_global_matrix.to_scale_rotation_translation();
+
+            for value in translation
+                .to_array()
+                .iter()
+                .chain(scale.to_array().iter())
+                .chain(rotation.to_array().iter())
+                .chain(inverse_translation.to_array().iter())
+                .chain(inverse_scale.to_array().iter())
+                .chain(inverse_rotation.to_array().iter())
+            {
+                writer.write_f32::<LittleEndian>(*value).unwrap();
+            }
+
+            let name_offset = name_offsets[i] - writer.len();
+            writer.write_i32::<LittleEndian>(name_offset as i32).unwrap();
+        }
+
+        let mut joint_indices: Vec<(u32, i16)> =
+            self.joints.iter().map(|joint| (joint.hash, joint.id)).collect();
+        joint_indices.sort_by_key(|(hash, _)| *hash);
+        for (hash, id) in joint_indices {
+            writer.write_i16::<LittleEndian>(id).unwrap();
+            writer.write_u16::<LittleEndian>(0).unwrap();
+            writer.write_u32::<LittleEndian>(hash).unwrap();
+        }
+
+        for influence in self.influences.iter() {
+            writer.write_u16::<LittleEndian>(*influence).unwrap();
+        }
+
+        for joint in self.joints.iter() {
+            writer.write_all(joint.name.as_bytes()).unwrap();
+            writer.write_u8(0).unwrap();
+        }
+
+        writer
+    }
+
     fn read_classic(reader: &mut Cursor<&Vec<u8>>) -> Skeleton {
         let mut signature = vec![0u8; 8];
         reader
@@ -171,11 +266,6 @@ impl Skeleton {
             }
         };
 
-        print!("SKL version {version} was succesfully loaded: ");
-        print!("Type: Classic ");
-        print!("Joints count: {} ", joints.len());
-        println!("Joints influences: {}", influences.len());
-
         Skeleton {
             stype: Type::Classic,
             version,
@@ -293,11 +383,6 @@ impl Skeleton {
             );
         }
 
-        print!("SKL version {version} was succesfully loaded: ");
-        print!("Type: Version2 ");
-        print!("Joints count: {} ", joints.len());
-        println!("Joints influences: {}", influences.len());
-
         Skeleton {
             stype: Type::Version2,
             version,
@@ -306,3 +391,65 @@ impl Skeleton {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joint(name: &str, id: i16, parent: Option<&Joint>, local_matrix: glam::Mat4) -> Joint {
+        let global_matrix = match parent {
+            Some(parent) => parent.global_matrix * local_matrix,
+            None => local_matrix,
+        };
+        Joint {
+            name: String::from(name),
+            hash: hasher::fnv1a(name),
+            id,
+            parent_id: parent.map(|x| x.id).unwrap_or(-1),
+            local_matrix,
+            global_matrix,
+            inverse_global_matrix: global_matrix.inverse(),
+            children: vec![],
+        }
+    }
+
+    #[test]
+    fn write_round_trip() {
+        let root = joint(
+            "Root",
+            0,
+            None,
+            glam::Mat4::from_translation(glam::vec3(0.0f32, 1.0f32, 0.0f32)),
+        );
+        let arm = joint(
+            "R_Arm",
+            1,
+            Some(&root),
+            glam::Mat4::from_scale_rotation_translation(
+                glam::Vec3::splat(2.0f32),
+                glam::Quat::from_rotation_z(0.5f32),
+                glam::vec3(1.0f32, 0.0f32, 0.0f32),
+            ),
+        );
+        let skeleton = Skeleton {
+            stype: Type::Version2,
+            version: 0,
+            joints: vec![root, arm],
+            influences: vec![1, 0],
+        };
+
+        let read = Skeleton::read(&skeleton.write());
+
+        assert!(matches!(read.stype, Type::Version2));
+        assert_eq!(read.influences, skeleton.influences);
+        assert_eq!(read.joints.len(), 2);
+        for (a, b) in read.joints.iter().zip(skeleton.joints.iter()) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.hash, b.hash);
+            assert_eq!((a.id, a.parent_id), (b.id, b.parent_id));
+            assert!(a.local_matrix.abs_diff_eq(b.local_matrix, 1e-5f32));
+            assert!(a.inverse_global_matrix.abs_diff_eq(b.inverse_global_matrix, 1e-5f32));
+        }
+        assert_eq!(read.joints[0].children, [1]);
+    }
+}
@@ -1,5 +1,5 @@
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::{Cursor, Read};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Cursor, Read, Write};
 
 use crate::{
     gls::glam_read,
@@ -20,7 +20,7 @@ pub struct Mesh {
 }
 
 impl Mesh {
-    fn new(submesh: SubMeshHeader) -> Mesh {
+    pub fn new(submesh: SubMeshHeader) -> Mesh {
         Mesh {
             hash: hasher::fnv1a(&submesh.name),
             submesh,
@@ -181,11 +181,6 @@ impl Skin {
             })]
         };
 
-        print!("SKN version {major} {minor} was succesfully loaded: ");
-        print!("SubMeshHeader count: {submeshheader_count} ");
-        print!("indices count: {indices_count} ");
-        println!("vertex count: {vertex_count} ");
-
         Skin {
             major,
             minor,
@@ -201,6 +196,182 @@ impl Skin {
         }
     }
 
+    // 以 4.1 版本写出，influences 必须是骨骼 influences 列表中的索引(即 apply_skeleton 之前的值)
+    pub fn write(&self) -> Vec<u8> {
+        let mut writer: Vec<u8> = vec![];
+
+        writer.write_all(&[0x33, 0x22, 0x11, 0x00]).unwrap();
+        writer.write_u16::<LittleEndian>(4).unwrap();
+        writer.write_u16::<LittleEndian>(1).unwrap();
+
+        writer
+            .write_u32::<LittleEndian>(self.meshes.len() as u32)
+            .unwrap();
+        for mesh in self.meshes.iter() {
+            let mut name = mesh.submesh.name.as_bytes().to_vec();
+            name.resize(64, 0);
+            name[63] = 0;
+            writer.write_all(&name).unwrap();
+
+            let indices_offset = mesh.submesh.indices_offset as usize;
+            let indices_count = mesh.submesh.indices_count as usize;
+            let indices = &self.indices[indices_offset..indices_offset + indices_count];
+            let vertex_min = indices.iter().copied().min().unwrap_or(0) as u32;
+            let vertex_max = indices.iter().copied().max().unwrap_or(0) as u32;
+
+            writer.write_u32::<LittleEndian>(vertex_min).unwrap();
+            writer
+                .write_u32::<LittleEndian>(vertex_max - vertex_min + 1)
+                .unwrap();
+            writer
+                .write_u32::<LittleEndian>(mesh.submesh.indices_offset)
+                .unwrap();
+            writer
+                .write_u32::<LittleEndian>(mesh.submesh.indices_count)
+                .unwrap();
+        }
+
+        writer.write_u32::<LittleEndian>(0).unwrap();
+
+        writer
+            .write_u32::<LittleEndian>(self.indices.len() as u32)
+            .unwrap();
+        writer
+            .write_u32::<LittleEndian>(self.vertices.len() as u32)
+            .unwrap();
+
+        writer.write_u32::<LittleEndian>(52).unwrap();
+        writer.write_u32::<LittleEndian>(0).unwrap();
+
+        let mut bbmin = glam::Vec3::splat(f32::MAX);
+        let mut bbmax = glam::Vec3::splat(f32::MIN);
+        for pos in self.vertices.iter() {
+            bbmin = bbmin.min(*pos);
+            bbmax = bbmax.max(*pos);
+        }
+        if self.vertices.is_empty() {
+            bbmin = glam::Vec3::ZERO;
+            bbmax = glam::Vec3::ZERO;
+        }
+        let sphere_center = (bbmin + bbmax) / 2.0f32;
+        let sphere_radius = (bbmax - sphere_center).length();
+
+        for value in bbmin
+            .to_array()
+            .iter()
+            .chain(bbmax.to_array().iter())
+            .chain(sphere_center.to_array().iter())
+            .chain([sphere_radius].iter())
+        {
+            writer.write_f32::<LittleEndian>(*value).unwrap();
+        }
+
+        for index in self.indices.iter() {
+            writer.write_u16::<LittleEndian>(*index).unwrap();
+        }
+
+        for i in 0..self.vertices.len() {
+            for value in self.vertices[i].to_array() {
+                writer.write_f32::<LittleEndian>(value).unwrap();
+            }
+            for j in 0..4 {
+                writer.write_u8(self.influences[i][j] as u8).unwrap();
+            }
+            for value in self.weights[i].to_array() {
+                writer.write_f32::<LittleEndian>(value).unwrap();
+            }
+            for value in self.normals[i].to_array() {
+                writer.write_f32::<LittleEndian>(value).unwrap();
+            }
+            for value in self.uvs[i].to_array() {
+                writer.write_f32::<LittleEndian>(value).unwrap();
+            }
+        }
+
+        writer
+    }
+
+    // 在CPU上计算蒙皮后的顶点和法线，与 model.vert 的骨骼混合方式一致
+    pub fn skinned(&self, joints_transforms: &[glam::Mat4]) -> (Vec<glam::Vec3>, Vec<glam::Vec3>) {
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        let mut normals = Vec::with_capacity(self.normals.len());
+        for i in 0..self.vertices.len() {
+            let mut transform = glam::Mat4::ZERO;
+            for j in 0..4 {
+                transform += joints_transforms[self.influences[i][j] as usize] * self.weights[i][j];
+            }
+            vertices.push(transform.transform_point3(self.vertices[i]));
+            normals.push(transform.transform_vector3(self.normals[i]).normalize_or_zero());
+        }
+        (vertices, normals)
+    }
+
+    // 只保留 keep 为 true 的子网格，顶点重新编号，子网格的索引偏移随之调整
+    pub fn filtered(&self, keep: &[bool]) -> Skin {
+        let mut vertex_remap: Vec<Option<u16>> = vec![None; self.vertices.len()];
+        let mut skin = Skin {
+            major: self.major,
+            minor: self.minor,
+            center: self.center,
+            bounding_box: [glam::Vec3::splat(f32::MAX), glam::Vec3::splat(f32::MIN)],
+            vertices: vec![],
+            normals: vec![],
+            uvs: vec![],
+            influences: vec![],
+            weights: vec![],
+            indices: vec![],
+            meshes: vec![],
+        };
+
+        for (i, mesh) in self.meshes.iter().enumerate() {
+            if !keep.get(i).copied().unwrap_or(true) {
+                continue;
+            }
+
+            let indices_offset = mesh.submesh.indices_offset as usize;
+            let indices_count = mesh.submesh.indices_count as usize;
+            let new_indices_offset = skin.indices.len() as u32;
+
+            for index in &self.indices[indices_offset..indices_offset + indices_count] {
+                let index = *index as usize;
+                let new_index = match vertex_remap[index] {
+                    Some(new_index) => new_index,
+                    None => {
+                        let new_index = skin.vertices.len() as u16;
+                        vertex_remap[index] = Some(new_index);
+                        skin.vertices.push(self.vertices[index]);
+                        skin.normals.push(self.normals[index]);
+                        skin.uvs.push(self.uvs[index]);
+                        skin.influences.push(self.influences[index]);
+                        skin.weights.push(self.weights[index]);
+                        new_index
+                    }
+                };
+                skin.indices.push(new_index);
+            }
+
+            skin.meshes.push(Mesh {
+                hash: mesh.hash,
+                submesh: SubMeshHeader {
+                    name: mesh.submesh.name.clone(),
+                    indices_offset: new_indices_offset,
+                    indices_count: mesh.submesh.indices_count,
+                    material_index: mesh.submesh.material_index,
+                },
+            });
+        }
+
+        for vertex in skin.vertices.iter() {
+            skin.bounding_box[0] = skin.bounding_box[0].min(*vertex);
+            skin.bounding_box[1] = skin.bounding_box[1].max(*vertex);
+        }
+        if skin.vertices.is_empty() {
+            skin.bounding_box = [glam::Vec3::ZERO; 2];
+        }
+
+        skin
+    }
+
     pub fn apply_skeleton(&mut self, skeleton: &Skeleton) {
         for skin_influence in self.influences.iter_mut() {
             skin_influence.x = skeleton.influences[skin_influence.x as usize];
@@ -210,3 +381,79 @@ impl Skin {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_round_trip() {
+        let skin = Skin {
+            major: 4,
+            minor: 1,
+            center: glam::Vec3::ZERO,
+            bounding_box: [glam::Vec3::ZERO; 2],
+            vertices: vec![
+                glam::vec3(0.0f32, 0.0f32, 0.0f32),
+                glam::vec3(1.0f32, 0.0f32, 0.0f32),
+                glam::vec3(0.0f32, 2.0f32, 0.0f32),
+                glam::vec3(0.0f32, 0.0f32, -3.0f32),
+            ],
+            normals: vec![glam::Vec3::Z, glam::Vec3::Y, glam::Vec3::X, glam::Vec3::NEG_Z],
+            uvs: vec![
+                glam::vec2(0.0f32, 0.0f32),
+                glam::vec2(1.0f32, 0.0f32),
+                glam::vec2(0.0f32, 1.0f32),
+                glam::vec2(0.5f32, 0.5f32),
+            ],
+            influences: vec![
+                glam_read::U16Vec4::new(0, 1, 0, 0),
+                glam_read::U16Vec4::new(1, 0, 0, 0),
+                glam_read::U16Vec4::new(2, 1, 0, 0),
+                glam_read::U16Vec4::new(3, 2, 1, 0),
+            ],
+            weights: vec![
+                glam::vec4(0.5f32, 0.5f32, 0.0f32, 0.0f32),
+                glam::vec4(1.0f32, 0.0f32, 0.0f32, 0.0f32),
+                glam::vec4(0.75f32, 0.25f32, 0.0f32, 0.0f32),
+                glam::vec4(0.25f32, 0.25f32, 0.5f32, 0.0f32),
+            ],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            meshes: vec![
+                Mesh::new(SubMeshHeader {
+                    name: String::from("Body"),
+                    indices_offset: 0,
+                    indices_count: 3,
+                    material_index: 0,
+                }),
+                Mesh::new(SubMeshHeader {
+                    name: String::from("Weapon"),
+                    indices_offset: 3,
+                    indices_count: 3,
+                    material_index: 0,
+                }),
+            ],
+        };
+
+        let read = Skin::read(&skin.write());
+
+        assert_eq!((read.major, read.minor), (4, 1));
+        assert_eq!(read.indices, skin.indices);
+        assert_eq!(read.vertices, skin.vertices);
+        assert_eq!(read.normals, skin.normals);
+        assert_eq!(read.uvs, skin.uvs);
+        assert_eq!(read.weights, skin.weights);
+        for (a, b) in read.influences.iter().zip(skin.influences.iter()) {
+            assert_eq!([a.x, a.y, a.z, a.w], [b.x, b.y, b.z, b.w]);
+        }
+        assert_eq!(read.bounding_box, [glam::vec3(0.0f32, 0.0f32, -3.0f32), glam::vec3(1.0f32, 2.0f32, 0.0f32)]);
+
+        assert_eq!(read.meshes.len(), 2);
+        for (a, b) in read.meshes.iter().zip(skin.meshes.iter()) {
+            assert_eq!(a.submesh.name, b.submesh.name);
+            assert_eq!(a.hash, b.hash);
+            assert_eq!(a.submesh.indices_offset, b.submesh.indices_offset);
+            assert_eq!(a.submesh.indices_count, b.submesh.indices_count);
+        }
+    }
+}
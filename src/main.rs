@@ -15,8 +15,8 @@ use std::{
     env,
     fs::File,
     io::Read,
-    ops::{Div, Neg},
-    path::Path,
+    panic,
+    path::{Path, PathBuf},
     rc::Rc,
 };
 use gl::TRUE;
@@ -24,18 +24,21 @@ use glfw::ffi::{glfwSetWindowIcon};
 
 use imgui::StyleColor;
 
+mod cli;
 mod config_json;
 mod export;
+mod hot_reload;
+mod import;
+mod preset;
 
 mod g3d;
 mod gls;
 mod lol;
 
-use crate::config_json::MeshJson;
-use g3d::{Bones, Character, Floor, Joints, Model, Names, Refs, Shaders, Skybox};
+use g3d::{camera, Bones, Character, Floor, Joints, Model, Names, Refs, Shaders, Skybox};
 use gls::{
     imgui_layout::{self, AddModel},
-    ImguiGLFW, Screenshot, Texture,
+    ImguiGLFW, Recording, Screenshot, Texture, TextureBrowser,
 };
 use lol::{Animation, Skeleton, Skin};
 use image::load_from_memory;
@@ -43,18 +46,21 @@ use image::load_from_memory;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let mut config_path = "config.json";
-    if args.len() > 1 {
-        if args[1] == "--config" && args.len() > 2 {
-            config_path = &args[2];
-            println!("Using config file: {}", config_path);
-        }
+    if let Some(exit_code) = cli::run(&args) {
+        std::process::exit(exit_code);
     }
 
+    let viewer_args = cli::viewer::parse(&args[1..])
+        .unwrap_or_else(|error| std::process::exit(cli::report(error)));
+    println!("Using config file: {}", viewer_args.config_path);
+    // 当前场景文件，保存配置时写入这个文件，另存为和打开场景时改变
+    let mut scene_path = viewer_args.scene_path();
+
     let cargo_pkg_version = env!("CARGO_PKG_VERSION");
     let working_dir = env::current_dir().expect("Could not get current dir");
 
-    let mut config_json = config_json::ConfigJson::read(Path::new(config_path));
+    let (mut config_json, mut config_errors) = config_json::ConfigJson::read(Path::new(&viewer_args.config_path));
+    viewer_args.apply(&mut config_json);
     // 更新全局 CONFIG_JSON
     if let Ok(mut global_config) = CONFIG_JSON.lock() {
         *global_config = config_json.clone();
@@ -80,10 +86,13 @@ fn main() {
             monitor.expect("Could not get GLFW monitor").get_workarea()
         });
 
-    let (mut window_width, mut window_height) = (
-        (monitor_width as f32 * 0.75f32) as i32,
-        (monitor_height as f32 * 0.75f32) as i32,
-    );
+    let (mut window_width, mut window_height) = match viewer_args.window_size {
+        Some([width, height]) => (width, height),
+        None => (
+            (monitor_width as f32 * 0.75f32) as i32,
+            (monitor_height as f32 * 0.75f32) as i32,
+        ),
+    };
 
     let (mut window, events) = glfw
         .create_window(
@@ -125,6 +134,7 @@ fn main() {
     window.set_cursor_pos_polling(true);
     window.set_mouse_button_polling(true);
     window.set_framebuffer_size_polling(true);
+    window.set_drag_and_drop_polling(true);
 
     glfw.set_swap_interval(match config_json.vsync {
         true => glfw::SwapInterval::Sync(1),
@@ -164,32 +174,21 @@ fn main() {
     let refs = Refs::new(&shaders);
 
     let mut characters: Vec<Character> = Vec::with_capacity(config_json.paths.len());
-    let mut mind_models: Vec<MindModel> = Vec::with_capacity(config_json.paths.len());
-
-    for i in 0..config_json.paths.len() {
-        let json_config = JsonConfig {
-            meshes: &config_json.meshes[i],
-            selected_animation_path: config_json.paths[i].animations.to_owned(),
-        };
-
-        let add_model = AddModel {
-            name: config_json.paths[i].name.to_owned(),
-            skin: config_json.paths[i].skin.to_owned(),
-            skeleton: config_json.paths[i].skeleton.to_owned(),
-            textures: config_json.paths[i].textures.to_owned(),
-            animations: config_json.paths[i].animations.to_owned(),
-        };
-
-        mind_models.push(load_mind_model(
-            &add_model,
-            Some(json_config),
-            &mut characters,
-            &shaders,
-            &refs,
-        ));
-    }
+    let (mut mind_models, load_errors) = load_scene_models(&mut config_json, &mut characters, &shaders, &refs);
+    config_errors.extend(load_errors);
 
     let mut screenshot = Screenshot::new(use_samples, config_json.screen_shot_resolution);
+    screenshot.output_path = viewer_args.screenshot.clone();
+    let mut recording = Recording::new();
+    let mut texture_browser = TextureBrowser::new();
+    let mut asset_watcher = hot_reload::AssetWatcher::new();
+    let mut toasts: Vec<imgui_layout::Toast> = vec![];
+    let mut dropped_files: Vec<PathBuf> = vec![];
+    // 每个模型标题在窗口中的区域，拖放文件时用于确定目标模型
+    let mut model_header_rects: Vec<(usize, [f32; 4])> = vec![];
+    let mut open_texture_browser = false;
+    let mut scene_action: Option<imgui_layout::SceneAction> = None;
+    let mut preset_action: Option<imgui_layout::PresetAction> = None;
 
     let mut imgui_ctx = imgui::Context::create();
 
@@ -236,25 +235,18 @@ fn main() {
     let mut last_time = 0.0f32;
     let mut last_time_fps = 0.0f32;
 
-    let center_y = if !mind_models.is_empty() {
-        mind_models
-            .iter()
-            .map(|mind_model| mind_model.skin.center.y)
-            .sum::<f32>()
-            .div(mind_models.len() as f32)
-            .neg()
-    } else {
-        0.0f32
-    };
-
-    let fov = 45.0f32.to_radians();
-    let mut yaw_pitch = glam::vec2(90.0f32, 70.0f32);
-    let mut translation = glam::vec3(0.0f32, center_y, 0.0f32);
+    let fov = camera::FOV_DEGREES.to_radians();
+    let mut yaw_pitch = glam::Vec2::from(camera::YAW_PITCH);
+    let mut translation = camera::initial_translation(&mind_models);
 
-    let mut mouse = Mouse::new(500.0f32, [0.0f32, 0.0f32]);
+    let mut mouse = Mouse::new(camera::ZOOM, [0.0f32, 0.0f32]);
 
-    let mut export_as = 0;
-    let mut take_screenshot = false;
+    let mut export_options = export::ExportOptions::new();
+    let mut export_scene_animations = true;
+    let mut export_batch_animations = true;
+    // --screenshot 在第一帧截图
+    let mut take_screenshot = viewer_args.screenshot.is_some();
+    let mut exit_code = 0;
 
     let mut add_model = AddModel::new();
 
@@ -267,8 +259,13 @@ fn main() {
         if delta_time_fps >= 1.0f32 {
             window.set_title(
                 format!(
-                    "MindCorpViewer-Rust(杀死黑夜汉化版) v{} - Fps: {:1.0} / Ms: {:1.3}",
+                    "MindCorpViewer-Rust(杀死黑夜汉化版) v{} - {} - Fps: {:1.0} / Ms: {:1.3}",
                     cargo_pkg_version,
+                    scene_path
+                        .as_ref()
+                        .and_then(|x| x.file_name())
+                        .map(|x| x.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| String::from("未命名(Untitled)")),
                     frames / delta_time_fps,
                     1000.0f32 / frames
                 )
@@ -292,8 +289,64 @@ fn main() {
             &mut window_height,
             &mut mouse,
             &config_json,
+            &mut dropped_files,
         );
 
+        if !dropped_files.is_empty() {
+            let (cursor_x, cursor_y) = window.get_cursor_pos();
+            let (cursor_x, cursor_y) = (cursor_x as f32, cursor_y as f32);
+            // 拖放到模型标题上时添加到该模型，否则添加到最后一个模型
+            let target_model = model_header_rects
+                .iter()
+                .find(|(_, rect)| {
+                    cursor_x >= rect[0] && cursor_x <= rect[2] && cursor_y >= rect[1] && cursor_y <= rect[3]
+                })
+                .map(|(i, _)| *i)
+                .or(mind_models.len().checked_sub(1));
+
+            for result in load_dropped_files(
+                std::mem::take(&mut dropped_files),
+                target_model,
+                &mut config_json,
+                &mut mind_models,
+                &mut characters,
+                &shaders,
+                &refs,
+            ) {
+                toasts.push(match result {
+                    Ok(message) => imgui_layout::Toast::info(message, current_time),
+                    Err(error) => {
+                        println!("{error}");
+                        imgui_layout::Toast::error(error, current_time)
+                    }
+                });
+            }
+        }
+
+        asset_watcher.sync(&config_json.paths);
+        for i in asset_watcher.poll(current_time) {
+            let name = config_json.paths[i].name.clone();
+            let toast = match reload_mind_model(
+                i,
+                &mut config_json,
+                &mut mind_models,
+                &mut characters,
+                &shaders,
+                &refs,
+            ) {
+                Ok(()) => {
+                    texture_browser.reload(i);
+                    imgui_layout::Toast::info(format!("已重新加载(Reloaded): {name}"), current_time)
+                }
+                Err(error) => {
+                    println!("{error}");
+                    imgui_layout::Toast::error(format!("重新加载失败(Reload failed): {name}: {error}"), current_time)
+                }
+            };
+            toasts.push(toast);
+            asset_watcher.mark_loaded(i);
+        }
+
         imgui_glfw.update_imgui(delta_time, &window, &mut imgui_ctx);
 
         let ui = imgui_ctx.new_frame();
@@ -312,6 +365,7 @@ fn main() {
                     &mut yaw_pitch,    // 传入相机旋转
                 );
 
+                model_header_rects.clear();
                 for i in 0..mind_models.len() {
                     let _model_id = ui.push_id_usize(i);
 
@@ -328,10 +382,10 @@ fn main() {
                         //     unsafe { gl::DeleteTextures(1, &texture.id) };
                         // }
                         // 3. 同步删除所有关联数据
+                        texture_browser.remove_model(i);
+                        recording.remove_model(i, &mut mind_models, &mut config_json.options);
                         mind_models.remove(i);
-                        config_json.paths.remove(i);
-                        config_json.options.remove(i);
-                        config_json.meshes.remove(i);
+                        config_json.remove_model(i);
                         characters.remove(i);
                         // mind_models.remove(i);
                         // config_json.paths.remove(i);
@@ -358,22 +412,77 @@ fn main() {
                     let _active_style = ui.push_style_color(StyleColor::HeaderActive, [0.6, 0.0, 0.0, 1.0]); // 深红
                     // 可选：配套文字颜色（白色更醒目）
                     let _text_style = ui.push_style_color(StyleColor::Text, [1.0, 1.0, 1.0, 1.0]); // 白字
-                    if let Some(_tree) = ui
+                    let tree = ui
                         .tree_node_config(&format!("{}    ", config_json.paths[i].name))
                         .flags(imgui::TreeNodeFlags::SPAN_AVAIL_WIDTH)
                         .flags(imgui::TreeNodeFlags::ALLOW_ITEM_OVERLAP)
                         .framed(true)
-                        .push()
-                    {
+                        .push();
+                    let (rect_min, rect_max) = (ui.item_rect_min(), ui.item_rect_max());
+                    model_header_rects.push((i, [rect_min[0], rect_min[1], rect_max[0], rect_max[1]]));
+                    if let Some(_tree) = tree {
                         {
                             let options = &mut config_json.options[i];
                             imgui_layout::model(
                                 ui,
                                 options,
                                 &mut mind_models[i],
-                                &mut export_as,
+                                &mut export_options,
                                 &config_json.paths[i].name,
+                                &mut open_texture_browser,
+                                &mut preset_action,
                             );
+                            if open_texture_browser {
+                                open_texture_browser = false;
+                                texture_browser.open(i);
+                            }
+                            match preset_action.take() {
+                                Some(imgui_layout::PresetAction::Import(path)) => {
+                                    let toast = match import_preset(
+                                        i,
+                                        &path,
+                                        &mut config_json,
+                                        &mut mind_models,
+                                        &mut characters,
+                                        &shaders,
+                                        &refs,
+                                    ) {
+                                        Ok(report) => {
+                                            texture_browser.reload(i);
+                                            preset_toast(&config_json.paths[i].name, &report, current_time)
+                                        }
+                                        Err(error) => {
+                                            println!("{error}");
+                                            imgui_layout::Toast::error(
+                                                format!("导入预设失败(Preset import failed): {error}"),
+                                                current_time,
+                                            )
+                                        }
+                                    };
+                                    toasts.push(toast);
+                                }
+                                Some(imgui_layout::PresetAction::Export(path)) => {
+                                    let preset = preset::PresetJson::from_model(
+                                        &config_json.paths[i],
+                                        &config_json.options[i],
+                                        &mind_models[i],
+                                    );
+                                    toasts.push(match preset.write(&path) {
+                                        Ok(path) => imgui_layout::Toast::info(
+                                            format!("已导出预设(Exported preset): {}", path.display()),
+                                            current_time,
+                                        ),
+                                        Err(error) => {
+                                            println!("{error}");
+                                            imgui_layout::Toast::error(
+                                                format!("导出预设失败(Preset export failed): {error}"),
+                                                current_time,
+                                            )
+                                        }
+                                    });
+                                }
+                                None => {}
+                            }
 
 
 
@@ -383,33 +492,46 @@ fn main() {
 
                 ui.separator();
 
-               
+                imgui_layout::export_scene(
+                    ui,
+                    &mut export_options,
+                    &mut export_scene_animations,
+                    &config_json,
+                    &mind_models,
+                );
 
+                imgui_layout::export_batch(
+                    ui,
+                    &mut export_options,
+                    &mut export_batch_animations,
+                    &config_json,
+                );
+
+                ui.separator();
 
                 imgui_layout::add_model(ui, &working_dir, &mut add_model, |add_model| {
-                    mind_models.push(load_mind_model(
-                        &add_model,
-                        None,
-                        &mut characters,
-                        &shaders,
-                        &refs,
-                    ));
-
-                    let name = if !add_model.name.is_empty() {
-                        add_model.name.to_owned()
-                    } else {
-                        String::from("model")
-                    };
-
-                    config_json.paths.push(config_json::PathJson {
-                        name,
-                        skin: add_model.skin.to_owned(),
-                        skeleton: add_model.skeleton.to_owned(),
-                        textures: add_model.textures.to_owned(),
-                        animations: add_model.animations.to_owned(),
-                    });
+                    // 损坏的文件或预设中错误的路径只提示，不退出
+                    match try_load_mind_model(&add_model, None, &mut characters, &shaders, &refs) {
+                        Ok(mind_model) => mind_models.push(mind_model),
+                        Err(error) => {
+                            println!("{error}");
+                            toasts.push(imgui_layout::Toast::error(
+                                format!("模型加载失败(Could not load model): {error}"),
+                                current_time,
+                            ));
+                            return;
+                        }
+                    }
+
+                    config_json.paths.push(add_model.path_json());
                     config_json.options.push(config_json::OptionsJson::new());
                     config_json.meshes.push(vec![]);
+
+                    if let Some(preset) = add_model.preset.take() {
+                        let index = mind_models.len() - 1;
+                        let report = preset.apply(&mut config_json.options[index], &mut mind_models[index]);
+                        toasts.push(preset_toast(&config_json.paths[index].name, &report, current_time));
+                    }
                 }, /* &mut ConfigJson */);
 
                 ui.separator();
@@ -424,23 +546,93 @@ fn main() {
 
                 ui.separator();
 
-                if ui.button_with_size("保存配置(Save Configuration)", [ui.content_region_avail()[0], 0.0f32])
-                {
-                    config_json.write(&mind_models);
-                }
+                imgui_layout::recording(ui, &mut recording, &mut config_json, &mut mind_models);
+
+                ui.separator();
+
+                ui.checkbox("文件改变时自动重新加载(Hot Reload)", &mut asset_watcher.enabled);
+
+                ui.separator();
+
+                scene_action = imgui_layout::scene(ui, scene_path.as_deref());
             });
 
+        match scene_action.take() {
+            Some(imgui_layout::SceneAction::Save) => {
+                if let Some(scene_path) = &scene_path {
+                    toasts.push(save_scene(scene_path, &config_json, &mind_models, current_time));
+                }
+            }
+            Some(imgui_layout::SceneAction::SaveAs(path)) => {
+                let toast = save_scene(&path, &config_json, &mind_models, current_time);
+                if !toast.error {
+                    scene_path = Some(path);
+                }
+                toasts.push(toast);
+            }
+            Some(imgui_layout::SceneAction::Open(path)) => {
+                // 卸载当前场景的模型后加载新场景，命令行参数只作用于启动时的场景
+                recording.stop(&mut mind_models, &mut config_json.options);
+                texture_browser.opened = false;
+                mind_models.clear();
+                characters.clear();
+
+                let (new_config_json, errors) = ConfigJson::read(&path);
+                config_json = new_config_json;
+                config_errors.extend(errors);
+                let (new_mind_models, load_errors) =
+                    load_scene_models(&mut config_json, &mut characters, &shaders, &refs);
+                mind_models = new_mind_models;
+                config_errors.extend(load_errors);
+
+                glfw.set_swap_interval(match config_json.vsync {
+                    true => glfw::SwapInterval::Sync(1),
+                    false => glfw::SwapInterval::None,
+                });
+                screenshot.resolution = config_json.screen_shot_resolution;
+                translation = camera::initial_translation(&mind_models);
+                yaw_pitch = glam::Vec2::from(camera::YAW_PITCH);
+
+                toasts.push(imgui_layout::Toast::info(
+                    format!("已打开场景(Opened scene): {}", path.display()),
+                    current_time,
+                ));
+                scene_path = Some(path);
+            }
+            None => {}
+        }
+
+        imgui_layout::texture_browser(ui, &mut texture_browser, &config_json, &mind_models);
+        imgui_layout::toasts(ui, &mut toasts, current_time);
+        imgui_layout::config_errors(ui, &mut config_errors);
+
+        // 录制时每一帧都截图，动画按固定帧率步进
+        if recording.is_active() {
+            recording.prepare_frame(&mut mind_models, &mut config_json.options);
+        }
+        if recording.is_active() {
+            take_screenshot = true;
+            screenshot.use_samples = use_samples;
+            screenshot.resolution = recording.resolution;
+        }
+        let animation_delta_time = if recording.is_active() {
+            recording.frame_delta_time()
+        } else {
+            delta_time
+        };
+
         if take_screenshot {
             screenshot.update();
         }
 
-        let (view_matrix, camera_pos) =
+        let (mut view_matrix, mut camera_pos) =
             compute_matrix_from_inputs(&mut translation, &mut yaw_pitch, &mut mouse, &config_json);
-        let projection_matrix = glam::Mat4::perspective_infinite_rh(
-            fov,
-            window_width as f32 / window_height as f32,
-            0.1f32,
-        );
+        if recording.is_active() {
+            let turntable_matrix = recording.turntable_matrix();
+            view_matrix *= turntable_matrix;
+            camera_pos = turntable_matrix.inverse().transform_point3(camera_pos);
+        }
+        let projection_matrix = camera::projection_matrix(window_width as f32, window_height as f32);
         let projection_view_matrix = projection_matrix * view_matrix;
 
         unsafe {
@@ -518,7 +710,9 @@ fn main() {
         };
 
         for i in 0..mind_models.len() {
-            let animation_synchronized_time = if config_json.synchronized_time && i != 0 {
+            // 录制中的模型按录制的固定帧时间播放，不与第一个模型同步
+            let recorded_model = recording.is_active() && recording.model == i;
+            let animation_synchronized_time = if config_json.synchronized_time && i != 0 && !recorded_model {
                 Some(config_json.options[0].animation_time)
             } else {
                 None
@@ -530,7 +724,7 @@ fn main() {
                 let character = &mut characters[i];
                 let mind_model = &mut mind_models[i];
 
-                play_animation(options, mind_model, delta_time, animation_synchronized_time);
+                play_animation(options, mind_model, animation_delta_time, animation_synchronized_time);
 
                 character
                     .model
@@ -565,9 +759,18 @@ fn main() {
             }
         }
 
-        if take_screenshot {
+        if take_screenshot && recording.is_active() {
+            take_screenshot = false;
+            let pixels = screenshot.read_pixels([window_width, window_height]);
+            if let Some(Err(error)) = recording.push_frame(pixels, &mut mind_models, &mut config_json.options) {
+                eprintln!("{error}");
+            }
+        } else if take_screenshot {
             take_screenshot = false;
-            screenshot.save([window_width, window_height]);
+            if let Err(error) = screenshot.save([window_width, window_height]) {
+                eprintln!("{error}");
+                exit_code = cli::EXIT_FAILURE;
+            }
         }
 
         unsafe {
@@ -577,6 +780,14 @@ fn main() {
         }
 
         window.swap_buffers();
+
+        if viewer_args.exit {
+            window.set_should_close(true);
+        }
+    }
+
+    if exit_code != 0 {
+        std::process::exit(exit_code);
     }
 }
 
@@ -603,15 +814,387 @@ pub struct MindModel {
     pub export_animations: Option<bool>, // 新增字段，用于保存导出动画的状态
 }
 
-fn load_mind_model(
+#[cfg(test)]
+impl MindModel {
+    // 没有网格和骨骼的模型，测试中按需填充
+    pub fn empty() -> MindModel {
+        MindModel {
+            skin: Skin {
+                major: 4,
+                minor: 1,
+                center: glam::Vec3::ZERO,
+                bounding_box: [glam::Vec3::ZERO; 2],
+                vertices: vec![],
+                normals: vec![],
+                uvs: vec![],
+                influences: vec![],
+                weights: vec![],
+                indices: vec![],
+                meshes: vec![],
+            },
+            skeleton: Skeleton {
+                stype: lol::skl::Type::Version2,
+                version: 0,
+                joints: vec![],
+                influences: vec![],
+            },
+            show_meshes: vec![],
+            joints_transforms: vec![],
+            textures: vec![],
+            textures_paths: vec![],
+            textures_selecteds: vec![],
+            textures_file_names: vec![],
+            animations: vec![],
+            animation_selected: 0,
+            animations_file_names: vec![],
+            export_animations: None,
+        }
+    }
+}
+
+fn try_load_mind_model(
     add_model: &AddModel,
     config_json: Option<JsonConfig>,
     characters: &mut Vec<Character>,
     shaders: &Shaders,
     refs: &Refs,
-) -> MindModel {
-    let mut skin = Skin::read(&read_to_u8(Path::new(&add_model.skin)));
-    let skeleton = Skeleton::read(&read_to_u8(Path::new(&add_model.skeleton)));
+) -> Result<MindModel, String> {
+    let mut mind_model = read_mind_model(add_model, config_json)?;
+
+    mind_model.textures = load_textures(&mind_model.textures_paths)?;
+    characters.push(create_character(&mind_model, shaders, refs));
+
+    Ok(mind_model)
+}
+
+// 加载场景中的所有模型，加载失败的模型移到 unloaded 中保存时原样写回，返回的错误在窗口中提示
+fn load_scene_models(
+    config_json: &mut ConfigJson,
+    characters: &mut Vec<Character>,
+    shaders: &Shaders,
+    refs: &Refs,
+) -> (Vec<MindModel>, Vec<String>) {
+    let mut mind_models = Vec::with_capacity(config_json.paths.len());
+    let mut errors = vec![];
+
+    let mut i = 0;
+    while i < config_json.paths.len() {
+        let result = read_config_model(config_json, i).and_then(|mut mind_model| {
+            mind_model.textures = load_textures(&mind_model.textures_paths)?;
+            Ok(mind_model)
+        });
+
+        match result {
+            Ok(mind_model) => {
+                characters.push(create_character(&mind_model, shaders, refs));
+                mind_models.push(mind_model);
+                i += 1;
+            }
+            Err(error) => {
+                println!("{error}");
+                errors.push(format!(
+                    "模型加载失败，保存时原样保留(Could not load model, it is kept unchanged on save) {}: {error}",
+                    config_json.paths[i].name
+                ));
+                let index = i + config_json.unloaded.len();
+                config_json.unloaded.push(config_json::UnloadedModel {
+                    index,
+                    path: config_json.paths.remove(i),
+                    options: config_json.options.remove(i),
+                    meshes: config_json.meshes.remove(i),
+                });
+            }
+        }
+    }
+
+    (mind_models, errors)
+}
+
+fn save_scene(path: &Path, config_json: &ConfigJson, mind_models: &[MindModel], current_time: f32) -> imgui_layout::Toast {
+    match config_json.write(path, mind_models) {
+        Ok(()) => imgui_layout::Toast::info(format!("已保存场景(Saved scene): {}", path.display()), current_time),
+        Err(error) => {
+            println!("{error}");
+            imgui_layout::Toast::error(format!("保存失败(Save failed): {error}"), current_time)
+        }
+    }
+}
+
+// 把预设应用到已加载的模型，预设的文件与模型不同时先按预设的文件重新加载
+fn import_preset(
+    index: usize,
+    path: &Path,
+    config_json: &mut ConfigJson,
+    mind_models: &mut [MindModel],
+    characters: &mut [Character],
+    shaders: &Shaders,
+    refs: &Refs,
+) -> Result<preset::PresetReport, String> {
+    let preset = preset::PresetJson::read(path)?;
+
+    if preset.changes_files(&config_json.paths[index]) {
+        let old_path = std::mem::replace(&mut config_json.paths[index], preset.path.clone());
+        if let Err(error) = reload_mind_model(index, config_json, mind_models, characters, shaders, refs) {
+            config_json.paths[index] = old_path;
+            return Err(error);
+        }
+    }
+
+    Ok(preset.apply(&mut config_json.options[index], &mut mind_models[index]))
+}
+
+// 有未匹配的子网格、缺少的纹理或未使用的条目时以错误样式显示
+fn preset_toast(name: &str, report: &preset::PresetReport, current_time: f32) -> imgui_layout::Toast {
+    let lines = report.lines();
+    for line in lines.iter() {
+        println!("{line}");
+    }
+    let message = format!("已应用预设(Applied preset): {name}\n{}", lines.join("\n"));
+    if report.is_complete() {
+        imgui_layout::Toast::info(message, current_time)
+    } else {
+        imgui_layout::Toast::error(message, current_time)
+    }
+}
+
+// 拖放的文件和目录：skn 按同目录推导其他路径后添加为新模型，目录添加其中所有的 skn，
+// anm 和 dds/tex 添加到目标模型的动画和纹理列表，每个文件返回一条结果用于提示
+fn load_dropped_files(
+    paths: Vec<PathBuf>,
+    target_model: Option<usize>,
+    config_json: &mut ConfigJson,
+    mind_models: &mut Vec<MindModel>,
+    characters: &mut Vec<Character>,
+    shaders: &Shaders,
+    refs: &Refs,
+) -> Vec<Result<String, String>> {
+    let mut results = vec![];
+    let mut skins = vec![];
+
+    for path in paths {
+        let extension = path
+            .extension()
+            .and_then(|x| x.to_str())
+            .map(|x| x.to_ascii_lowercase())
+            .unwrap_or_default();
+
+        if path.is_dir() {
+            let pattern = format!("{}/**/*.skn", glob::Pattern::escape(&path.to_string_lossy()));
+            let found: Vec<PathBuf> = match glob::glob(&pattern) {
+                Ok(paths) => paths.filter_map(Result::ok).collect(),
+                Err(error) => {
+                    results.push(Err(format!("Failed to read glob skn pattern: {error}")));
+                    continue;
+                }
+            };
+            if found.is_empty() {
+                results.push(Err(format!("目录中没有 skn 文件(No skn files in): {}", path.display())));
+            }
+            skins.extend(found);
+            continue;
+        }
+
+        match extension.as_str() {
+            "skn" => skins.push(path),
+            "anm" | "dds" | "tex" => {
+                let Some(target_model) = target_model else {
+                    results.push(Err(format!(
+                        "请先添加模型(Add a model first): {}",
+                        path.display()
+                    )));
+                    continue;
+                };
+                let path_json = &mut config_json.paths[target_model];
+                let result = if extension == "anm" {
+                    add_dropped_animation(&path, &mut mind_models[target_model])
+                        .inspect(|_| add_extra_file(&mut path_json.extra_animations, &path_json.animations, &path))
+                } else {
+                    add_dropped_texture(&path, &mut mind_models[target_model])
+                        .inspect(|_| add_extra_file(&mut path_json.extra_textures, &path_json.textures, &path))
+                };
+                results.push(result.map(|name| format!("{name} -> {}", config_json.paths[target_model].name)));
+            }
+            _ => results.push(Err(format!("不支持的文件(Unsupported file): {}", path.display()))),
+        }
+    }
+
+    for skin in skins {
+        let add_model = AddModel::from_skin(&skin);
+        if add_model.skeleton.is_empty() {
+            results.push(Err(format!("找不到骨骼文件(No skeleton found for): {}", skin.display())));
+            continue;
+        }
+
+        match try_load_mind_model(&add_model, None, characters, shaders, refs) {
+            Ok(mind_model) => {
+                mind_models.push(mind_model);
+                config_json.paths.push(add_model.path_json());
+                config_json.options.push(config_json::OptionsJson::new());
+                config_json.meshes.push(vec![]);
+                results.push(Ok(format!(
+                    "已添加模型(Model added): {}",
+                    config_json.paths[config_json.paths.len() - 1].name
+                )));
+            }
+            Err(error) => results.push(Err(error)),
+        }
+    }
+
+    results
+}
+
+// 记录拖放的文件，重新加载和保存场景时使用，模型目录中的文件不需要记录
+fn add_extra_file(extra_files: &mut Vec<String>, folder: &str, path: &Path) {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let in_folder = !folder.is_empty()
+        && path.parent().zip(Path::new(folder).canonicalize().ok()).is_some_and(|(parent, folder)| parent == folder);
+
+    let stem = path.file_stem();
+    extra_files.retain(|x| Path::new(x).file_stem() != stem);
+    if !in_folder {
+        extra_files.push(path.to_string_lossy().into_owned());
+    }
+}
+
+// 同名的动画会被替换，不会写入动画目录
+fn add_dropped_animation(path: &Path, mind_model: &mut MindModel) -> Result<String, String> {
+    let animation = parse_file(path, Animation::read)?;
+    let name = path.file_stem().and_then(|x| x.to_str()).unwrap_or("animation").to_owned();
+
+    match mind_model.animations_file_names.iter().position(|x| *x == name) {
+        Some(position) => mind_model.animations[position] = animation,
+        None => {
+            mind_model.animations.push(animation);
+            mind_model.animations_file_names.push(name.clone());
+        }
+    }
+    Ok(format!("已添加动画(Animation added): {name}"))
+}
+
+fn add_dropped_texture(path: &Path, mind_model: &mut MindModel) -> Result<String, String> {
+    let path_string = path.to_string_lossy().into_owned();
+    let texture = load_textures(std::slice::from_ref(&path_string))?.remove(0);
+    let name = path.file_stem().and_then(|x| x.to_str()).unwrap_or("texture").to_owned();
+
+    match mind_model.textures_file_names.iter().position(|x| *x == name) {
+        Some(position) => {
+            mind_model.textures[position] = texture;
+            mind_model.textures_paths[position] = path_string;
+        }
+        None => {
+            mind_model.textures.push(texture);
+            mind_model.textures_paths.push(path_string);
+            mind_model.textures_file_names.push(name.clone());
+        }
+    }
+    Ok(format!("已添加纹理(Texture added): {name}"))
+}
+
+fn create_character(mind_model: &MindModel, shaders: &Shaders, refs: &Refs) -> Character {
+    let mut model = Model::create(&mind_model.skin, Rc::clone(&shaders.model));
+    let mut names = Names::create(&mind_model.skeleton, Rc::clone(&shaders.names));
+    let mut bones = Bones::create(&mind_model.skeleton, Rc::clone(&shaders.bones));
+    let mut joints = Joints::create(&mind_model.skeleton, Rc::clone(&shaders.joints));
+
+    model.set_shader_refs(&refs.model, refs.model_ubo);
+    names.set_shader_refs(&refs.names);
+    bones.set_shader_refs(&refs.bones);
+    joints.set_shader_refs(&refs.joints);
+
+    Character {
+        bones,
+        joints,
+        model,
+        names,
+    }
+}
+
+// 纹理解码失败时会 panic，这里转换为错误信息
+fn load_textures(textures_paths: &[String]) -> Result<Vec<Texture>, String> {
+    let mut textures = Vec::with_capacity(textures_paths.len());
+    for path in textures_paths.iter() {
+        let texture = panic::catch_unwind(|| Texture::load_texture(&read_to_u8(Path::new(path))))
+            .map_err(|_| format!("Could not load texture {path}"))?;
+        textures.push(texture);
+    }
+    Ok(textures)
+}
+
+// 文件在磁盘上改变后重新读取模型，按名称保留网格显示、纹理选择和当前动画，
+// OptionsJson 不变，新模型没有动画时关闭动画
+fn reload_mind_model(
+    index: usize,
+    config_json: &mut ConfigJson,
+    mind_models: &mut [MindModel],
+    characters: &mut [Character],
+    shaders: &Shaders,
+    refs: &Refs,
+) -> Result<(), String> {
+    let path = &config_json.paths[index];
+    let add_model = AddModel::from_path(path);
+
+    let mut mind_model = read_mind_model(&add_model, None)?;
+    let old_model = &mind_models[index];
+
+    for (j, mesh) in mind_model.skin.meshes.iter().enumerate() {
+        let Some(old_j) = old_model
+            .skin
+            .meshes
+            .iter()
+            .position(|x| x.submesh.name == mesh.submesh.name)
+        else {
+            continue;
+        };
+        mind_model.show_meshes[j] = old_model.show_meshes[old_j];
+        if let Some(texture_position) = old_model
+            .textures_file_names
+            .get(old_model.textures_selecteds[old_j])
+            .and_then(|name| mind_model.textures_file_names.iter().position(|x| x == name))
+        {
+            mind_model.textures_selecteds[j] = texture_position;
+        }
+    }
+
+    if let Some(animation_position) = old_model
+        .animations_file_names
+        .get(old_model.animation_selected)
+        .and_then(|name| mind_model.animations_file_names.iter().position(|x| x == name))
+    {
+        mind_model.animation_selected = animation_position;
+    }
+    mind_model.export_animations = old_model.export_animations;
+
+    mind_model.textures = load_textures(&mind_model.textures_paths)?;
+    let character = create_character(&mind_model, shaders, refs);
+
+    if mind_model.animations.is_empty() {
+        config_json.options[index].use_animation = false;
+    }
+
+    mind_models[index] = mind_model;
+    characters[index] = character;
+    Ok(())
+}
+
+// 只读取模型数据，不创建任何 GL 资源，命令行导出也使用这里
+pub fn read_mind_model(add_model: &AddModel, config_json: Option<JsonConfig>) -> Result<MindModel, String> {
+    let mut skin = parse_file(Path::new(&add_model.skin), Skin::read)?;
+    let skeleton = parse_file(Path::new(&add_model.skeleton), Skeleton::read)?;
+
+    println!(
+        "SKN version {} {} was succesfully loaded: SubMeshHeader count: {} indices count: {} vertex count: {}",
+        skin.major,
+        skin.minor,
+        skin.meshes.len(),
+        skin.indices.len(),
+        skin.vertices.len()
+    );
+    println!(
+        "SKL version {} was succesfully loaded: Joints count: {} Joints influences: {}",
+        skeleton.version,
+        skeleton.joints.len(),
+        skeleton.influences.len()
+    );
 
     skin.apply_skeleton(&skeleton);
 
@@ -630,72 +1213,44 @@ fn load_mind_model(
         }
     }
 
-    let dds_paths = glob::glob(format!("{}/*.dds", add_model.textures).as_str())
-        .expect("Failed to read glob dds pattern")
-        .filter_map(Result::ok);
-
-    let tex_paths = glob::glob(format!("{}/*.tex", add_model.textures).as_str())
-        .expect("Failed to read glob tex pattern")
-        .filter_map(Result::ok);
-
     let mut textures_paths = vec![];
     let mut textures_file_names = vec![];
 
-    for path in dds_paths.chain(tex_paths) {
-        textures_paths.push(String::from(path.to_str().unwrap()));
-        textures_file_names.push(String::from(path.file_stem().unwrap().to_str().unwrap()));
-    }
+    // 未指定目录时不搜索，避免匹配到根目录
+    if !add_model.textures.is_empty() {
+        let dds_paths = glob::glob(format!("{}/*.dds", add_model.textures).as_str())
+            .map_err(|error| format!("Failed to read glob dds pattern: {error}"))?
+            .filter_map(Result::ok);
 
-    let mut textures = vec![];
-    for path in textures_paths.iter() {
-        textures.push(Texture::load_texture(&read_to_u8(Path::new(path))));
-    }
+        let tex_paths = glob::glob(format!("{}/*.tex", add_model.textures).as_str())
+            .map_err(|error| format!("Failed to read glob tex pattern: {error}"))?
+            .filter_map(Result::ok);
 
-    let mut textures_selecteds: Vec<usize> = vec![0; skin.meshes.len()];
-    // 首先检查是否有临时MESHES文件
-    if let Ok(meshes_content) = std::fs::read_to_string("temp_meshes.json") {
-        println!("Loaded temp_meshes.json");
-        if let Ok(meshes) = serde_json::from_str::<Vec<MeshJson>>(&meshes_content) {
-            for (j, mesh) in skin.meshes.iter().enumerate() {
-                let mesh_name = &mesh.submesh.name;
-
-                // 1. 查找匹配的配置项
-                if let Some(mesh_json) = meshes.iter().find(|x| x.name_texture.contains_key(mesh_name)) {
-                    // 2. 获取对应的纹理名称
-                    if let Some(texture_name) = mesh_json.name_texture.get(mesh_name) {
-                        // 3. 查找纹理在列表中的位置
-                        if let Some(texture_position) = textures_file_names.iter().position(|x| x == texture_name) {
-                            textures_selecteds[j] = texture_position;
-                            println!("为网格 '{}' 设置纹理索引: {} (纹理: {})",
-                                     mesh_name, texture_position, texture_name);
-                        } else {
-                            println!("警告: 纹理 '{}' 未在加载列表中找到", texture_name);
-                        }
-                    }
-
-                    // 4. 设置显示状态
-                    show_meshes[j] = mesh_json.show;
-                    println!("设置网格 '{}' 可见性为 {}", mesh_name, mesh_json.show);
-                } else {
-                    println!("警告: 未找到网格 '{}' 的配置", mesh_name);
-                }
-            }
+        for path in dds_paths.chain(tex_paths) {
+            textures_paths.push(String::from(path.to_str().unwrap()));
+            textures_file_names.push(String::from(path.file_stem().unwrap().to_str().unwrap()));
+        }
+    }
 
-            for (j, mesh) in skin.meshes.iter_mut().enumerate() {
-                mesh.submesh.material_index = textures_selecteds[j];
-                println!("网格 {} -> 材质索引: {}",
-                         mesh.submesh.name, mesh.submesh.material_index);
+    // 拖放的纹理，同名时替换目录中的纹理
+    for path in add_model.extra_textures.iter().map(Path::new) {
+        if !path.is_file() {
+            println!("Dropped texture not found: {}", path.display());
+            continue;
+        }
+        let name = path.file_stem().and_then(|x| x.to_str()).unwrap_or("texture").to_owned();
+        let path_string = path.to_string_lossy().into_owned();
+        match textures_file_names.iter().position(|x| *x == name) {
+            Some(position) => textures_paths[position] = path_string,
+            None => {
+                textures_paths.push(path_string);
+                textures_file_names.push(name);
             }
-
-        } else {
-            println!("错误: 无法解析meshes配置");
         }
-
-        // 删除临时文件
-        let _ = std::fs::remove_file("temp_meshes.json");
     }
-    // 仍然保留原有配置的处理
-    else if let Some(config_json) = &config_json {
+
+    let mut textures_selecteds: Vec<usize> = vec![0; skin.meshes.len()];
+    if let Some(config_json) = &config_json {
         for j in 0..skin.meshes.len() {
             if let Some(mesh_json) = config_json
                 .meshes
@@ -712,16 +1267,35 @@ fn load_mind_model(
         }
     }
 
-    let animations_paths = glob::glob(format!("{}/*.anm", add_model.animations).as_str())
-        .expect("Failed to read glob anm pattern")
-        .filter_map(Result::ok);
-
     let mut animations = vec![];
     let mut animations_file_names = vec![];
 
-    for path in animations_paths {
-        animations.push(Animation::read(&read_to_u8(&path)));
-        animations_file_names.push(String::from(path.file_stem().unwrap().to_str().unwrap()));
+    if !add_model.animations.is_empty() {
+        let animations_paths = glob::glob(format!("{}/*.anm", add_model.animations).as_str())
+            .map_err(|error| format!("Failed to read glob anm pattern: {error}"))?
+            .filter_map(Result::ok);
+
+        for path in animations_paths {
+            animations.push(parse_file(&path, Animation::read)?);
+            animations_file_names.push(String::from(path.file_stem().unwrap().to_str().unwrap()));
+        }
+    }
+
+    // 拖放的动画，同名时替换目录中的动画
+    for path in add_model.extra_animations.iter().map(Path::new) {
+        if !path.is_file() {
+            println!("Dropped animation not found: {}", path.display());
+            continue;
+        }
+        let animation = parse_file(path, Animation::read)?;
+        let name = path.file_stem().and_then(|x| x.to_str()).unwrap_or("animation").to_owned();
+        match animations_file_names.iter().position(|x| *x == name) {
+            Some(position) => animations[position] = animation,
+            None => {
+                animations.push(animation);
+                animations_file_names.push(name);
+            }
+        }
     }
 
     let mut animation_selected = 0;
@@ -734,40 +1308,49 @@ fn load_mind_model(
         }
     }
 
-    let mut model = Model::create(&skin, Rc::clone(&shaders.model));
-    let mut names = Names::create(&skeleton, Rc::clone(&shaders.names));
-    let mut bones = Bones::create(&skeleton, Rc::clone(&shaders.bones));
-    let mut joints = Joints::create(&skeleton, Rc::clone(&shaders.joints));
-
-    model.set_shader_refs(&refs.model, refs.model_ubo);
-    names.set_shader_refs(&refs.names);
-    bones.set_shader_refs(&refs.bones);
-    joints.set_shader_refs(&refs.joints);
-
-    characters.push(Character {
-        bones,
-        joints,
-        model,
-        names,
-    });
-
-
-
-    // 然后创建MindModel实例
-    MindModel {
+    Ok(MindModel {
         skin,
         skeleton,
         animations,
         animations_file_names,
         animation_selected,
-        textures,
+        textures: vec![],
         textures_paths,
         textures_file_names,
         textures_selecteds,
         show_meshes,
         joints_transforms,
         export_animations: None,
-    }
+    })
+}
+
+// 按配置文件中第 index 个模型的路径、网格和动画设置读取模型
+pub fn read_config_model(config_json: &ConfigJson, index: usize) -> Result<MindModel, String> {
+    let path = &config_json.paths[index];
+    let add_model = AddModel::from_path(path);
+    let json_config = JsonConfig {
+        meshes: config_json.meshes.get(index).map(Vec::as_slice).unwrap_or(&[]),
+        selected_animation_path: config_json
+            .options
+            .get(index)
+            .map(|x| x.selected_animation_path.clone())
+            .unwrap_or_default(),
+    };
+    read_mind_model(&add_model, Some(json_config))
+}
+
+// 解析器遇到损坏的文件会 panic，这里转换为错误信息
+fn parse_file<T>(path: &Path, parse: fn(&Vec<u8>) -> T) -> Result<T, String> {
+    let contents = std::fs::read(path)
+        .map_err(|error| format!("Could not read {}: {error}", path.display()))?;
+    panic::catch_unwind(|| parse(&contents)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|x| x.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("unknown error"));
+        format!("Could not parse {}: {message}", path.display())
+    })
 }
 struct Mouse {
     last_offset: [f32; 2],
@@ -798,7 +1381,7 @@ fn process_events(
     window_height: &mut i32,
     mouse: &mut Mouse,
     config: &ConfigJson, // 新增参数
-
+    dropped_files: &mut Vec<PathBuf>,
 ) {
     for (_, event) in glfw::flush_messages(events) {
         imgui_glfw.handle_event(imgui_ctx, &event);
@@ -840,6 +1423,7 @@ fn process_events(
             glfw::WindowEvent::Scroll(_, yoffset) => {
                 mouse.zoom -= yoffset as f32 * config.control_sensitivity.zoom;
             }
+            glfw::WindowEvent::FileDrop(paths) => dropped_files.extend(paths),
             _ => {}
         }
     }
@@ -866,7 +1450,6 @@ fn compute_matrix_from_inputs(
         .normalize();
 
     let right = position.cross(glam::Vec3::Y).normalize();
-    let up = right.cross(position).normalize();
 
     if mouse.state == 2 {
         translation.x -= right.x * (mouse.offset[0] * config.control_sensitivity.pan);
@@ -877,13 +1460,7 @@ fn compute_matrix_from_inputs(
     mouse.last_offset[0] = mouse.offset[0];
     mouse.last_offset[1] = mouse.offset[1];
 
-    let eye = position * mouse.zoom;
-    let camera_pos = eye - *translation;
-
-    let mut view_matrix = glam::Mat4::look_to_rh(eye, -eye, up);
-    view_matrix *= glam::Mat4::from_translation(*translation);
-
-    (view_matrix, camera_pos)
+    camera::view_matrix(*translation, *yaw_pitch, mouse.zoom)
 }
 
 fn play_animation(
@@ -0,0 +1,314 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config_json::{self, ConfigJson, MeshJson, OptionsJson, PathJson},
+    gls::imgui_layout::AddModel,
+    MindModel,
+};
+
+// 预设文件格式版本，格式改变时增加
+pub const PRESET_VERSION: u32 = 1;
+
+// 单个模型的预设：文件路径、显示选项和子网格与纹理的对应关系
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PresetJson {
+    #[serde(rename = "Version", default)]
+    pub version: u32,
+
+    #[serde(rename = "Path", default)]
+    pub path: PathJson,
+
+    #[serde(rename = "Options", default)]
+    pub options: OptionsJson,
+
+    #[serde(rename = "Meshes", default)]
+    pub meshes: Vec<MeshJson>,
+}
+
+// 应用预设的结果，子网格按名称匹配
+#[derive(Default)]
+pub struct PresetReport {
+    // 在预设中找到的子网格
+    pub matched: Vec<String>,
+    // 预设中没有的子网格，保持原来的设置
+    pub unmatched: Vec<String>,
+    // (子网格, 纹理) 纹理不在模型的纹理目录中
+    pub missing_textures: Vec<(String, String)>,
+    // 预设中没有对应子网格的条目
+    pub unused: Vec<String>,
+}
+
+impl PresetJson {
+    pub fn from_model(path: &PathJson, options: &OptionsJson, mind_model: &MindModel) -> PresetJson {
+        let mut options = options.clone();
+        options.selected_animation_path = config_json::selected_animation_name(mind_model);
+
+        PresetJson {
+            version: PRESET_VERSION,
+            path: path.clone(),
+            options,
+            meshes: config_json::model_meshes(mind_model),
+        }
+    }
+
+    // 也可以读取配置文件，此时使用其中的第一个模型
+    pub fn read(path: &Path) -> Result<PresetJson, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| format!("Could not read preset {}: {error}", path.display()))?;
+        let value: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|error| format!("Could not parse preset {}: {error}", path.display()))?;
+
+        if value.get("PATHS").is_some() {
+            let (config_json, _) = ConfigJson::parse(&contents)?;
+            let path_json = config_json
+                .paths
+                .first()
+                .ok_or_else(|| format!("No models listed in {}", path.display()))?;
+            return Ok(PresetJson {
+                version: PRESET_VERSION,
+                path: path_json.clone(),
+                options: config_json.options[0].clone(),
+                meshes: config_json.meshes[0].clone(),
+            });
+        }
+
+        // 所有字段都有默认值，任意 JSON 对象都能解析，至少需要一个预设的字段
+        if !["Path", "Options", "Meshes"].iter().any(|key| value.get(key).is_some()) {
+            return Err(format!(
+                "{} is not a preset or config file (no Path, Options or Meshes)",
+                path.display()
+            ));
+        }
+
+        let preset: PresetJson = serde_json::from_value(value)
+            .map_err(|error| format!("Could not parse preset {}: {error}", path.display()))?;
+        if preset.version > PRESET_VERSION {
+            return Err(format!(
+                "Preset {} is from a newer version ({} > {PRESET_VERSION})",
+                path.display(),
+                preset.version
+            ));
+        }
+        Ok(preset)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<PathBuf, String> {
+        let contents =
+            config_json::pretty_json(self).map_err(|error| format!("Could not serialize preset: {error}"))?;
+        std::fs::write(path, contents)
+            .map_err(|error| format!("Could not write preset {}: {error}", path.display()))?;
+        println!("Preset saved to {}", path.display());
+        Ok(path.to_path_buf())
+    }
+
+    pub fn add_model(&self) -> AddModel {
+        AddModel {
+            preset: Some(self.clone()),
+            ..AddModel::from_path(&self.path)
+        }
+    }
+
+    // 预设指定了与模型不同的文件时需要重新加载模型
+    pub fn changes_files(&self, path: &PathJson) -> bool {
+        !self.path.skin.is_empty()
+            && (self.path.skin != path.skin
+                || self.path.skeleton != path.skeleton
+                || self.path.textures != path.textures
+                || self.path.animations != path.animations
+                || self.path.extra_textures != path.extra_textures
+                || self.path.extra_animations != path.extra_animations)
+    }
+
+    // 应用显示选项、选中的动画和子网格设置
+    pub fn apply(&self, options: &mut OptionsJson, mind_model: &mut MindModel) -> PresetReport {
+        *options = self.options.clone();
+        if let Some(animation_position) = mind_model
+            .animations_file_names
+            .iter()
+            .position(|x| *x == self.options.selected_animation_path)
+        {
+            mind_model.animation_selected = animation_position;
+        }
+        if mind_model.animations.is_empty() {
+            options.use_animation = false;
+        }
+
+        self.apply_meshes(mind_model)
+    }
+
+    pub fn apply_meshes(&self, mind_model: &mut MindModel) -> PresetReport {
+        let mut report = PresetReport::default();
+        let mut used = BTreeSet::new();
+
+        for (j, mesh) in mind_model.skin.meshes.iter_mut().enumerate() {
+            let mesh_name = &mesh.submesh.name;
+            let Some((mesh_json, texture_name)) = self
+                .meshes
+                .iter()
+                .find_map(|x| x.name_texture.get(mesh_name).map(|texture| (x, texture)))
+            else {
+                report.unmatched.push(mesh_name.to_owned());
+                continue;
+            };
+
+            used.insert(mesh_name.to_owned());
+            report.matched.push(mesh_name.to_owned());
+            mind_model.show_meshes[j] = mesh_json.show;
+
+            // 模型没有纹理时保存的纹理名称为空
+            if texture_name.is_empty() {
+                continue;
+            }
+            match mind_model.textures_file_names.iter().position(|x| x == texture_name) {
+                Some(texture_position) => {
+                    mind_model.textures_selecteds[j] = texture_position;
+                    mesh.submesh.material_index = texture_position;
+                }
+                None => report
+                    .missing_textures
+                    .push((mesh_name.to_owned(), texture_name.to_owned())),
+            }
+        }
+
+        for mesh_json in self.meshes.iter() {
+            for name in mesh_json.name_texture.keys() {
+                if !used.contains(name) && !report.unused.contains(name) {
+                    report.unused.push(name.to_owned());
+                }
+            }
+        }
+
+        report
+    }
+}
+
+impl PresetReport {
+    pub fn is_complete(&self) -> bool {
+        self.unmatched.is_empty() && self.missing_textures.is_empty() && self.unused.is_empty()
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "匹配的子网格(Matched submeshes) {}/{}: {}",
+            self.matched.len(),
+            self.matched.len() + self.unmatched.len(),
+            self.matched.join(", ")
+        )];
+        if !self.unmatched.is_empty() {
+            lines.push(format!(
+                "预设中没有的子网格(Submeshes not in the preset): {}",
+                self.unmatched.join(", ")
+            ));
+        }
+        if !self.missing_textures.is_empty() {
+            let missing_textures: Vec<String> = self
+                .missing_textures
+                .iter()
+                .map(|(mesh, texture)| format!("{mesh} -> {texture}"))
+                .collect();
+            lines.push(format!(
+                "缺少的纹理(Missing textures): {}",
+                missing_textures.join(", ")
+            ));
+        }
+        if !self.unused.is_empty() {
+            lines.push(format!("未使用的条目(Unused entries): {}", self.unused.join(", ")));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::lol::skn::{Mesh, SubMeshHeader};
+
+    fn read_str(name: &str, contents: &str) -> Result<PresetJson, String> {
+        let path = std::env::temp_dir().join(format!("preset_{name}_{}.json", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        let result = PresetJson::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        result
+    }
+
+    fn mesh_json(show: bool, name: &str, texture: &str) -> MeshJson {
+        MeshJson {
+            show,
+            name_texture: BTreeMap::from([(String::from(name), String::from(texture))]),
+        }
+    }
+
+    #[test]
+    fn config_file_is_read_by_its_paths_key() {
+        let preset = read_str(
+            "config",
+            r#"{
+                "PATHS": [{ "Name": "first", "Skin": "first.skn" }, { "Name": "second" }],
+                "OPTIONS": [{ "AnimationTime": 1.5 }],
+                "MESHES": [[{ "Show": false, "Body": "skin" }]]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(preset.path.name, "first");
+        assert_eq!(preset.path.skin, "first.skn");
+        assert_eq!(preset.options.animation_time, 1.5f32);
+        assert_eq!(preset.meshes.len(), 1);
+        assert!(!preset.meshes[0].show);
+    }
+
+    #[test]
+    fn json_without_preset_fields_is_rejected() {
+        assert!(read_str("other", r#"{ "name": "package", "version": "1.0.0" }"#).is_err());
+        assert!(read_str("newer", &format!(r#"{{ "Version": {}, "Meshes": [] }}"#, PRESET_VERSION + 1)).is_err());
+        assert!(read_str("preset", r#"{ "Version": 1, "Meshes": [] }"#).is_ok());
+    }
+
+    #[test]
+    fn apply_meshes_reports_matches() {
+        let mut mind_model = MindModel::empty();
+        for name in ["Body", "Weapon", "Cape"] {
+            mind_model.skin.meshes.push(Mesh::new(SubMeshHeader {
+                name: String::from(name),
+                indices_offset: 0,
+                indices_count: 0,
+                material_index: 0,
+            }));
+        }
+        mind_model.show_meshes = vec![true; 3];
+        mind_model.textures_selecteds = vec![0; 3];
+        mind_model.textures_file_names = vec![String::from("base"), String::from("skin")];
+
+        let preset = PresetJson {
+            version: PRESET_VERSION,
+            path: PathJson::default(),
+            options: OptionsJson::new(),
+            meshes: vec![
+                mesh_json(false, "Body", "skin"),
+                mesh_json(true, "Weapon", "missing"),
+                mesh_json(true, "Shield", "base"),
+            ],
+        };
+
+        let report = preset.apply_meshes(&mut mind_model);
+
+        assert_eq!(report.matched, ["Body", "Weapon"]);
+        assert_eq!(report.unmatched, ["Cape"]);
+        assert_eq!(
+            report.missing_textures,
+            [(String::from("Weapon"), String::from("missing"))]
+        );
+        assert_eq!(report.unused, ["Shield"]);
+        assert!(!report.is_complete());
+
+        assert_eq!(mind_model.show_meshes, [false, true, true]);
+        assert_eq!(mind_model.textures_selecteds, [1, 0, 0]);
+    }
+}